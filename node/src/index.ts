@@ -7,6 +7,7 @@ export interface ClientOptions {
   username?: string;
   password?: string;
   isSecure?: boolean;
+  token?: string;
 }
 
 function applyDefaults(options: ClientOptions): ClientOptions {
@@ -15,11 +16,62 @@ function applyDefaults(options: ClientOptions): ClientOptions {
     username: options.username,
     password: options.password,
     isSecure: options.isSecure === undefined ? true : options.isSecure,
+    token: options.token,
+  };
+}
+
+/**
+ * Reads the client options from the environment, the same variables as
+ * `ClientBuilder::from_env` of the Rust client:
+ *
+ * - `SUPERCHAIN_ENDPOINT`, falling back to `SUPER_URL`
+ * - `SUPERCHAIN_USERNAME`, falling back to `SUPER_USERNAME`
+ * - `SUPERCHAIN_PASSWORD`, falling back to `SUPER_PASSWORD`
+ * - `SUPERCHAIN_SECURE`, `true` or `false`
+ * - `SUPERCHAIN_TOKEN`, used instead of username and password if set
+ *
+ * Throws if neither a token nor username and password are set.
+ */
+export function optionsFromEnv(env = process.env): ClientOptions {
+  const token = env.SUPERCHAIN_TOKEN;
+  const username = env.SUPERCHAIN_USERNAME || env.SUPER_USERNAME;
+  const password = env.SUPERCHAIN_PASSWORD || env.SUPER_PASSWORD;
+  if (!token && !username) {
+    throw new Error('missing environment variable SUPERCHAIN_USERNAME or SUPERCHAIN_TOKEN');
+  }
+  if (!token && !password) {
+    throw new Error('missing environment variable SUPERCHAIN_PASSWORD');
+  }
+
+  let isSecure: boolean | undefined;
+  switch (env.SUPERCHAIN_SECURE?.toLowerCase()) {
+    case undefined:
+    case '':
+      break;
+    case 'true':
+    case '1':
+      isSecure = true;
+      break;
+    case 'false':
+    case '0':
+      isSecure = false;
+      break;
+    default:
+      throw new Error(`invalid value of SUPERCHAIN_SECURE: ${env.SUPERCHAIN_SECURE}`);
+  }
+
+  return {
+    endpoint: env.SUPERCHAIN_ENDPOINT || env.SUPER_URL,
+    username: token ? undefined : username,
+    password: token ? undefined : password,
+    isSecure,
+    token,
   };
 }
 
 export class Client {
   endpoint: string;
+  headers: Record<string, string>;
   connection: WebSocket;
   subscriptions: Map<string, { request: any, cursor: string | null }>;
 
@@ -34,8 +86,10 @@ export class Client {
       ? `wss://${endpoint}/v1/websocket`
       : `ws://${endpoint}/v1/websocket`;
 
+    this.headers = options.token ? { Authorization: `Bearer ${options.token}` } : {};
+
     this.subscriptions = new Map();
-    this.connection = new WebSocket(this.endpoint);
+    this.connection = new WebSocket(this.endpoint, { headers: this.headers });
     this.connect();
   }
 
@@ -43,7 +97,7 @@ export class Client {
 
     try {
       if (!this.connection || this.connection.readyState === WebSocket.CLOSED) {
-        this.connection = new WebSocket(this.endpoint);
+        this.connection = new WebSocket(this.endpoint, { headers: this.headers });
       }
 
       await this.waitForConnection();
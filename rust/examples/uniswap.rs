@@ -44,7 +44,7 @@ async fn main() {
     
     // async iterator over stream of data
     while let Some(data) = stream.next().await {
-        let price = String::from_utf8(data.unwrap()).unwrap(); // or use serde json
+        let price = String::from_utf8(data.unwrap().to_vec()).unwrap(); // or use serde json
         println!("Price: {price:?}");
     }
 }
\ No newline at end of file
@@ -0,0 +1,467 @@
+use std::{collections::VecDeque, time::Duration};
+
+use futures::{Stream, StreamExt};
+
+use super::error::Result;
+
+/// A single trade or price update that can be aggregated
+pub trait PricePoint {
+    /// Unix timestamp of the trade in seconds
+    fn timestamp(&self) -> u64;
+    /// Block height the trade was included in
+    fn block_number(&self) -> u64;
+    /// Price of the trade
+    fn price(&self) -> f64;
+    /// Traded volume, in the unit the aggregated volume should be reported in
+    fn volume(&self) -> f64;
+}
+
+/// The width of a candle bucket
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Interval {
+    /// Buckets spanning a fixed amount of time, aligned to the unix epoch
+    Time(Duration),
+    /// Buckets spanning a fixed number of blocks, aligned to block 0
+    Blocks(u64),
+}
+
+impl Interval {
+    fn bucket<P: PricePoint>(&self, point: &P) -> u64 {
+        match self {
+            Self::Time(duration) => point.timestamp() / duration.as_secs().max(1),
+            Self::Blocks(blocks) => point.block_number() / (*blocks).max(1),
+        }
+    }
+}
+
+/// An OHLCV candle
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Candle {
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+    /// Start of the candle in seconds (inclusive)
+    ///
+    /// For [`Interval::Time`] this is the start of the bucket, for
+    /// [`Interval::Blocks`] the timestamp of the first trade in the bucket.
+    pub start_ts: u64,
+    /// End of the candle in seconds
+    ///
+    /// For [`Interval::Time`] this is the (exclusive) end of the bucket, for
+    /// [`Interval::Blocks`] the timestamp of the last trade in the bucket.
+    pub end_ts: u64,
+}
+
+impl Candle {
+    fn new<P: PricePoint>(interval: Interval, bucket: u64, point: &P) -> Self {
+        let (start_ts, end_ts) = match interval {
+            Interval::Time(duration) => {
+                let width = duration.as_secs().max(1);
+                (bucket * width, (bucket + 1) * width)
+            }
+            Interval::Blocks(_) => (point.timestamp(), point.timestamp()),
+        };
+
+        Self {
+            open: point.price(),
+            high: point.price(),
+            low: point.price(),
+            close: point.price(),
+            volume: point.volume(),
+            start_ts,
+            end_ts,
+        }
+    }
+
+    /// A candle without trades, carrying the close of the previous candle
+    fn empty(interval: Interval, bucket: u64, previous: &Candle) -> Self {
+        let (start_ts, end_ts) = match interval {
+            Interval::Time(duration) => {
+                let width = duration.as_secs().max(1);
+                (bucket * width, (bucket + 1) * width)
+            }
+            Interval::Blocks(_) => (previous.end_ts, previous.end_ts),
+        };
+
+        Self {
+            open: previous.close,
+            high: previous.close,
+            low: previous.close,
+            close: previous.close,
+            volume: 0.0,
+            start_ts,
+            end_ts,
+        }
+    }
+
+    fn update<P: PricePoint>(&mut self, interval: Interval, point: &P) {
+        self.high = self.high.max(point.price());
+        self.low = self.low.min(point.price());
+        self.close = point.price();
+        self.volume += point.volume();
+
+        if let Interval::Blocks(_) = interval {
+            self.end_ts = self.end_ts.max(point.timestamp());
+        }
+    }
+}
+
+struct OhlcState<S> {
+    stream: S,
+    interval: Interval,
+    current: Option<(u64, Candle)>,
+    /// Empty buckets still to be emitted: the next one, the end (exclusive)
+    /// and the candle before the next one
+    gap: Option<(u64, u64, Candle)>,
+    queue: VecDeque<Result<Candle>>,
+    done: bool,
+}
+
+/// Aggregates a stream of trades into OHLCV candles
+///
+/// A candle is emitted once the first trade of a later bucket arrives, or
+/// when the input stream ends. Buckets without any trades are filled with a
+/// candle carrying forward the previous close. These are produced one at a
+/// time as the stream is polled, so a large jump in time or height does not
+/// buffer the whole gap. Trades arriving for an already
+/// emitted bucket are added to the current candle. Errors of the input stream
+/// are forwarded as they occur.
+pub fn ohlc<S, P>(stream: S, interval: Interval) -> impl Stream<Item = Result<Candle>>
+where
+    S: Stream<Item = Result<P>> + Unpin,
+    P: PricePoint,
+{
+    let state = OhlcState {
+        stream,
+        interval,
+        current: None,
+        gap: None,
+        queue: VecDeque::new(),
+        done: false,
+    };
+
+    futures::stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(item) = state.queue.pop_front() {
+                return Some((item, state));
+            }
+            if let Some((next, end, previous)) = state.gap.take() {
+                if next < end {
+                    let candle = Candle::empty(state.interval, next, &previous);
+                    state.gap = Some((next + 1, end, candle));
+                    return Some((Ok(candle), state));
+                }
+            }
+            if state.done {
+                return None;
+            }
+
+            match state.stream.next().await {
+                None => {
+                    state.done = true;
+                    if let Some((_, candle)) = state.current.take() {
+                        state.queue.push_back(Ok(candle));
+                    }
+                }
+                Some(Err(e)) => state.queue.push_back(Err(e)),
+                Some(Ok(point)) => {
+                    let interval = state.interval;
+                    let bucket = interval.bucket(&point);
+
+                    match state.current.as_mut() {
+                        Some((current, candle)) if bucket <= *current => {
+                            candle.update(interval, &point);
+                        }
+                        Some((current, candle)) => {
+                            state.queue.push_back(Ok(*candle));
+                            state.gap = Some((*current + 1, bucket, *candle));
+                            state.current = Some((bucket, Candle::new(interval, bucket, &point)));
+                        }
+                        None => {
+                            state.current = Some((bucket, Candle::new(interval, bucket, &point)));
+                        }
+                    }
+                }
+            }
+        }
+    })
+}
+
+/// The rolling window of a VWAP
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Window {
+    /// All trades within this duration of the latest trade
+    Time(Duration),
+    /// The latest `n` trades
+    Trades(usize),
+}
+
+/// A volume-weighted average price over a rolling window
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Vwap {
+    /// The volume-weighted average price
+    ///
+    /// If the window carries no volume, this is the price of the latest trade.
+    pub price: f64,
+    /// The total volume within the window
+    pub volume: f64,
+    /// The number of trades within the window
+    pub trades: usize,
+    /// Timestamp of the latest trade in seconds
+    pub timestamp: u64,
+}
+
+#[derive(Default)]
+struct VwapState {
+    window: VecDeque<(u64, f64, f64)>,
+    notional: f64,
+    volume: f64,
+}
+
+impl VwapState {
+    fn push<P: PricePoint>(&mut self, window: Window, point: &P) -> Vwap {
+        let volume = point.volume().abs();
+        self.window
+            .push_back((point.timestamp(), point.price(), volume));
+        self.notional += point.price() * volume;
+        self.volume += volume;
+
+        loop {
+            let expired = match (window, self.window.front()) {
+                (Window::Time(duration), Some((timestamp, _, _))) => {
+                    timestamp + duration.as_secs() < point.timestamp()
+                }
+                (Window::Trades(n), Some(_)) => self.window.len() > n.max(1),
+                (_, None) => false,
+            };
+            if !expired {
+                break;
+            }
+            if let Some((_, price, volume)) = self.window.pop_front() {
+                self.notional -= price * volume;
+                self.volume -= volume;
+            }
+        }
+
+        let price = if self.volume > 0.0 {
+            self.notional / self.volume
+        } else {
+            point.price()
+        };
+
+        Vwap {
+            price,
+            volume: self.volume,
+            trades: self.window.len(),
+            timestamp: point.timestamp(),
+        }
+    }
+}
+
+/// Computes a rolling volume-weighted average price for every trade
+///
+/// The volume of a trade is taken from [`PricePoint::volume`] and its absolute
+/// value is used as weight, so buys and sells count alike. For Uniswap prices
+/// this should be the amount of the base token (`amount0`), as prices are
+/// quoted in `token1` per `token0` and a VWAP is weighted by base volume.
+/// Errors of the input stream are forwarded as they occur.
+pub fn vwap<S, P>(stream: S, window: Window) -> impl Stream<Item = Result<Vwap>>
+where
+    S: Stream<Item = Result<P>>,
+    P: PricePoint,
+{
+    stream.scan(VwapState::default(), move |state, point| {
+        let item = point.map(|point| state.push(window, &point));
+        futures::future::ready(Some(item))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+
+    use super::*;
+
+    struct Trade {
+        timestamp: u64,
+        price: f64,
+        volume: f64,
+    }
+
+    impl PricePoint for Trade {
+        fn timestamp(&self) -> u64 {
+            self.timestamp
+        }
+
+        fn block_number(&self) -> u64 {
+            self.timestamp
+        }
+
+        fn price(&self) -> f64 {
+            self.price
+        }
+
+        fn volume(&self) -> f64 {
+            self.volume
+        }
+    }
+
+    fn trade(timestamp: u64, price: f64, volume: f64) -> Result<Trade> {
+        Ok(Trade {
+            timestamp,
+            price,
+            volume,
+        })
+    }
+
+    #[test]
+    fn buckets_trades_into_candles() {
+        let trades = futures::stream::iter([
+            trade(0, 10.0, 1.0),
+            trade(20, 12.0, 2.0),
+            trade(40, 9.0, 1.0),
+            trade(60, 11.0, 3.0),
+        ]);
+
+        let candles: Vec<_> =
+            block_on(ohlc(trades, Interval::Time(Duration::from_secs(60))).collect());
+
+        let candles: Vec<_> = candles.into_iter().map(Result::unwrap).collect();
+        assert_eq!(
+            candles,
+            [
+                Candle {
+                    open: 10.0,
+                    high: 12.0,
+                    low: 9.0,
+                    close: 9.0,
+                    volume: 4.0,
+                    start_ts: 0,
+                    end_ts: 60,
+                },
+                Candle {
+                    open: 11.0,
+                    high: 11.0,
+                    low: 11.0,
+                    close: 11.0,
+                    volume: 3.0,
+                    start_ts: 60,
+                    end_ts: 120,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn fills_empty_buckets_with_previous_close() {
+        let trades = futures::stream::iter([trade(0, 10.0, 1.0), trade(180, 12.0, 1.0)]);
+
+        let candles: Vec<_> =
+            block_on(ohlc(trades, Interval::Time(Duration::from_secs(60))).collect());
+
+        let candles: Vec<_> = candles.into_iter().map(Result::unwrap).collect();
+        assert_eq!(candles.len(), 4);
+        for (candle, start_ts) in candles[1..3].iter().zip([60, 120]) {
+            assert_eq!(candle.start_ts, start_ts);
+            assert_eq!(
+                (candle.open, candle.close, candle.volume),
+                (10.0, 10.0, 0.0)
+            );
+        }
+        assert_eq!(candles[3].open, 12.0);
+    }
+
+    #[test]
+    fn emits_large_gaps_lazily() {
+        let trades = futures::stream::iter([trade(0, 10.0, 1.0), trade(1 << 40, 12.0, 1.0)]);
+
+        let candles: Vec<_> = block_on(
+            ohlc(trades, Interval::Time(Duration::from_secs(1)))
+                .take(3)
+                .collect(),
+        );
+
+        assert_eq!(candles.len(), 3);
+        assert_eq!(candles[2].as_ref().unwrap().start_ts, 2);
+    }
+
+    #[test]
+    fn bucket_edges_belong_to_the_later_bucket() {
+        let trades = futures::stream::iter([trade(59, 10.0, 1.0), trade(60, 12.0, 1.0)]);
+
+        let candles: Vec<_> =
+            block_on(ohlc(trades, Interval::Time(Duration::from_secs(60))).collect());
+
+        let candles: Vec<_> = candles.into_iter().map(Result::unwrap).collect();
+        assert_eq!(
+            candles
+                .iter()
+                .map(|candle| (candle.start_ts, candle.close))
+                .collect::<Vec<_>>(),
+            [(0, 10.0), (60, 12.0)]
+        );
+    }
+
+    #[test]
+    fn empty_stream_has_no_candles_or_vwaps() {
+        let candles: Vec<_> = block_on(
+            ohlc(
+                futures::stream::empty::<Result<Trade>>(),
+                Interval::Blocks(10),
+            )
+            .collect(),
+        );
+        assert!(candles.is_empty());
+
+        let vwaps: Vec<_> =
+            block_on(vwap(futures::stream::empty::<Result<Trade>>(), Window::Trades(3)).collect());
+        assert!(vwaps.is_empty());
+    }
+
+    #[test]
+    fn vwap_weights_prices_by_absolute_volume() {
+        let trades = futures::stream::iter([
+            trade(0, 10.0, 1.0),
+            trade(1, 20.0, -3.0),
+            trade(2, 30.0, 1.0),
+        ]);
+
+        let vwaps: Vec<_> = block_on(vwap(trades, Window::Trades(2)).collect());
+
+        let vwaps: Vec<_> = vwaps.into_iter().map(Result::unwrap).collect();
+        assert_eq!(
+            vwaps
+                .iter()
+                .map(|vwap| (vwap.price, vwap.volume, vwap.trades))
+                .collect::<Vec<_>>(),
+            // the first trade left the window of the last one
+            [(10.0, 1.0, 1), (17.5, 4.0, 2), (22.5, 4.0, 2)]
+        );
+    }
+
+    #[test]
+    fn vwap_time_window_keeps_trades_at_its_edge() {
+        let trades = futures::stream::iter([
+            trade(0, 10.0, 1.0),
+            trade(60, 20.0, 1.0),
+            trade(61, 30.0, 2.0),
+        ]);
+
+        let vwaps: Vec<_> = block_on(vwap(trades, Window::Time(Duration::from_secs(60))).collect());
+
+        let vwaps: Vec<_> = vwaps.into_iter().map(Result::unwrap).collect();
+        assert_eq!((vwaps[1].price, vwaps[1].trades), (15.0, 2));
+        assert_eq!((vwaps[2].price, vwaps[2].trades), (80.0 / 3.0, 2));
+    }
+
+    #[test]
+    fn vwap_without_volume_is_the_latest_price() {
+        let trades = futures::stream::iter([trade(0, 10.0, 0.0), trade(1, 12.0, 0.0)]);
+
+        let vwaps: Vec<_> = block_on(vwap(trades, Window::Trades(5)).collect());
+
+        assert_eq!(vwaps[1].as_ref().unwrap().price, 12.0);
+    }
+}
@@ -0,0 +1,153 @@
+use std::{fmt, time::UNIX_EPOCH};
+
+use hmac::{Hmac, Mac};
+use sha2::{Sha256, Sha512};
+
+use super::utils::system_now;
+
+/// The hash function of an HMAC signature
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum HmacAlgo {
+    /// HMAC-SHA256
+    #[default]
+    Sha256,
+    /// HMAC-SHA512
+    Sha512,
+}
+
+/// Signs requests with a shared secret for gateways that require HMAC
+/// authentication
+///
+/// Every request is signed over the canonical string
+///
+/// ```text
+/// {METHOD}\n{path}\n{query}\n{timestamp}\n{body}
+/// ```
+///
+/// where `METHOD` is the upper case HTTP method, `path` the percent encoded
+/// path of the URL including the leading `/`, `query` the percent encoded
+/// query string without the leading `?`, empty if there is none, `timestamp`
+/// the unix time in seconds as a decimal number and `body` the raw request
+/// body, empty for the `GET` requests of this crate. The signature is sent
+/// lower case hex encoded in the [`SIGNATURE_HEADER`](Self::SIGNATURE_HEADER),
+/// together with the [`KEY_ID_HEADER`](Self::KEY_ID_HEADER) and the
+/// [`TIMESTAMP_HEADER`](Self::TIMESTAMP_HEADER). The WebSocket provider signs
+/// the handshake request of every connection attempt.
+#[derive(Clone)]
+pub struct HmacSigner {
+    key_id: String,
+    secret: Vec<u8>,
+    algo: HmacAlgo,
+}
+
+impl HmacSigner {
+    /// The header carrying the id of the key
+    pub const KEY_ID_HEADER: &'static str = "X-Key-Id";
+    /// The header carrying the timestamp the signature was computed with
+    pub const TIMESTAMP_HEADER: &'static str = "X-Timestamp";
+    /// The header carrying the signature
+    pub const SIGNATURE_HEADER: &'static str = "X-Signature";
+
+    pub fn new(key_id: impl Into<String>, secret: impl Into<Vec<u8>>, algo: HmacAlgo) -> Self {
+        Self {
+            key_id: key_id.into(),
+            secret: secret.into(),
+            algo,
+        }
+    }
+
+    /// Returns the id of the key
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    /// Computes the hex encoded signature of a request
+    pub fn sign(
+        &self,
+        method: &str,
+        path: &str,
+        query: Option<&str>,
+        timestamp: u64,
+        body: &[u8],
+    ) -> String {
+        let mut canonical = format!(
+            "{}\n{path}\n{}\n{timestamp}\n",
+            method.to_uppercase(),
+            query.unwrap_or_default(),
+        )
+        .into_bytes();
+        canonical.extend_from_slice(body);
+
+        let signature = match self.algo {
+            HmacAlgo::Sha256 => mac::<Hmac<Sha256>>(&self.secret, &canonical),
+            HmacAlgo::Sha512 => mac::<Hmac<Sha512>>(&self.secret, &canonical),
+        };
+        signature.iter().map(|byte| format!("{byte:02x}")).collect()
+    }
+
+    /// Returns the headers authenticating a request sent now
+    pub fn headers(
+        &self,
+        method: &str,
+        path: &str,
+        query: Option<&str>,
+        body: &[u8],
+    ) -> [(&'static str, String); 3] {
+        let timestamp = system_now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        [
+            (Self::KEY_ID_HEADER, self.key_id.clone()),
+            (Self::TIMESTAMP_HEADER, timestamp.to_string()),
+            (
+                Self::SIGNATURE_HEADER,
+                self.sign(method, path, query, timestamp, body),
+            ),
+        ]
+    }
+}
+
+fn mac<M: Mac + hmac::digest::KeyInit>(secret: &[u8], message: &[u8]) -> Vec<u8> {
+    let mut mac = <M as hmac::digest::KeyInit>::new_from_slice(secret)
+        .expect("HMAC accepts keys of any length");
+    mac.update(message);
+    mac.finalize().into_bytes().to_vec()
+}
+
+impl fmt::Debug for HmacSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HmacSigner")
+            .field("key_id", &self.key_id)
+            .field("algo", &self.algo)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signs_the_canonical_string() {
+        let sign = |algo| {
+            HmacSigner::new("key", "secret", algo).sign(
+                "get",
+                "/v1/api/blocks",
+                Some("chains=ETH&from_block=-10"),
+                1_700_000_000,
+                b"",
+            )
+        };
+
+        assert_eq!(
+            sign(HmacAlgo::Sha256),
+            "dcdfea598d903dd966e3cced1cb9c7d133f2e307cfe2bc808b1e57a3a1100164"
+        );
+        assert_eq!(
+            sign(HmacAlgo::Sha512),
+            "f91ae1f7e6fdb6db7d8af96e040ed74d263078feb20d04a6a7b44dfad9644e7c\
+             fbcea8229a6c181404231e24e56465b50323924732ab6667efc341f711212957"
+        );
+    }
+}
@@ -0,0 +1,147 @@
+//! A synchronous facade over the HTTP client
+//!
+//! Mirrors `reqwest::blocking`: every [`BlockingClient`] owns a dedicated
+//! single threaded Tokio runtime and blocks the calling thread on it. It must
+//! therefore not be used from within an async runtime, where blocking the
+//! thread panics.
+
+use std::{future::Future, sync::Arc};
+
+use futures::StreamExt;
+use serde::de::DeserializeOwned;
+use tokio::runtime::Runtime;
+
+use super::{
+    builder::ClientBuilder,
+    client::Client,
+    decode::decode_json_stream,
+    error::Result,
+    provider::{ChainProvider, ResponseStream, StreamResponse},
+    requests::{blocks::GetBlocksRequest, logs::GetLogsRequest, txs::GetTxsRequest},
+    types::format::Format,
+};
+use crate::HttpProvider;
+
+/// A [`Client`] over HTTP whose methods block instead of being async
+pub struct BlockingClient {
+    client: Client<HttpProvider>,
+    runtime: Arc<Runtime>,
+}
+
+impl ClientBuilder {
+    /// Creates a [`BlockingClient`] with the given configuration
+    ///
+    /// Starts the runtime the client blocks on, see [`BlockingClient`].
+    pub fn build_blocking(self) -> Result<BlockingClient> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        let client = runtime.block_on(self.build::<HttpProvider>())?;
+        Ok(BlockingClient {
+            client,
+            runtime: Arc::new(runtime),
+        })
+    }
+}
+
+impl BlockingClient {
+    /// Returns the async client, e.g. to [`block_on`](Self::block_on) any of
+    /// its methods that has no blocking counterpart
+    pub fn client(&self) -> &Client<HttpProvider> {
+        &self.client
+    }
+
+    /// Runs a future of the async client to completion
+    pub fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future)
+    }
+
+    /// Turns a stream of the async client into a blocking iterator
+    pub fn iter<R>(&self, stream: StreamResponse<R>) -> Result<Iter<R>> {
+        Ok(Iter {
+            stream: stream?,
+            runtime: self.runtime.clone(),
+        })
+    }
+
+    /// Returns all requested blocks
+    pub fn get_blocks<R>(&self, request: GetBlocksRequest) -> Result<Vec<R>>
+    where
+        R: DeserializeOwned + Send + 'static,
+    {
+        self.iter_blocks(request)?.collect()
+    }
+
+    /// Iterates over the requested blocks as they arrive
+    pub fn iter_blocks<R>(&self, request: GetBlocksRequest) -> Result<Iter<R>>
+    where
+        R: DeserializeOwned + Send + 'static,
+    {
+        let stream = self.block_on(self.client.get_blocks_by_format(
+            request,
+            Format::JsonStream,
+            false,
+        ));
+        self.iter(stream.map(decode_json_stream))
+    }
+
+    /// Returns all requested logs
+    pub fn get_logs<R>(&self, request: GetLogsRequest) -> Result<Vec<R>>
+    where
+        R: DeserializeOwned + Send + 'static,
+    {
+        self.iter_logs(request)?.collect()
+    }
+
+    /// Iterates over the requested logs as they arrive
+    pub fn iter_logs<R>(&self, request: GetLogsRequest) -> Result<Iter<R>>
+    where
+        R: DeserializeOwned + Send + 'static,
+    {
+        let stream = self.block_on(self.client.get_logs_by_format(
+            request,
+            Format::JsonStream,
+            false,
+        ));
+        self.iter(stream.map(decode_json_stream))
+    }
+
+    /// Returns all requested transactions
+    pub fn get_txs<R>(&self, request: GetTxsRequest) -> Result<Vec<R>>
+    where
+        R: DeserializeOwned + Send + 'static,
+    {
+        self.iter_txs(request)?.collect()
+    }
+
+    /// Iterates over the requested transactions as they arrive
+    pub fn iter_txs<R>(&self, request: GetTxsRequest) -> Result<Iter<R>>
+    where
+        R: DeserializeOwned + Send + 'static,
+    {
+        let stream = self.block_on(self.client.get_txs_by_format(
+            request,
+            Format::JsonStream,
+            false,
+        ));
+        self.iter(stream.map(decode_json_stream))
+    }
+}
+
+/// A blocking iterator over a [`ResponseStream`]
+///
+/// Every call to `next` blocks until the next item arrived. The iterator
+/// shares the runtime of its client, so it stays usable after the client
+/// was dropped.
+pub struct Iter<T> {
+    stream: ResponseStream<T>,
+    runtime: Arc<Runtime>,
+}
+
+impl<T> Iterator for Iter<T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.runtime.block_on(self.stream.next())
+    }
+}
@@ -1,16 +1,34 @@
+#[cfg(not(target_arch = "wasm32"))]
+use std::num::NonZeroU32;
+use std::time::Duration;
+
+use futures::future::BoxFuture;
 use lazy_static::lazy_static;
+#[cfg(not(target_arch = "wasm32"))]
+use tracing::warn;
 
-use super::{client::Client, error::Result, provider::Provider};
+use super::{
+    client::Client,
+    error::{Error, Result},
+    provider::{IdStrategy, Provider, ProviderConfig, Spawner},
+    types::ChainId,
+};
 
 lazy_static! {
     static ref DEFAULT_ENDPOINT: String =
         std::env::var("SUPER_URL").unwrap_or_else(|_| "app.superchain.network".to_string());
 }
+// unset and empty credentials are both treated as absent, so that no
+// `Authorization` header is sent for them
 lazy_static! {
-    static ref USERNAME: String = std::env::var("SUPER_USERNAME").unwrap_or_default();
+    static ref USERNAME: Option<String> = std::env::var("SUPER_USERNAME")
+        .ok()
+        .filter(|username| !username.is_empty());
 }
 lazy_static! {
-    static ref PASSWORD: String = std::env::var("SUPER_PASSWORD").unwrap_or_default();
+    static ref PASSWORD: Option<String> = std::env::var("SUPER_PASSWORD")
+        .ok()
+        .filter(|password| !password.is_empty());
 }
 
 /// A builder for `Client`.
@@ -24,19 +42,24 @@ lazy_static! {
 ///   .build::<HttpProvider>();
 /// ```
 ///
+/// With the `ws` feature:
 /// ```
+/// # #[cfg(feature = "ws")]
+/// # {
 /// use superchain_client::ClientBuilder;
 /// use superchain_client::WsProvider;
 ///
 /// let client = ClientBuilder::default()
 ///  .endpoint("app.superchain.network")
 ///  .build::<WsProvider>();
+/// # }
 /// ```
+///
+/// Credentials are redacted in the `Debug` output, see [`ProviderConfig`].
+#[derive(Debug)]
 pub struct ClientBuilder {
-    endpoint: String,
-    is_secure: bool,
-    username: Option<String>,
-    password: Option<String>,
+    config: ProviderConfig,
+    default_chain: Option<ChainId>,
 }
 
 /// A builder for `Client`.
@@ -52,15 +75,111 @@ pub struct ClientBuilder {
 /// ```
 impl ClientBuilder {
     /// Sets the endpoint of the client.
-    pub fn endpoint(mut self, endpoint: &str) -> Self {
-        self.endpoint = endpoint.to_string();
+    /// Accepts a host with an optional port and path, e.g.
+    /// `app.superchain.network`, or a full URL as a string or [`Url`]. The
+    /// scheme of a URL replaces the [`secure`](Self::secure) setting:
+    /// `https` and `wss` are secure, `http` and `ws` are not.
+    ///
+    /// [`Url`]: url::Url
+    pub fn endpoint(mut self, endpoint: impl AsRef<str>) -> Self {
+        let (endpoint, is_secure) = split_scheme(endpoint.as_ref());
+        self.config.endpoint = endpoint.to_string();
+        if let Some(is_secure) = is_secure {
+            self.config.is_secure = is_secure;
+        }
+        self
+    }
+
+    /// Sets several endpoints to fail over between, in order.
+    /// Only used by the [`FailoverProvider`](crate::FailoverProvider), the
+    /// other providers connect to the first endpoint. Accepts the same forms
+    /// as [`endpoint`](Self::endpoint), the scheme of the first endpoint
+    /// applies to all of them.
+    pub fn endpoints<S: AsRef<str>>(mut self, endpoints: impl IntoIterator<Item = S>) -> Self {
+        let endpoints: Vec<_> = endpoints.into_iter().collect();
+        if let Some(first) = endpoints.first() {
+            self = self.endpoint(first);
+        }
+        self.config.endpoints = endpoints
+            .iter()
+            .map(|endpoint| split_scheme(endpoint.as_ref()).0.to_string())
+            .collect();
         self
     }
 
+    /// Sets several endpoints to distribute requests across.
+    /// Only used by the [`LoadBalancedProvider`](crate::LoadBalancedProvider),
+    /// the other providers connect to the first endpoint.
+    pub fn load_balance<S: AsRef<str>>(self, endpoints: impl IntoIterator<Item = S>) -> Self {
+        self.endpoints(endpoints)
+    }
+
+    /// Creates a builder configured from environment variables.
+    ///
+    /// | Variable | Fallback | |
+    /// |---|---|---|
+    /// | `SUPERCHAIN_ENDPOINT` | `SUPER_URL` | The endpoint, default `app.superchain.network` |
+    /// | `SUPERCHAIN_USERNAME` | `SUPER_USERNAME` | The username |
+    /// | `SUPERCHAIN_PASSWORD` | `SUPER_PASSWORD` | The password |
+    /// | `SUPERCHAIN_SECURE` | | `true` or `false`, default `true` |
+    /// | `SUPERCHAIN_TOKEN` | | A bearer token, replaces username and password |
+    ///
+    /// Returns an error if neither a token nor username and password are set,
+    /// or if `SUPERCHAIN_SECURE` is not a boolean.
+    pub fn from_env() -> Result<Self> {
+        fn var(name: &str) -> Option<String> {
+            std::env::var(name).ok().filter(|value| !value.is_empty())
+        }
+
+        let mut builder = Self::default();
+        if let Some(endpoint) = var("SUPERCHAIN_ENDPOINT") {
+            builder = builder.endpoint(endpoint);
+        }
+
+        match var("SUPERCHAIN_SECURE").map(|secure| secure.to_lowercase()) {
+            None => {}
+            Some(secure) if secure == "true" || secure == "1" => builder = builder.secure(true),
+            Some(secure) if secure == "false" || secure == "0" => builder = builder.secure(false),
+            Some(secure) => {
+                return Err(Error::Custom(
+                    format!("invalid value of SUPERCHAIN_SECURE: {secure}").into(),
+                ))
+            }
+        }
+
+        if let Some(token) = var("SUPERCHAIN_TOKEN") {
+            builder.config.username = None;
+            builder.config.password = None;
+            return Ok(builder.header("Authorization", format!("Bearer {token}")));
+        }
+
+        let username = var("SUPERCHAIN_USERNAME")
+            .or_else(|| USERNAME.clone())
+            .ok_or(Error::MissingEnvVar("SUPERCHAIN_USERNAME"))?;
+        let password = var("SUPERCHAIN_PASSWORD")
+            .or_else(|| PASSWORD.clone())
+            .ok_or(Error::MissingEnvVar("SUPERCHAIN_PASSWORD"))?;
+
+        Ok(builder.credential(username, password))
+    }
+
     /// Sets the username of the client.
     pub fn credential(mut self, username: impl Into<String>, password: impl Into<String>) -> Self {
-        self.username = Some(username.into());
-        self.password = Some(password.into());
+        self.config.username = Some(username.into());
+        self.config.password = Some(password.into());
+        self
+    }
+
+    /// Removes all credentials, including those read from the environment and
+    /// an `Authorization` header, so that no `Authorization` header is sent.
+    /// Meant for public endpoints and local servers without authentication.
+    /// Credentials set after this call are used again.
+    pub fn anonymous(mut self) -> Self {
+        self.config.username = None;
+        self.config.password = None;
+        self.config
+            .headers
+            .retain(|(name, _)| !name.eq_ignore_ascii_case("authorization"));
         self
     }
 
@@ -68,7 +187,172 @@ impl ClientBuilder {
     /// If the secure is false, the client will use http instead of https for
     /// API. Default is true.
     pub fn secure(mut self, is_secure: bool) -> Self {
-        self.is_secure = is_secure;
+        self.config.is_secure = is_secure;
+        self
+    }
+
+    /// Sets the `User-Agent` header of HTTP requests and the WebSocket
+    /// handshake.
+    /// Default is `superchain-client/<version>`.
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.config.user_agent = user_agent.into();
+        self
+    }
+
+    /// Adds a header to HTTP requests and the WebSocket handshake, e.g. an
+    /// API key required by a gateway.
+    /// Invalid names or values make building the client fail.
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.config.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Adds all headers of the map, see [`ClientBuilder::header`].
+    pub fn headers(mut self, headers: http::HeaderMap) -> Self {
+        self.config
+            .headers
+            .extend(headers.iter().map(|(name, value)| {
+                (
+                    name.to_string(),
+                    String::from_utf8_lossy(value.as_bytes()).into_owned(),
+                )
+            }));
+        self
+    }
+
+    /// Disables TLS certificate verification if the endpoint is on `host`.
+    /// This is only meant for development, e.g. integration tests against a
+    /// self-hosted gateway on `localhost` with a self-signed certificate.
+    /// Never use it in production, as it makes the connection vulnerable to
+    /// man-in-the-middle attacks. Other hosts are verified as usual.
+    pub fn insecure_for_host(mut self, host: &str) -> Self {
+        self.config.insecure_hosts.push(host.to_owned());
+        self
+    }
+
+    /// Sets the maximum size of a WebSocket message.
+    /// Messages split into several frames are limited after reassembly, so
+    /// this bounds the memory a single message can take. Larger messages fail
+    /// the connection with a capacity error. Default is 64 MiB.
+    pub fn ws_max_message_size(mut self, max_message_size: usize) -> Self {
+        self.config.ws_max_message_size = Some(max_message_size);
+        self
+    }
+
+    /// Sets the maximum size of a single WebSocket frame.
+    /// Default is 16 MiB.
+    pub fn ws_max_frame_size(mut self, max_frame_size: usize) -> Self {
+        self.config.ws_max_frame_size = Some(max_frame_size);
+        self
+    }
+
+    /// Sets the idle timeout of the WebSocket connection.
+    /// If no frame, including pongs to the regular pings, is received within
+    /// this duration, the connection is considered dead and reconnected.
+    /// Pings are sent every 30 seconds, so the timeout should be longer than
+    /// that. Default is no timeout.
+    pub fn ws_idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.config.ws_idle_timeout = Some(idle_timeout);
+        self
+    }
+
+    /// Sets the timeout of connecting to a single address of the WebSocket
+    /// endpoint, including the TLS and WebSocket handshakes.
+    /// The addresses of the host are tried in turn, alternating between IPv6
+    /// and IPv4, so a broken address family or a stalled server only delays
+    /// the connection by this duration. Applies to reconnects as well. Default is the behavior
+    /// of the system, which may stall for minutes.
+    pub fn ws_connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.config.ws_connect_timeout = Some(connect_timeout);
+        self
+    }
+
+    /// Sets the number of consecutive pings the server may leave unanswered.
+    /// Pings are sent every 30 seconds. Once more pings than this are
+    /// outstanding, the connection is considered half-open and reconnected.
+    /// Default is 2.
+    pub fn ws_max_missed_pongs(mut self, max_missed_pongs: u32) -> Self {
+        self.config.ws_max_missed_pongs = max_missed_pongs;
+        self
+    }
+
+    /// Coalesces WebSocket operations queued within `batch_window` of the
+    /// first one into a single batched frame, e.g. when subscribing to many
+    /// pools at once. Requires a server accepting the batch envelope
+    /// `{"operation": "batch", "requests": [...]}` of the plain requests,
+    /// every operation is delayed by up to `batch_window`.
+    /// Ignored by the HTTP provider.
+    pub fn ws_batch_window(mut self, batch_window: Duration) -> Self {
+        self.config.ws_batch_window = Some(batch_window);
+        self
+    }
+
+    /// Limits the rate of outgoing requests.
+    /// Requests are throttled by a token bucket that allows bursts of up to
+    /// `burst` requests and refills at `requests_per_sec`. A `Retry-After`
+    /// sent along with a `429 Too Many Requests` response additionally pauses
+    /// all requests. A value of 0 for either disables the limit.
+    /// Not available on wasm32, which lacks the timers of the limiter.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn rate_limit(mut self, requests_per_sec: u32, burst: u32) -> Self {
+        self.config.rate_limit = NonZeroU32::new(requests_per_sec).zip(NonZeroU32::new(burst));
+        self
+    }
+
+    /// Sets how the WebSocket provider assigns request ids, random by default.
+    /// Ignored by the HTTP provider.
+    pub fn id_strategy(mut self, id_strategy: IdStrategy) -> Self {
+        self.config.id_strategy = id_strategy;
+        self
+    }
+
+    /// Signs every request with the given secret, sending the signature along
+    /// with the key id and a timestamp in headers, see [`HmacSigner`] for
+    /// the signed content. Only required by gateways that authenticate
+    /// requests this way, in addition to or instead of the credentials.
+    ///
+    /// [`HmacSigner`]: crate::core::auth::HmacSigner
+    #[cfg(feature = "hmac")]
+    pub fn hmac_signer(
+        mut self,
+        key_id: impl Into<String>,
+        secret: impl Into<Vec<u8>>,
+        algo: crate::core::auth::HmacAlgo,
+    ) -> Self {
+        self.config.hmac_signer = Some(crate::core::auth::HmacSigner::new(key_id, secret, algo));
+        self
+    }
+
+    /// Sets the function used to spawn the background tasks of the provider,
+    /// [`tokio::spawn`] by default.
+    /// The spawner must keep polling the futures until they complete and run
+    /// them within a Tokio runtime context, see [`Spawner`].
+    pub fn spawner<F>(mut self, spawn: F) -> Self
+    where
+        F: Fn(BoxFuture<'static, ()>) + Send + Sync + 'static,
+    {
+        self.config.spawner = Spawner::new(spawn);
+        self
+    }
+
+    /// Enables an in-memory LRU cache for HTTP responses.
+    /// Only requests with exact block heights for both bounds are cached,
+    /// as their results can not change. At most `capacity` responses are
+    /// kept, each for at most `ttl`. A capacity of 0 disables the cache.
+    /// Ignored by the WebSocket provider, not available on wasm32.
+    #[cfg(all(feature = "cache", not(target_arch = "wasm32")))]
+    pub fn http_cache(mut self, capacity: usize, ttl: Duration) -> Self {
+        self.config.http_cache = std::num::NonZeroUsize::new(capacity).map(|c| (c, ttl));
+        self
+    }
+
+    /// Sets the chain of requests that leave their `chains` empty, e.g. the
+    /// chain of a gateway serving a single chain.
+    /// The `Default` of the requests is `ETH`, so clear the `chains` to pick
+    /// up this chain. Requests with any chains, `ETH` included, are sent
+    /// unchanged.
+    pub fn default_chain(mut self, chain: ChainId) -> Self {
+        self.default_chain = Some(chain);
         self
     }
 
@@ -77,16 +361,77 @@ impl ClientBuilder {
     where
         T: Provider + Send,
     {
-        let inner = T::try_new(self.endpoint, self.is_secure, self.username, self.password).await?;
-        Ok(Client::new(inner))
+        let inner = T::try_new_with_config(self.config).await?;
+        Ok(Client::new(inner).with_default_chain(self.default_chain))
+    }
+
+    /// Creates a new `Client`, retrying the initial connection according to
+    /// the given policy.
+    /// Returns the error of the last attempt once the policy is exhausted.
+    /// Not available on wasm32, which lacks the timer of the backoff.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn build_with_retry<T>(self, policy: RetryPolicy) -> Result<Client<T>>
+    where
+        T: Provider + Send,
+    {
+        let mut backoff = policy.initial_backoff;
+        let mut attempt = 1;
+
+        loop {
+            match T::try_new_with_config(self.config.clone()).await {
+                Ok(inner) => return Ok(Client::new(inner).with_default_chain(self.default_chain)),
+                Err(e) if attempt >= policy.max_attempts => return Err(e),
+                Err(e) => {
+                    warn!(
+                        "Connection attempt {}/{} failed: {:?}",
+                        attempt, policy.max_attempts, e
+                    );
+                }
+            }
+
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(policy.max_backoff);
+            attempt += 1;
+        }
+    }
+}
+
+/// The retry policy of [`ClientBuilder::build_with_retry`].
+/// The backoff between attempts doubles after every failed attempt, up to
+/// `max_backoff`.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone, Copy, Debug)]
+pub struct RetryPolicy {
+    /// Total number of connection attempts, including the first one
+    pub max_attempts: u32,
+    /// Backoff after the first failed attempt
+    pub initial_backoff: Duration,
+    /// Upper limit of the backoff
+    pub max_backoff: Duration,
+}
+
+/// Default implementation for `RetryPolicy`.
+/// Default is 5 attempts with a backoff starting at 1 second, up to 30
+/// seconds.
+#[cfg(not(target_arch = "wasm32"))]
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(30),
+        }
     }
 }
 
 /// Default implementation for `ClientBuilder`.
 /// Default endpoint is `app.superchain.network`.
 /// Default secure is true.
-/// Default username is None.
-/// Default password is None.
+/// Default username is `SUPER_USERNAME` if it is set and not empty, None
+/// otherwise.
+/// Default password is `SUPER_PASSWORD` if it is set and not empty, None
+/// otherwise.
+/// Without both no `Authorization` header is sent.
 /// ```
 /// use superchain_client::ClientBuilder;
 /// use superchain_client::HttpProvider;
@@ -97,10 +442,175 @@ impl ClientBuilder {
 impl Default for ClientBuilder {
     fn default() -> Self {
         Self {
-            endpoint: DEFAULT_ENDPOINT.to_string(),
-            username: Some(USERNAME.to_string()),
-            password: Some(PASSWORD.to_string()),
-            is_secure: true,
+            config: ProviderConfig::new(
+                DEFAULT_ENDPOINT.to_string(),
+                true,
+                USERNAME.clone(),
+                PASSWORD.clone(),
+            ),
+            default_chain: None,
+        }
+    }
+}
+
+/// Splits the scheme off an endpoint, returning the endpoint without scheme
+/// and trailing slash and whether the scheme is secure
+///
+/// Unknown schemes are dropped without changing the security setting.
+fn split_scheme(endpoint: &str) -> (&str, Option<bool>) {
+    let Some((scheme, rest)) = endpoint.split_once("://") else {
+        return (endpoint.trim_end_matches('/'), None);
+    };
+
+    let is_secure = match scheme.to_ascii_lowercase().as_str() {
+        "https" | "wss" => Some(true),
+        "http" | "ws" => Some(false),
+        _ => None,
+    };
+    (rest.trim_end_matches('/'), is_secure)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use async_trait::async_trait;
+    use bytes::Bytes;
+
+    use super::*;
+    use crate::{core::provider::StreamResponse, Error, Format};
+
+    static ATTEMPTS: AtomicU32 = AtomicU32::new(0);
+
+    /// Fails to connect twice before it succeeds
+    struct Flaky;
+
+    #[async_trait]
+    impl Provider for Flaky {
+        async fn try_new(
+            _endpoint: String,
+            _is_secure: bool,
+            _username: Option<String>,
+            _password: Option<String>,
+        ) -> Result<Self> {
+            match ATTEMPTS.fetch_add(1, Ordering::SeqCst) {
+                0 | 1 => Err(Error::Custom("backend not ready".into())),
+                _ => Ok(Self),
+            }
+        }
+
+        async fn get_status_by_format(&self, _format: Format) -> StreamResponse<Bytes> {
+            unimplemented!()
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn build_with_retry_retries_until_connected() {
+        let start = tokio::time::Instant::now();
+        let client = ClientBuilder::default()
+            .build_with_retry::<Flaky>(RetryPolicy::default())
+            .await;
+
+        assert!(client.is_ok());
+        assert_eq!(ATTEMPTS.load(Ordering::SeqCst), 3);
+        // backoffs of 1 and 2 seconds
+        assert_eq!(start.elapsed(), Duration::from_secs(3));
+    }
+
+    /// Sets the `SUPERCHAIN_*` variables, unsetting all others
+    fn set_env(vars: &[(&str, &str)]) {
+        for name in [
+            "SUPERCHAIN_ENDPOINT",
+            "SUPERCHAIN_USERNAME",
+            "SUPERCHAIN_PASSWORD",
+            "SUPERCHAIN_SECURE",
+            "SUPERCHAIN_TOKEN",
+        ] {
+            std::env::remove_var(name);
+        }
+        for (name, value) in vars {
+            std::env::set_var(name, value);
+        }
+    }
+
+    // a single test, as the variables are shared by all tests of the process
+    #[test]
+    fn from_env_reads_the_superchain_variables() {
+        set_env(&[
+            ("SUPERCHAIN_ENDPOINT", "gateway:8080"),
+            ("SUPERCHAIN_SECURE", "false"),
+            ("SUPERCHAIN_USERNAME", "user"),
+            ("SUPERCHAIN_PASSWORD", "secret"),
+        ]);
+        let config = ClientBuilder::from_env().unwrap().config;
+        assert_eq!(config.endpoint, "gateway:8080");
+        assert!(!config.is_secure);
+        assert_eq!(config.username.as_deref(), Some("user"));
+        assert_eq!(config.password.as_deref(), Some("secret"));
+
+        set_env(&[("SUPERCHAIN_TOKEN", "abc")]);
+        let config = ClientBuilder::from_env().unwrap().config;
+        assert_eq!((config.username, config.password), (None, None));
+        assert_eq!(
+            config.headers,
+            [("Authorization".to_owned(), "Bearer abc".to_owned())]
+        );
+
+        set_env(&[("SUPERCHAIN_SECURE", "maybe"), ("SUPERCHAIN_TOKEN", "abc")]);
+        assert!(matches!(ClientBuilder::from_env(), Err(Error::Custom(_))));
+
+        // unless the fallbacks are set in the environment running the tests
+        set_env(&[]);
+        if USERNAME.is_none() {
+            assert!(matches!(
+                ClientBuilder::from_env(),
+                Err(Error::MissingEnvVar("SUPERCHAIN_USERNAME"))
+            ));
+        }
+    }
+
+    #[test]
+    fn endpoint_accepts_strings_and_urls() {
+        let endpoint = |builder: ClientBuilder| (builder.config.endpoint, builder.config.is_secure);
+
+        let host = String::from("app.superchain.network");
+        assert_eq!(
+            endpoint(ClientBuilder::default().secure(true).endpoint(host)),
+            ("app.superchain.network".to_owned(), true)
+        );
+        assert_eq!(
+            endpoint(ClientBuilder::default().endpoint("http://localhost:8080/")),
+            ("localhost:8080".to_owned(), false)
+        );
+
+        let url = url::Url::parse("wss://app.superchain.network/v1").unwrap();
+        assert_eq!(
+            endpoint(ClientBuilder::default().secure(false).endpoint(&url)),
+            ("app.superchain.network/v1".to_owned(), true)
+        );
+        assert_eq!(
+            endpoint(ClientBuilder::default().secure(false).endpoint(url)),
+            ("app.superchain.network/v1".to_owned(), true)
+        );
+    }
+
+    #[test]
+    fn debug_output_redacts_credentials() {
+        let builder = ClientBuilder::default()
+            .credential("user", "hunter2")
+            .header("Authorization", "Bearer tok-secret")
+            .header("X-Trace", "visible");
+        #[cfg(feature = "hmac")]
+        let builder =
+            builder.hmac_signer("key", "hmac-secret", crate::core::auth::HmacAlgo::Sha256);
+
+        let debug = format!("{builder:?}");
+
+        assert!(debug.contains("***"), "{debug}");
+        for secret in ["hunter2", "tok-secret", "hmac-secret"] {
+            assert!(!debug.contains(secret), "{debug}");
         }
+        assert!(debug.contains("\"user\""), "{debug}");
+        assert!(debug.contains("visible"), "{debug}");
     }
 }
@@ -1,35 +1,100 @@
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
+
 use async_trait::async_trait;
-use futures::StreamExt;
+use bytes::Bytes;
+#[cfg(feature = "uniswap")]
+use ethers_core::types::Address;
+#[cfg(feature = "uniswap")]
+use futures::stream::FuturesUnordered;
+use futures::{StreamExt, TryStreamExt};
+use serde::de::DeserializeOwned;
+use tokio::sync::watch;
+use tracing::warn;
 
+#[cfg(not(target_arch = "wasm32"))]
+use super::heartbeat::{with_heartbeat, StreamItem};
 use super::{
-    error::ResponseError,
-    provider::{
-        BtcProvider, ChainProvider, Provider, StreamResponse, UniswapV2Provider, UniswapV3Provider,
-    },
+    decode::{decode_json_stream, decode_record},
+    error::{Error, ResponseError, Result},
+    join::join_blocks_with_logs,
+    provider::{ChainProvider, Cursored, Provider, ReplayProvider, ResponseStream, StreamResponse},
     requests::{
-        self,
-        blocks::GetBlocksRequest,
-        btc::{GetBtcBlocksRequest, GetBtcTxsRequest},
-        fuel::{GetFuelReceiptsRequest, GetUtxoRequest},
-        logs::GetLogsRequest,
-        txs::GetTxsRequest,
-        uniswap_v2::GetPairsRequest,
-        uniswap_v3::GetPoolsRequest,
+        balances::GetBalancesRequest, blocks::GetBlocksRequest,
+        contracts::GetContractCreationsRequest, erc721::GetErc721TransfersRequest,
+        internal_txs::GetInternalTxsRequest, logs::GetLogsRequest,
+        pending_txs::GetPendingTxsRequest, receipts::GetReceiptsRequest, txs::GetTxsRequest,
+        withdrawals::GetWithdrawalsRequest,
+    },
+    types::{
+        ethereum::Block,
+        format::Format,
+        query::Bound,
+        status::{HealthReport, Status},
+        ChainId,
+    },
+    utils::system_now,
+};
+#[cfg(feature = "uniswap")]
+use crate::{
+    core::{
+        derive::trades_from_prices,
+        types::{
+            uniswap_v2::{self, Trade},
+            uniswap_v3,
+        },
+    },
+    provider::{UniswapV2Provider, UniswapV3Provider},
+    requests::{self, uniswap_v2::GetPairsRequest, uniswap_v3::GetPoolsRequest},
+};
+#[cfg(feature = "btc")]
+use crate::{
+    provider::BtcProvider,
+    requests::btc::{GetBtcBlocksRequest, GetBtcTxsRequest},
+};
+#[cfg(feature = "curve")]
+use crate::{
+    provider::CurveProvider,
+    requests::curve::{GetCrvPoolRequest, GetCrvPriceRequest, GetCrvTokenRequest},
+};
+#[cfg(feature = "fuel")]
+use crate::{
+    provider::FuelProvider,
+    requests::fuel::{
+        GetFuelBlocksRequest, GetFuelLogsRequest, GetFuelReceiptsRequest, GetFuelTxsRequest,
+        GetSparkOrderRequest, GetUtxoRequest,
     },
-    types::{format::Format, status::Status},
 };
 use crate::{
-    provider::{CurveProvider, Erc20Provider, FuelProvider},
+    provider::{Erc20Provider, Erc721Provider},
     requests::{
-        curve::{GetCrvPoolRequest, GetCrvPriceRequest, GetCrvTokenRequest},
         erc20::{GetErc20ApprovalsRequest, GetErc20Request, GetErc20TransferssRequest},
-        fuel::{GetFuelBlocksRequest, GetFuelLogsRequest, GetFuelTxsRequest, GetSparkOrderRequest},
         transfers::GetTransfersRequest,
     },
 };
 
+/// The age of the latest block after which [`Client::health`] considers a
+/// chain stale
+pub const DEFAULT_MAX_BLOCK_AGE: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Clone)]
 pub struct Client<T> {
     inner: T,
+    default_chain: Option<ChainId>,
+}
+
+impl<T> Client<T> {
+    /// Replaces empty `chains` of a request with the default chain, if any
+    fn apply_default_chain(&self, chains: &mut HashSet<ChainId>) {
+        let Some(default_chain) = self.default_chain else {
+            return;
+        };
+        if chains.is_empty() {
+            *chains = HashSet::from([default_chain]);
+        }
+    }
 }
 
 impl<T> Client<T>
@@ -37,20 +102,508 @@ where
     T: Provider,
 {
     pub fn new(inner: T) -> Self {
-        Self { inner }
+        Self {
+            inner,
+            default_chain: None,
+        }
+    }
+
+    /// Returns the provider of the client
+    pub fn provider(&self) -> &T {
+        &self.inner
     }
 
+    /// Sets the chain requests with empty `chains` are sent for, see
+    /// [`ClientBuilder::default_chain`](crate::ClientBuilder::default_chain)
+    pub(crate) fn with_default_chain(mut self, chain: Option<ChainId>) -> Self {
+        self.default_chain = chain;
+        self
+    }
+
+    /// Streams the status of all services
+    ///
+    /// Error responses are returned as
+    /// [`Error::ErrorResponse`](crate::Error::ErrorResponse) for both the HTTP
+    /// and the WebSocket provider.
     pub async fn get_status(&self) -> StreamResponse<Status> {
         let raw_data_stream = self.inner.get_status_by_format(Format::JsonStream).await?;
-        let raw_data_stream = ResponseError::map_stream(raw_data_stream);
+        Ok(decode_json_stream(raw_data_stream))
+    }
+
+    /// Collects the status of all services into a map keyed by chain and
+    /// service
+    ///
+    /// Consumes the status stream to completion, the snapshot is a bounded
+    /// response for both providers. If a service reports several entities
+    /// for a chain, the row that arrived last is kept.
+    pub async fn get_status_map(&self) -> Result<HashMap<(ChainId, String), Status>> {
+        self.get_status()
+            .await?
+            .try_fold(HashMap::new(), |mut map, status| async move {
+                map.insert((status.chain, status.service.clone()), status);
+                Ok(map)
+            })
+            .await
+    }
+
+    /// Summarizes the status of all services into the health of every chain
+    ///
+    /// A chain is unhealthy if any of its services reports a failure or its
+    /// latest block is older than [`DEFAULT_MAX_BLOCK_AGE`], see
+    /// [`health_with_max_block_age`](Self::health_with_max_block_age) for
+    /// chains with slow blocks.
+    pub async fn health(&self) -> Result<HealthReport> {
+        self.health_with_max_block_age(DEFAULT_MAX_BLOCK_AGE).await
+    }
+
+    /// Summarizes the status of all services into the health of every chain,
+    /// flagging chains whose latest block is older than `max_block_age`
+    pub async fn health_with_max_block_age(&self, max_block_age: Duration) -> Result<HealthReport> {
+        let statuses: Vec<Status> = self.get_status().await?.try_collect().await?;
+        Ok(HealthReport::from_statuses(
+            statuses,
+            system_now(),
+            max_block_age,
+        ))
+    }
+
+    /// Pumps a stream into a [`watch`] channel holding its latest item
+    ///
+    /// The receiver starts at `None` and only ever holds the most recent item,
+    /// intermediate items that were not observed in time are dropped. The
+    /// spawned task ends when the stream ends, yields an error, or all
+    /// receivers are dropped; in the first two cases the last item stays
+    /// readable but [`watch::Receiver::changed`] returns an error.
+    ///
+    /// Must be called from within a Tokio runtime, on wasm32 the task is
+    /// spawned on the browser's event loop instead.
+    pub fn watch_latest<R>(&self, stream: StreamResponse<R>) -> Result<watch::Receiver<Option<R>>>
+    where
+        R: Send + Sync + 'static,
+    {
+        let mut stream = stream?;
+        let (sender, receiver) = watch::channel(None);
+
+        let task = async move {
+            loop {
+                tokio::select! {
+                    item = stream.next() => match item {
+                        Some(Ok(item)) => {
+                            if sender.send(Some(item)).is_err() {
+                                break;
+                            }
+                        }
+                        Some(Err(err)) => {
+                            warn!("watched stream failed: {err}");
+                            break;
+                        }
+                        None => break,
+                    },
+                    _ = sender.closed() => break,
+                }
+            }
+        };
+        #[cfg(not(target_arch = "wasm32"))]
+        tokio::spawn(task);
+        #[cfg(target_arch = "wasm32")]
+        wasm_bindgen_futures::spawn_local(task);
+
+        Ok(receiver)
+    }
+}
+
+impl<T> Client<T>
+where
+    T: Provider + Clone + Send + Sync + 'static,
+{
+    /// Wraps a stream of this client so that it yields a
+    /// [`StreamItem::Heartbeat`] whenever no item arrived for `interval`
+    ///
+    /// Not available on wasm32, which lacks the timer of the heartbeat.
+    ///
+    /// Heartbeats are only yielded while the provider is
+    /// [ready](Provider::ready), otherwise the stream ends with
+    /// [`Error::BackendShutDown`](crate::Error::BackendShutDown). This allows
+    /// telling a quiet subscription, e.g. with a filter that rarely matches,
+    /// from a dead connection.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn stream_with_heartbeat<R>(
+        &self,
+        stream: ResponseStream<R>,
+        interval: Duration,
+    ) -> ResponseStream<StreamItem<R>>
+    where
+        R: Send + 'static,
+    {
+        let provider = self.inner.clone();
+        with_heartbeat(stream, interval, move || provider.ready())
+    }
+
+    /// Wraps a stream of this client so that it ends once `token` is
+    /// cancelled
+    ///
+    /// The inner stream is dropped as soon as the token is cancelled, even if
+    /// the returned stream is kept, which ends the subscription: the
+    /// WebSocket provider unsubscribes it at the server and does not
+    /// re-subscribe it on reconnect. This allows shutting down several
+    /// subscriptions at once without dropping the client.
+    #[cfg(feature = "cancellation")]
+    pub fn with_cancellation<R>(
+        &self,
+        stream: ResponseStream<R>,
+        token: tokio_util::sync::CancellationToken,
+    ) -> ResponseStream<R>
+    where
+        R: Send + 'static,
+    {
+        let state = (stream, Box::pin(token.cancelled_owned()));
+        let stream = futures::stream::unfold(state, |(mut stream, mut cancelled)| async move {
+            match futures::future::select(stream.next(), cancelled.as_mut()).await {
+                futures::future::Either::Left((Some(item), _)) => Some((item, (stream, cancelled))),
+                _ => None,
+            }
+        });
+        ResponseStream::new(stream)
+    }
+}
+
+/// The response of a `count_only` request
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum CountResponse {
+    Scalar(u64),
+    Object { count: u64 },
+}
+
+impl CountResponse {
+    async fn from_stream(stream: crate::provider::ResponseStream<Bytes>) -> Result<u64> {
+        let body = ResponseError::map_stream(stream)
+            .try_fold(Vec::new(), |mut body, chunk| {
+                body.extend_from_slice(&chunk);
+                futures::future::ok(body)
+            })
+            .await?;
+        let count = match serde_json::from_slice::<CountResponse>(&body)? {
+            Self::Scalar(count) | Self::Object { count } => count,
+        };
+        Ok(count)
+    }
+}
+
+impl<T> Client<T>
+where
+    T: ChainProvider + Send + Sync,
+{
+    /// Returns the number of logs matching the request without streaming
+    /// the logs themselves
+    ///
+    /// Requires server support for the `count_only` flag.
+    pub async fn count_logs(&self, mut request: GetLogsRequest) -> Result<u64> {
+        request.count_only = true;
+        let stream = self
+            .get_logs_by_format(request, Format::Json, false)
+            .await?;
+        CountResponse::from_stream(stream).await
+    }
+
+    /// Returns the number of transactions matching the request without
+    /// streaming the transactions themselves
+    ///
+    /// Requires server support for the `count_only` flag.
+    pub async fn count_txs(&self, mut request: GetTxsRequest) -> Result<u64> {
+        request.count_only = true;
+        let stream = self.get_txs_by_format(request, Format::Json, false).await?;
+        CountResponse::from_stream(stream).await
+    }
+
+    /// Returns the number of transfers matching the request without
+    /// streaming the transfers themselves
+    ///
+    /// Requires server support for the `count_only` flag.
+    pub async fn count_transfers(&self, mut request: GetTransfersRequest) -> Result<u64> {
+        request.count_only = true;
+        let stream = self
+            .get_transfers_by_format(request, Format::Json, false)
+            .await?;
+        CountResponse::from_stream(stream).await
+    }
+
+    /// Streams the requested blocks as untyped JSON values
+    #[cfg(feature = "json-value")]
+    pub async fn get_blocks_json(
+        &self,
+        request: GetBlocksRequest,
+    ) -> StreamResponse<serde_json::Value> {
+        let raw_data_stream = self
+            .get_blocks_by_format(request, Format::JsonStream, false)
+            .await?;
+        Ok(decode_json_stream(raw_data_stream))
+    }
+
+    /// Streams the requested logs as untyped JSON values
+    #[cfg(feature = "json-value")]
+    pub async fn get_logs_json(
+        &self,
+        request: GetLogsRequest,
+    ) -> StreamResponse<serde_json::Value> {
+        let raw_data_stream = self
+            .get_logs_by_format(request, Format::JsonStream, false)
+            .await?;
+        Ok(decode_json_stream(raw_data_stream))
+    }
+
+    /// Streams the requested transactions as untyped JSON values
+    #[cfg(feature = "json-value")]
+    pub async fn get_txs_json(&self, request: GetTxsRequest) -> StreamResponse<serde_json::Value> {
+        let raw_data_stream = self
+            .get_txs_by_format(request, Format::JsonStream, false)
+            .await?;
+        Ok(decode_json_stream(raw_data_stream))
+    }
+
+    /// Streams the requested transfers as untyped JSON values
+    #[cfg(feature = "json-value")]
+    pub async fn get_transfers_json(
+        &self,
+        request: GetTransfersRequest,
+    ) -> StreamResponse<serde_json::Value> {
+        let raw_data_stream = self
+            .get_transfers_by_format(request, Format::JsonStream, false)
+            .await?;
+        Ok(decode_json_stream(raw_data_stream))
+    }
+}
+
+impl<T> Client<T>
+where
+    T: ChainProvider + Send + Sync,
+{
+    /// Streams every block of `blocks` together with its logs matching `logs`
+    ///
+    /// Both requests are usually subscriptions, i.e. have a `to_block` of
+    /// [`Bound::Subscribe`](crate::query::Bound::Subscribe), and should cover
+    /// the same chain and block range. Blocks and logs are yielded as untyped
+    /// JSON values, joined by the `number` of the block and the
+    /// `block_number` of the log.
+    ///
+    /// A block is yielded once its logs are considered complete, which is
+    /// when a log of a greater height arrives, or the block after the next
+    /// one, which gives the logs subscription one block of grace to catch
+    /// up. The remaining blocks are yielded when both streams ended.
+    ///
+    /// Delivery is at-least-once: if a block arrives again at a height that
+    /// was already yielded, as happens on reorgs, it is yielded again together
+    /// with the logs carrying its hash. Logs of a replaced block that arrive
+    /// after the next block may be missing from the pair.
+    pub async fn subscribe_blocks_with_logs(
+        &self,
+        blocks: GetBlocksRequest,
+        logs: GetLogsRequest,
+    ) -> StreamResponse<(serde_json::Value, Vec<serde_json::Value>)> {
+        let blocks = self
+            .get_blocks_by_format(blocks, Format::JsonStream, false)
+            .await?;
+        let logs = self
+            .get_logs_by_format(logs, Format::JsonStream, false)
+            .await?;
+
+        Ok(join_blocks_with_logs(
+            decode_json_stream(blocks),
+            decode_json_stream(logs),
+        ))
+    }
+
+    /// Returns all blocks of a bounded block range, failing if any block is
+    /// missing from the response
+    ///
+    /// Both bounds of the request must be [`Bound::Exact`] and no timestamp
+    /// filter may be set. The received `number`s of every requested chain,
+    /// or of every chain in the response if the request has none, are
+    /// checked to cover the range, which excludes `to_block`, without gaps, otherwise an
+    /// [`Error::Custom`](crate::Error::Custom) listing the missing ranges is
+    /// returned. An empty response is an error as well. Meant for backfills
+    /// that can not tolerate silent data loss.
+    pub async fn get_blocks_complete(&self, request: GetBlocksRequest) -> Result<Vec<Block>> {
+        let (Bound::Exact(from), Bound::Exact(to)) = (request.from_block, request.to_block) else {
+            return Err(Error::InvalidRequest(
+                "get_blocks_complete requires exact block bounds",
+            ));
+        };
+        if request.from_timestamp.is_some() || request.to_timestamp.is_some() {
+            return Err(Error::InvalidRequest(
+                "get_blocks_complete does not support timestamp filters",
+            ));
+        }
+        let (Ok(from), Ok(to)) = (u64::try_from(from), u64::try_from(to)) else {
+            return Err(Error::InvalidRequest(
+                "get_blocks_complete requires non-negative block bounds",
+            ));
+        };
+        if from >= to {
+            return Err(Error::InvalidRequest(
+                "get_blocks_complete requires from_block < to_block",
+            ));
+        }
+        let chains = request.chains.clone();
+
+        let raw_data_stream = self
+            .get_blocks_by_format(request, Format::JsonStream, false)
+            .await?;
+        let blocks: Vec<Block> = decode_json_stream(raw_data_stream).try_collect().await?;
+        if blocks.is_empty() {
+            return Err(Error::Custom(
+                format!("no blocks received for {from}..{to}").into(),
+            ));
+        }
+
+        let mut received: HashMap<ChainId, Vec<u64>> = chains
+            .into_iter()
+            .map(|chain| (chain, Vec::new()))
+            .collect();
+        for block in &blocks {
+            received.entry(block.chain).or_default().push(block.number);
+        }
+        let mut received: Vec<_> = received.into_iter().collect();
+        received.sort_by(|(a, _), (b, _)| a.as_ref().cmp(b.as_ref()));
+
+        let mut gaps = Vec::new();
+        for (chain, mut numbers) in received {
+            numbers.retain(|number| (from..to).contains(number));
+            numbers.sort_unstable();
+            numbers.dedup();
+            let mut next = from;
+            for number in numbers.into_iter().chain([to]) {
+                if number > next {
+                    gaps.push((chain, next, number - 1));
+                }
+                next = number + 1;
+            }
+        }
+        if !gaps.is_empty() {
+            return Err(Error::Custom(
+                format!("missing blocks: {}", summarize_gaps(&gaps)).into(),
+            ));
+        }
+
+        Ok(blocks)
+    }
+}
+
+impl<T> Client<T>
+where
+    T: ChainProvider + Clone + Send + Sync + 'static,
+{
+    /// Streams the blocks of a timestamp range by requesting it in
+    /// consecutive windows of length `window`
+    ///
+    /// Every window is a copy of `request` with `from_timestamp` and
+    /// `to_timestamp` set to its bounds, so the chains and block bounds of the
+    /// request apply to all of them, e.g. use `Bound::Exact(0)..Bound::Latest`
+    /// to not limit the blocks further. Timestamps are in seconds, the lower
+    /// bound is inclusive and the upper one exclusive, just like on the
+    /// request. The windows are requested one after another, the next one
+    /// only once the previous one was read to the end, which avoids one
+    /// large query on long backfills and makes the progress observable.
+    pub fn get_blocks_by_time_windows<R>(
+        &self,
+        request: GetBlocksRequest,
+        from_timestamp: i64,
+        to_timestamp: i64,
+        window: Duration,
+    ) -> StreamResponse<R>
+    where
+        R: DeserializeOwned + Send + 'static,
+    {
+        let window = i64::try_from(window.as_secs()).unwrap_or(i64::MAX);
+        if window == 0 {
+            return Err(Error::InvalidRequest(
+                "time windows must be at least one second long",
+            ));
+        }
+
+        let client = self.clone();
+        let blocks = futures::stream::iter(time_windows(from_timestamp, to_timestamp, window))
+            .then(move |(from, to)| {
+                let client = client.clone();
+                let request = GetBlocksRequest {
+                    from_timestamp: Some(from),
+                    to_timestamp: Some(to),
+                    ..request.clone()
+                };
+                async move {
+                    client
+                        .get_blocks_by_format(request, Format::JsonStream, false)
+                        .await
+                        .map(decode_json_stream::<R>)
+                }
+            })
+            .try_flatten();
+
+        Ok(ResponseStream::new(blocks))
+    }
+}
+
+/// Splits `from..to` into consecutive windows of at most `window` seconds
+fn time_windows(from: i64, to: i64, window: i64) -> impl Iterator<Item = (i64, i64)> {
+    std::iter::successors(Some(from), move |start| start.checked_add(window))
+        .take_while(move |start| *start < to)
+        .map(move |start| (start, start.saturating_add(window).min(to)))
+}
 
-        let records = raw_data_stream
-            .map(|chunk_result| {
-                chunk_result.and_then(|chunk| Ok(serde_json::from_slice::<Status>(&chunk)?))
+impl<T> Client<T>
+where
+    T: ReplayProvider + Send + Sync,
+{
+    /// Streams the requested logs along with the cursors to resume them,
+    /// starting right after `cursor` if given
+    ///
+    /// This allows an indexer to persist the cursor of the last processed
+    /// log and to continue after it in a new process. Logs are returned in
+    /// order and delivered at least once: a cursor is only reported with the
+    /// last log of each server message, so resuming from the latest reported
+    /// cursor may repeat the logs processed after it, but never skips any.
+    /// Cursors are only valid within an epoch, see [`Cursored::epoch`]. Only the
+    /// WebSocket provider reports cursors, over HTTP a cursor obtained
+    /// elsewhere can only be passed in.
+    pub async fn replay_logs<R>(
+        &self,
+        request: GetLogsRequest,
+        cursor: Option<String>,
+    ) -> StreamResponse<Cursored<R>>
+    where
+        R: DeserializeOwned + Send + 'static,
+    {
+        let records = self.replay_logs_records(request, cursor).await?;
+        let logs = records.enumerate().map(|(row_index, record)| {
+            let Cursored {
+                item,
+                cursor,
+                epoch,
+            } = record?;
+            let item = decode_record(row_index as u64, item.into())?;
+            Ok(Cursored {
+                item,
+                cursor,
+                epoch,
             })
-            .boxed();
+        });
+        Ok(ResponseStream::new(logs))
+    }
+}
 
-        Ok(records)
+#[async_trait]
+impl<T> ReplayProvider for Client<T>
+where
+    T: ReplayProvider + Send + Sync,
+{
+    async fn replay_logs_records(
+        &self,
+        mut request: GetLogsRequest,
+        cursor: Option<String>,
+    ) -> StreamResponse<Cursored<Bytes>> {
+        self.apply_default_chain(&mut request.chains);
+        request.validate()?;
+        self.inner.replay_logs_records(request, cursor).await
     }
 }
 
@@ -61,10 +614,12 @@ where
 {
     async fn get_blocks_by_format(
         &self,
-        request: GetBlocksRequest,
+        mut request: GetBlocksRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
+        self.apply_default_chain(&mut request.chains);
+        request.validate()?;
         self.inner
             .get_blocks_by_format(request, format, deltas)
             .await
@@ -72,34 +627,159 @@ where
 
     async fn get_logs_by_format(
         &self,
-        request: GetLogsRequest,
+        mut request: GetLogsRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
+        self.apply_default_chain(&mut request.chains);
+        request.validate()?;
         self.inner.get_logs_by_format(request, format, deltas).await
     }
 
     async fn get_txs_by_format(
         &self,
-        request: GetTxsRequest,
+        mut request: GetTxsRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
+        self.apply_default_chain(&mut request.chains);
+        request.validate()?;
         self.inner.get_txs_by_format(request, format, deltas).await
     }
 
     async fn get_transfers_by_format(
         &self,
-        request: GetTransfersRequest,
+        mut request: GetTransfersRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
+        self.apply_default_chain(&mut request.chains);
+        request.validate()?;
         self.inner
             .get_transfers_by_format(request, format, deltas)
             .await
     }
+
+    async fn get_receipts_by_format(
+        &self,
+        mut request: GetReceiptsRequest,
+        format: Format,
+        deltas: bool,
+    ) -> StreamResponse<Bytes> {
+        self.apply_default_chain(&mut request.chains);
+        request.validate()?;
+        self.inner
+            .get_receipts_by_format(request, format, deltas)
+            .await
+    }
+
+    async fn get_internal_txs_by_format(
+        &self,
+        mut request: GetInternalTxsRequest,
+        format: Format,
+        deltas: bool,
+    ) -> StreamResponse<Bytes> {
+        self.apply_default_chain(&mut request.chains);
+        request.validate()?;
+        self.inner
+            .get_internal_txs_by_format(request, format, deltas)
+            .await
+    }
+
+    async fn get_contract_creations_by_format(
+        &self,
+        mut request: GetContractCreationsRequest,
+        format: Format,
+        deltas: bool,
+    ) -> StreamResponse<Bytes> {
+        self.apply_default_chain(&mut request.chains);
+        request.validate()?;
+        self.inner
+            .get_contract_creations_by_format(request, format, deltas)
+            .await
+    }
+
+    async fn get_withdrawals_by_format(
+        &self,
+        mut request: GetWithdrawalsRequest,
+        format: Format,
+        deltas: bool,
+    ) -> StreamResponse<Bytes> {
+        self.apply_default_chain(&mut request.chains);
+        request.validate()?;
+        self.inner
+            .get_withdrawals_by_format(request, format, deltas)
+            .await
+    }
+
+    async fn get_balances_by_format(
+        &self,
+        mut request: GetBalancesRequest,
+        format: Format,
+        deltas: bool,
+    ) -> StreamResponse<Bytes> {
+        self.apply_default_chain(&mut request.chains);
+        self.inner
+            .get_balances_by_format(request, format, deltas)
+            .await
+    }
+
+    async fn get_pending_txs_by_format(
+        &self,
+        mut request: GetPendingTxsRequest,
+        format: Format,
+    ) -> StreamResponse<Bytes> {
+        self.apply_default_chain(&mut request.chains);
+        self.inner.get_pending_txs_by_format(request, format).await
+    }
 }
 
+#[cfg(feature = "uniswap")]
+impl<T> Client<T>
+where
+    T: UniswapV2Provider + Send + Sync,
+{
+    /// Streams the swaps of the requested Uniswap V2 pairs
+    ///
+    /// Trades are derived from the prices stream, see
+    /// [`trades_from_prices`] for how the side of a trade is inferred.
+    pub async fn get_uniswap_v2_trades(
+        &self,
+        request: requests::uniswap_v2::GetPricesRequest,
+    ) -> StreamResponse<Trade> {
+        let raw_data_stream = self
+            .get_prices_by_format(request, Format::JsonStream, false)
+            .await?;
+
+        Ok(trades_from_prices(decode_json_stream(raw_data_stream)))
+    }
+
+    /// Streams every pair the given Uniswap V2 factory created on `chain`
+    ///
+    /// The request covers the full history of the chain, from block 0 up to
+    /// the latest block, so all pairs are found without knowing when the
+    /// factory was deployed.
+    pub async fn discover_pairs(
+        &self,
+        factory: Address,
+        chain: ChainId,
+    ) -> StreamResponse<uniswap_v2::Pair> {
+        let request = GetPairsRequest {
+            chains: HashSet::from([chain]),
+            from_block: Bound::Exact(0),
+            to_block: Bound::Latest,
+            factory_address__in: HashSet::from([factory]),
+            ..Default::default()
+        };
+        let raw_data_stream = self
+            .get_pairs_by_format(request, Format::JsonStream, false)
+            .await?;
+
+        Ok(decode_json_stream(raw_data_stream))
+    }
+}
+
+#[cfg(feature = "uniswap")]
 #[async_trait]
 impl<T> UniswapV2Provider for Client<T>
 where
@@ -107,10 +787,12 @@ where
 {
     async fn get_pairs_by_format(
         &self,
-        request: GetPairsRequest,
+        mut request: GetPairsRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
+        self.apply_default_chain(&mut request.chains);
+        request.validate()?;
         self.inner
             .get_pairs_by_format(request, format, deltas)
             .await
@@ -118,16 +800,125 @@ where
 
     async fn get_prices_by_format(
         &self,
-        request: requests::uniswap_v2::GetPricesRequest,
+        mut request: requests::uniswap_v2::GetPricesRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
+        self.apply_default_chain(&mut request.chains);
+        request.validate()?;
         self.inner
             .get_prices_by_format(request, format, deltas)
             .await
     }
 }
 
+/// The number of blocks [`Client::uniswap_v3_latest_prices`] looks back for
+/// the latest price of a pool
+#[cfg(feature = "uniswap")]
+const LATEST_PRICES_LOOKBACK: u64 = 1_000;
+
+#[cfg(feature = "uniswap")]
+impl<T> Client<T>
+where
+    T: UniswapV3Provider + Send + Sync,
+{
+    /// Returns the latest price of each of the given Uniswap V3 pools
+    ///
+    /// Only the last 1000 blocks of the default chains
+    /// are searched, pools without any event in that window are missing from
+    /// the result. The returned prices are those after the last event of each
+    /// pool and may therefore be as old as the window itself; compare
+    /// [`uniswap_v3::Price::block_number`] or `timestamp` against the chain
+    /// head when freshness matters.
+    pub async fn uniswap_v3_latest_prices(
+        &self,
+        pools: impl IntoIterator<Item = Address>,
+    ) -> Result<HashMap<Address, uniswap_v3::Price>> {
+        let request = requests::uniswap_v3::GetPricesRequest {
+            from_block: Bound::FromLatest(LATEST_PRICES_LOOKBACK),
+            to_block: Bound::Latest,
+            pool_address__in: pools.into_iter().collect(),
+            ..Default::default()
+        };
+        if request.pool_address__in.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let raw_data_stream = self
+            .get_prices_by_format(request, Format::JsonStream, false)
+            .await?;
+
+        decode_json_stream::<uniswap_v3::Price>(raw_data_stream)
+            .try_fold(HashMap::new(), |mut latest, price| {
+                latest.insert(price.pool_address, price);
+                futures::future::ok(latest)
+            })
+            .await
+    }
+
+    /// Opens a separate stream of Uniswap V3 prices for every pool in
+    /// `request.pool_address__in`
+    ///
+    /// All other filters of the request apply to every stream. Unlike a single
+    /// request for all pools, each stream can be consumed and dropped
+    /// independently, so a slow pool does not hold back the others. Every
+    /// stream is its own subscription, which counts against the limit of 256
+    /// concurrent subscriptions per WebSocket connection. Fails if any of the
+    /// streams can not be opened.
+    pub async fn uniswap_v3_prices_per_pool(
+        &self,
+        request: requests::uniswap_v3::GetPricesRequest,
+    ) -> Result<HashMap<Address, ResponseStream<uniswap_v3::Price>>> {
+        let mut pending: FuturesUnordered<_> = request
+            .pool_address__in
+            .iter()
+            .map(|pool| {
+                let request = requests::uniswap_v3::GetPricesRequest {
+                    pool_address__in: HashSet::from([*pool]),
+                    ..request.clone()
+                };
+                async move {
+                    let stream = self
+                        .get_prices_by_format(request, Format::JsonStream, false)
+                        .await?;
+                    Ok::<_, crate::Error>((*pool, decode_json_stream(stream)))
+                }
+            })
+            .collect();
+
+        let mut streams = HashMap::with_capacity(request.pool_address__in.len());
+        while let Some((pool, stream)) = pending.try_next().await? {
+            streams.insert(pool, stream);
+        }
+
+        Ok(streams)
+    }
+
+    /// Streams every pool the given Uniswap V3 factory created on `chain`
+    ///
+    /// Like [`Client::discover_pairs`], the request covers the full history
+    /// of the chain.
+    pub async fn discover_pools(
+        &self,
+        factory: Address,
+        chain: ChainId,
+    ) -> StreamResponse<uniswap_v3::Pool> {
+        let request = GetPoolsRequest {
+            chains: HashSet::from([chain]),
+            from_block: Bound::Exact(0),
+            to_block: Bound::Latest,
+            factory_address__in: HashSet::from([factory]),
+            ..Default::default()
+        };
+        let raw_data_stream = self
+            .get_pools_by_format(request, Format::JsonStream, false)
+            .await?;
+
+        Ok(decode_json_stream(raw_data_stream))
+    }
+}
+
+#[cfg(feature = "uniswap")]
 #[async_trait]
 impl<T> UniswapV3Provider for Client<T>
 where
@@ -135,10 +926,12 @@ where
 {
     async fn get_pools_by_format(
         &self,
-        request: GetPoolsRequest,
+        mut request: GetPoolsRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
+        self.apply_default_chain(&mut request.chains);
+        request.validate()?;
         self.inner
             .get_pools_by_format(request, format, deltas)
             .await
@@ -146,16 +939,19 @@ where
 
     async fn get_prices_by_format(
         &self,
-        request: requests::uniswap_v3::GetPricesRequest,
+        mut request: requests::uniswap_v3::GetPricesRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
+        self.apply_default_chain(&mut request.chains);
+        request.validate()?;
         self.inner
             .get_prices_by_format(request, format, deltas)
             .await
     }
 }
 
+#[cfg(feature = "curve")]
 #[async_trait]
 impl<T> CurveProvider for Client<T>
 where
@@ -163,10 +959,12 @@ where
 {
     async fn get_tokens_by_format(
         &self,
-        request: GetCrvTokenRequest,
+        mut request: GetCrvTokenRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
+        self.apply_default_chain(&mut request.chains);
+        request.validate()?;
         self.inner
             .get_tokens_by_format(request, format, deltas)
             .await
@@ -174,10 +972,12 @@ where
 
     async fn get_pools_by_format(
         &self,
-        request: GetCrvPoolRequest,
+        mut request: GetCrvPoolRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
+        self.apply_default_chain(&mut request.chains);
+        request.validate()?;
         self.inner
             .get_pools_by_format(request, format, deltas)
             .await
@@ -185,10 +985,12 @@ where
 
     async fn get_prices_by_format(
         &self,
-        request: GetCrvPriceRequest,
+        mut request: GetCrvPriceRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
+        self.apply_default_chain(&mut request.chains);
+        request.validate()?;
         self.inner
             .get_prices_by_format(request, format, deltas)
             .await
@@ -202,10 +1004,12 @@ where
 {
     async fn get_erc20_by_format(
         &self,
-        request: GetErc20Request,
+        mut request: GetErc20Request,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
+        self.apply_default_chain(&mut request.chains);
+        request.validate()?;
         self.inner
             .get_erc20_by_format(request, format, deltas)
             .await
@@ -213,10 +1017,12 @@ where
 
     async fn get_erc20_approval_by_format(
         &self,
-        request: GetErc20ApprovalsRequest,
+        mut request: GetErc20ApprovalsRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
+        self.apply_default_chain(&mut request.chains);
+        request.validate()?;
         self.inner
             .get_erc20_approval_by_format(request, format, deltas)
             .await
@@ -224,16 +1030,38 @@ where
 
     async fn get_erc20_transfers_by_format(
         &self,
-        request: GetErc20TransferssRequest,
+        mut request: GetErc20TransferssRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
+        self.apply_default_chain(&mut request.chains);
+        request.validate()?;
         self.inner
             .get_erc20_transfers_by_format(request, format, deltas)
             .await
     }
 }
 
+#[async_trait]
+impl<T> Erc721Provider for Client<T>
+where
+    T: Erc721Provider + Send + Sync,
+{
+    async fn get_erc721_transfers_by_format(
+        &self,
+        mut request: GetErc721TransfersRequest,
+        format: Format,
+        deltas: bool,
+    ) -> StreamResponse<Bytes> {
+        self.apply_default_chain(&mut request.chains);
+        request.validate()?;
+        self.inner
+            .get_erc721_transfers_by_format(request, format, deltas)
+            .await
+    }
+}
+
+#[cfg(feature = "fuel")]
 #[async_trait]
 impl<T> FuelProvider for Client<T>
 where
@@ -241,10 +1069,12 @@ where
 {
     async fn get_fuel_blocks_by_format(
         &self,
-        request: GetFuelBlocksRequest,
+        mut request: GetFuelBlocksRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
+        self.apply_default_chain(&mut request.chains);
+        request.validate()?;
         self.inner
             .get_fuel_blocks_by_format(request, format, deltas)
             .await
@@ -252,10 +1082,12 @@ where
 
     async fn get_fuel_logs_by_format(
         &self,
-        request: GetFuelLogsRequest,
+        mut request: GetFuelLogsRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
+        self.apply_default_chain(&mut request.chains);
+        request.validate()?;
         self.inner
             .get_fuel_logs_by_format(request, format, deltas)
             .await
@@ -263,10 +1095,12 @@ where
 
     async fn get_fuel_txs_by_format(
         &self,
-        request: GetFuelTxsRequest,
+        mut request: GetFuelTxsRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
+        self.apply_default_chain(&mut request.chains);
+        request.validate()?;
         self.inner
             .get_fuel_txs_by_format(request, format, deltas)
             .await
@@ -274,10 +1108,12 @@ where
 
     async fn get_fuel_receipts_by_format(
         &self,
-        request: GetFuelReceiptsRequest,
+        mut request: GetFuelReceiptsRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
+        self.apply_default_chain(&mut request.chains);
+        request.validate()?;
         self.inner
             .get_fuel_receipts_by_format(request, format, deltas)
             .await
@@ -285,10 +1121,12 @@ where
 
     async fn get_fuel_unspent_utxos_by_format(
         &self,
-        request: GetUtxoRequest,
+        mut request: GetUtxoRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
+        self.apply_default_chain(&mut request.chains);
+        request.validate()?;
         self.inner
             .get_fuel_unspent_utxos_by_format(request, format, deltas)
             .await
@@ -296,16 +1134,19 @@ where
 
     async fn get_fuel_spark_orders_by_format(
         &self,
-        request: GetSparkOrderRequest,
+        mut request: GetSparkOrderRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
+        self.apply_default_chain(&mut request.chains);
+        request.validate()?;
         self.inner
             .get_fuel_spark_orders_by_format(request, format, deltas)
             .await
     }
 }
 
+#[cfg(feature = "btc")]
 #[async_trait]
 impl<T> BtcProvider for Client<T>
 where
@@ -313,10 +1154,12 @@ where
 {
     async fn get_btc_blocks_by_format(
         &self,
-        request: GetBtcBlocksRequest,
+        mut request: GetBtcBlocksRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
+        self.apply_default_chain(&mut request.chains);
+        request.validate()?;
         self.inner
             .get_btc_blocks_by_format(request, format, deltas)
             .await
@@ -324,12 +1167,604 @@ where
 
     async fn get_btc_txs_by_format(
         &self,
-        request: GetBtcTxsRequest,
+        mut request: GetBtcTxsRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
+        self.apply_default_chain(&mut request.chains);
+        request.validate()?;
         self.inner
             .get_btc_txs_by_format(request, format, deltas)
             .await
     }
 }
+
+/// The number of missing ranges listed by [`Client::get_blocks_complete`]
+/// before the rest is only counted
+const MAX_LISTED_GAPS: usize = 10;
+
+/// Formats missing block ranges, e.g. `[ETH 5, ETH 7..=9, and 2 more]`
+fn summarize_gaps(gaps: &[(ChainId, u64, u64)]) -> String {
+    let mut listed: Vec<_> = gaps
+        .iter()
+        .take(MAX_LISTED_GAPS)
+        .map(|(chain, first, last)| {
+            if first == last {
+                format!("{} {first}", chain.as_ref())
+            } else {
+                format!("{} {first}..={last}", chain.as_ref())
+            }
+        })
+        .collect();
+    if gaps.len() > MAX_LISTED_GAPS {
+        listed.push(format!("and {} more", gaps.len() - MAX_LISTED_GAPS));
+    }
+    format!("[{}]", listed.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "uniswap")]
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    /// Records the query string of every prices request
+    #[cfg(feature = "uniswap")]
+    #[derive(Clone, Default)]
+    struct Recorder(Arc<Mutex<Vec<String>>>);
+
+    #[cfg(feature = "uniswap")]
+    impl Recorder {
+        fn record(&self, query: impl serde::Serialize) -> StreamResponse<Bytes> {
+            let query = serde_urlencoded::to_string(query)?;
+            self.0.lock().unwrap().push(query);
+            Ok(ResponseStream::new(futures::stream::empty()))
+        }
+    }
+
+    #[async_trait]
+    #[cfg(feature = "uniswap")]
+    impl UniswapV2Provider for Recorder {
+        async fn get_pairs_by_format(
+            &self,
+            request: GetPairsRequest,
+            _: Format,
+            _: bool,
+        ) -> StreamResponse<Bytes> {
+            self.record(request)
+        }
+
+        async fn get_prices_by_format(
+            &self,
+            request: requests::uniswap_v2::GetPricesRequest,
+            _: Format,
+            _: bool,
+        ) -> StreamResponse<Bytes> {
+            self.record(request)
+        }
+    }
+
+    #[async_trait]
+    #[cfg(feature = "uniswap")]
+    impl UniswapV3Provider for Recorder {
+        async fn get_pools_by_format(
+            &self,
+            request: GetPoolsRequest,
+            _: Format,
+            _: bool,
+        ) -> StreamResponse<Bytes> {
+            self.record(request)
+        }
+
+        async fn get_prices_by_format(
+            &self,
+            request: requests::uniswap_v3::GetPricesRequest,
+            _: Format,
+            _: bool,
+        ) -> StreamResponse<Bytes> {
+            self.record(request)
+        }
+    }
+
+    #[cfg(feature = "uniswap")]
+    fn client(default_chain: Option<ChainId>) -> (Client<Recorder>, Recorder) {
+        let recorder = Recorder::default();
+        let client = Client {
+            inner: recorder.clone(),
+            default_chain,
+        };
+        (client, recorder)
+    }
+
+    #[cfg(feature = "uniswap")]
+    #[test]
+    fn prices_requests_without_chains_use_default_chain() {
+        let (client, recorder) = client(Some(ChainId::FUEL));
+
+        futures::executor::block_on(async {
+            UniswapV2Provider::get_prices_by_format(
+                &client,
+                requests::uniswap_v2::GetPricesRequest {
+                    chains: HashSet::new(),
+                    ..Default::default()
+                },
+                Format::JsonStream,
+                false,
+            )
+            .await
+            .unwrap();
+            UniswapV3Provider::get_prices_by_format(
+                &client,
+                requests::uniswap_v3::GetPricesRequest {
+                    chains: HashSet::new(),
+                    ..Default::default()
+                },
+                Format::JsonStream,
+                false,
+            )
+            .await
+            .unwrap();
+        });
+
+        let queries = recorder.0.lock().unwrap();
+        assert_eq!(queries.len(), 2);
+        for query in queries.iter() {
+            assert!(query.starts_with("chains=FUEL&"), "{query}");
+        }
+    }
+
+    #[cfg(feature = "uniswap")]
+    #[test]
+    fn explicit_chains_are_kept() {
+        let (client, recorder) = client(Some(ChainId::FUEL));
+        let request = requests::uniswap_v2::GetPricesRequest {
+            chains: HashSet::from([ChainId::ARB]),
+            ..Default::default()
+        };
+
+        futures::executor::block_on(UniswapV2Provider::get_prices_by_format(
+            &client,
+            request,
+            Format::JsonStream,
+            false,
+        ))
+        .unwrap();
+
+        assert!(recorder.0.lock().unwrap()[0].starts_with("chains=ARB&"));
+    }
+
+    #[cfg(feature = "uniswap")]
+    #[test]
+    fn explicit_eth_is_kept() {
+        let (client, recorder) = client(Some(ChainId::FUEL));
+        let request = requests::uniswap_v2::GetPricesRequest {
+            chains: HashSet::from([ChainId::ETH]),
+            ..Default::default()
+        };
+
+        futures::executor::block_on(UniswapV2Provider::get_prices_by_format(
+            &client,
+            request,
+            Format::JsonStream,
+            false,
+        ))
+        .unwrap();
+
+        assert!(recorder.0.lock().unwrap()[0].starts_with("chains=ETH&"));
+    }
+
+    #[cfg(feature = "uniswap")]
+    #[test]
+    fn without_default_chain_requests_are_unchanged() {
+        let (client, recorder) = client(None);
+
+        futures::executor::block_on(UniswapV2Provider::get_pairs_by_format(
+            &client,
+            Default::default(),
+            Format::JsonStream,
+            false,
+        ))
+        .unwrap();
+
+        assert!(recorder.0.lock().unwrap()[0].starts_with("chains=ETH&"));
+    }
+
+    /// Serves a fixed response of JSON lines to every blocks request
+    struct Blocks(&'static str);
+
+    #[async_trait]
+    impl ChainProvider for Blocks {
+        async fn get_blocks_by_format(
+            &self,
+            _: GetBlocksRequest,
+            _: Format,
+            _: bool,
+        ) -> StreamResponse<Bytes> {
+            let body = Bytes::from_static(self.0.as_bytes());
+            Ok(ResponseStream::new(futures::stream::once(async {
+                Ok(body)
+            })))
+        }
+
+        async fn get_logs_by_format(
+            &self,
+            _: GetLogsRequest,
+            _: Format,
+            _: bool,
+        ) -> StreamResponse<Bytes> {
+            unimplemented!()
+        }
+
+        async fn get_txs_by_format(
+            &self,
+            _: GetTxsRequest,
+            _: Format,
+            _: bool,
+        ) -> StreamResponse<Bytes> {
+            unimplemented!()
+        }
+
+        async fn get_transfers_by_format(
+            &self,
+            _: GetTransfersRequest,
+            _: Format,
+            _: bool,
+        ) -> StreamResponse<Bytes> {
+            unimplemented!()
+        }
+    }
+
+    /// Answers the count requests of each dataset with a fixed count
+    struct Counts;
+
+    impl Counts {
+        fn respond(count_only: bool, format: Format, body: &'static str) -> StreamResponse<Bytes> {
+            assert!(count_only);
+            assert!(matches!(format, Format::Json));
+            Ok(ResponseStream::new(futures::stream::once(async move {
+                Ok(Bytes::from_static(body.as_bytes()))
+            })))
+        }
+    }
+
+    #[async_trait]
+    impl ChainProvider for Counts {
+        async fn get_blocks_by_format(
+            &self,
+            _: GetBlocksRequest,
+            _: Format,
+            _: bool,
+        ) -> StreamResponse<Bytes> {
+            unimplemented!()
+        }
+
+        async fn get_logs_by_format(
+            &self,
+            request: GetLogsRequest,
+            format: Format,
+            _: bool,
+        ) -> StreamResponse<Bytes> {
+            Self::respond(request.count_only, format, "42")
+        }
+
+        async fn get_txs_by_format(
+            &self,
+            request: GetTxsRequest,
+            format: Format,
+            _: bool,
+        ) -> StreamResponse<Bytes> {
+            Self::respond(request.count_only, format, "{\"count\":7}")
+        }
+
+        async fn get_transfers_by_format(
+            &self,
+            request: GetTransfersRequest,
+            format: Format,
+            _: bool,
+        ) -> StreamResponse<Bytes> {
+            Self::respond(request.count_only, format, "3\n")
+        }
+    }
+
+    #[test]
+    fn counts_are_decoded() {
+        let client = Client {
+            inner: Counts,
+            default_chain: None,
+        };
+
+        futures::executor::block_on(async {
+            assert_eq!(client.count_logs(Default::default()).await.unwrap(), 42);
+            assert_eq!(client.count_txs(Default::default()).await.unwrap(), 7);
+            assert_eq!(client.count_transfers(Default::default()).await.unwrap(), 3);
+        });
+    }
+
+    fn get_blocks_complete(body: &'static str, from: i64, to: i64) -> Result<Vec<Block>> {
+        let client = Client {
+            inner: Blocks(body),
+            default_chain: None,
+        };
+        let request = GetBlocksRequest {
+            chains: HashSet::from([ChainId::ETH]),
+            from_block: Bound::Exact(from),
+            to_block: Bound::Exact(to),
+            ..Default::default()
+        };
+        futures::executor::block_on(client.get_blocks_complete(request))
+    }
+
+    #[test]
+    fn complete_blocks_are_returned_typed() {
+        let blocks = get_blocks_complete(
+            "{\"chain\":1,\"number\":10}\n{\"chain\":1,\"number\":11}\n{\"chain\":1,\"number\":12}\n",
+            10,
+            13,
+        )
+        .unwrap();
+
+        let numbers: Vec<_> = blocks.iter().map(|block| block.number).collect();
+        assert_eq!(numbers, [10, 11, 12]);
+    }
+
+    #[test]
+    fn missing_block_in_the_middle_is_an_error() {
+        let result = get_blocks_complete(
+            "{\"chain\":1,\"number\":10}\n{\"chain\":1,\"number\":12}\n",
+            10,
+            13,
+        );
+
+        let Err(Error::Custom(message)) = result else {
+            panic!("{result:?}");
+        };
+        assert_eq!(message, "missing blocks: [ETH 11]");
+    }
+
+    #[test]
+    fn empty_response_is_an_error() {
+        assert!(matches!(
+            get_blocks_complete("", 10, 13),
+            Err(Error::Custom(_))
+        ));
+    }
+
+    #[test]
+    fn missing_ranges_are_summarized() {
+        let gaps: Vec<_> = (0..12)
+            .map(|i| (ChainId::ETH, i * 10, i * 10 + 5))
+            .collect();
+
+        let summary = summarize_gaps(&gaps);
+
+        assert!(
+            summary.starts_with("[ETH 0..=5, ETH 10..=15, "),
+            "{summary}"
+        );
+        assert!(summary.ends_with("ETH 90..=95, and 2 more]"), "{summary}");
+    }
+
+    #[cfg(feature = "json-value")]
+    #[test]
+    fn untyped_rows_keep_every_key() {
+        let client = Client {
+            inner: Blocks("{\"chain\":1,\"number\":10,\"new_field\":\"0xab\"}\n"),
+            default_chain: None,
+        };
+
+        let rows: Vec<serde_json::Value> = futures::executor::block_on(async {
+            let stream = client.get_blocks_json(Default::default()).await.unwrap();
+            stream.try_collect().await.unwrap()
+        });
+
+        let [row] = rows.as_slice() else {
+            panic!("{rows:?}");
+        };
+        let keys: Vec<_> = row.as_object().unwrap().keys().collect();
+        assert_eq!(keys, ["chain", "new_field", "number"]);
+    }
+
+    /// Serves two prices at consecutive blocks for every requested Uniswap V3
+    /// pool, the pool `repeat_byte(n)` trading at `n` and then `2 * n`
+    #[cfg(feature = "uniswap")]
+    struct Prices;
+
+    #[async_trait]
+    #[cfg(feature = "uniswap")]
+    impl UniswapV3Provider for Prices {
+        async fn get_pools_by_format(
+            &self,
+            _: GetPoolsRequest,
+            _: Format,
+            _: bool,
+        ) -> StreamResponse<Bytes> {
+            unimplemented!()
+        }
+
+        async fn get_prices_by_format(
+            &self,
+            request: requests::uniswap_v3::GetPricesRequest,
+            _: Format,
+            _: bool,
+        ) -> StreamResponse<Bytes> {
+            let mut rows = String::new();
+            for block_number in [1, 2] {
+                for pool in &request.pool_address__in {
+                    let price = uniswap_v3::Price {
+                        block_number,
+                        pool_address: *pool,
+                        price: f64::from(pool.0[0]) * block_number as f64,
+                        ..Default::default()
+                    };
+                    rows += &serde_json::to_string(&price).unwrap();
+                    rows.push('\n');
+                }
+            }
+            Ok(ResponseStream::new(futures::stream::once(async {
+                Ok(Bytes::from(rows))
+            })))
+        }
+    }
+
+    #[cfg(feature = "uniswap")]
+    #[test]
+    fn latest_prices_keep_the_last_price_per_pool() {
+        let client = Client {
+            inner: Prices,
+            default_chain: None,
+        };
+        let pools = [Address::repeat_byte(1), Address::repeat_byte(2)];
+
+        let latest = futures::executor::block_on(client.uniswap_v3_latest_prices(pools)).unwrap();
+
+        let prices: HashMap<_, _> = latest
+            .iter()
+            .map(|(pool, price)| (*pool, (price.block_number, price.price)))
+            .collect();
+        assert_eq!(
+            prices,
+            HashMap::from([(pools[0], (2, 2.0)), (pools[1], (2, 4.0))])
+        );
+    }
+
+    /// Serves no requests, for the methods that only wrap streams
+    struct Idle;
+
+    #[async_trait]
+    impl Provider for Idle {
+        async fn try_new(_: String, _: bool, _: Option<String>, _: Option<String>) -> Result<Self> {
+            Ok(Self)
+        }
+
+        async fn get_status_by_format(&self, _: Format) -> StreamResponse<Bytes> {
+            unimplemented!()
+        }
+    }
+
+    #[tokio::test]
+    async fn watched_receiver_holds_the_latest_item() {
+        let client = Client::new(Idle);
+        let (sender, items) = futures::channel::mpsc::unbounded();
+        let mut receiver = client.watch_latest(Ok(ResponseStream::new(items))).unwrap();
+        assert_eq!(*receiver.borrow(), None);
+
+        for item in 1..=3 {
+            sender.unbounded_send(Ok(item)).unwrap();
+        }
+        receiver.wait_for(|item| *item == Some(3)).await.unwrap();
+
+        // the last item stays readable after the stream ended
+        drop(sender);
+        assert!(receiver.changed().await.is_err());
+        assert_eq!(*receiver.borrow(), Some(3));
+    }
+
+    #[cfg(feature = "uniswap")]
+    #[tokio::test]
+    async fn prices_per_pool_open_one_stream_per_pool() {
+        let client = Client {
+            inner: Prices,
+            default_chain: None,
+        };
+        let pools = [Address::repeat_byte(1), Address::repeat_byte(2)];
+        let request = requests::uniswap_v3::GetPricesRequest {
+            pool_address__in: HashSet::from(pools),
+            ..Default::default()
+        };
+
+        let streams = client.uniswap_v3_prices_per_pool(request).await.unwrap();
+
+        assert_eq!(streams.len(), 2);
+        for (pool, stream) in streams {
+            let prices: Vec<_> = stream.try_collect().await.unwrap();
+            assert!(prices.iter().all(|price| price.pool_address == pool));
+            assert_eq!(prices.len(), 2);
+        }
+    }
+
+    #[test]
+    fn time_windows_cover_the_range_without_overlap() {
+        let windows: Vec<_> = time_windows(0, 250, 100).collect();
+        assert_eq!(windows, [(0, 100), (100, 200), (200, 250)]);
+
+        assert_eq!(time_windows(100, 100, 10).count(), 0);
+        assert_eq!(time_windows(200, 100, 10).count(), 0);
+
+        // the last window ends at the upper bound instead of overflowing
+        let windows: Vec<_> = time_windows(i64::MAX - 15, i64::MAX, 10).collect();
+        assert_eq!(
+            windows,
+            [(i64::MAX - 15, i64::MAX - 5), (i64::MAX - 5, i64::MAX)]
+        );
+    }
+
+    /// Serves a fixed response of JSON lines to every status request
+    struct Statuses(String);
+
+    #[async_trait]
+    impl Provider for Statuses {
+        async fn try_new(_: String, _: bool, _: Option<String>, _: Option<String>) -> Result<Self> {
+            unimplemented!()
+        }
+
+        async fn get_status_by_format(&self, _: Format) -> StreamResponse<Bytes> {
+            let body = Bytes::from(self.0.clone());
+            Ok(ResponseStream::new(futures::stream::once(async {
+                Ok(body)
+            })))
+        }
+    }
+
+    #[tokio::test]
+    async fn status_map_is_keyed_by_chain_and_service() {
+        let status = |chain, service: &str, entity: &str, height| Status {
+            chain,
+            service: service.into(),
+            entity: entity.into(),
+            latest_block_height: height,
+            ..Default::default()
+        };
+        let rows = [
+            status(ChainId::ETH, "blocks", "a", 100),
+            status(ChainId::ETH, "logs", "a", 99),
+            status(ChainId::ARB, "blocks", "a", 500),
+            // a later entity of the same service replaces the earlier one
+            status(ChainId::ETH, "blocks", "b", 101),
+        ];
+        let body = rows
+            .iter()
+            .map(|row| serde_json::to_string(row).unwrap() + "\n")
+            .collect();
+        let client = Client::new(Statuses(body));
+
+        let map = client.get_status_map().await.unwrap();
+
+        assert_eq!(map.len(), 3);
+        assert_eq!(map[&(ChainId::ETH, "blocks".to_owned())], rows[3]);
+        assert_eq!(map[&(ChainId::ETH, "logs".to_owned())], rows[1]);
+        assert_eq!(map[&(ChainId::ARB, "blocks".to_owned())], rows[2]);
+    }
+
+    #[cfg(feature = "uniswap")]
+    #[test]
+    fn discovery_targets_the_factory_with_a_full_range() {
+        let (client, recorder) = client(None);
+        let factory = Address::repeat_byte(1);
+
+        futures::executor::block_on(async {
+            client.discover_pairs(factory, ChainId::ETH).await.unwrap();
+            client.discover_pools(factory, ChainId::ARB).await.unwrap();
+        });
+
+        let queries = recorder.0.lock().unwrap();
+        let filter = "factory_address__in=0x0101010101010101010101010101010101010101";
+        assert_eq!(
+            *queries,
+            [
+                format!("chains=ETH&from_block=0&to_block=latest&{filter}"),
+                format!("chains=ARB&from_block=0&to_block=latest&{filter}"),
+            ]
+        );
+    }
+}
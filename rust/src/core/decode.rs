@@ -0,0 +1,463 @@
+use std::collections::VecDeque;
+
+use bytes::Bytes;
+use futures::{StreamExt, TryStreamExt};
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::{
+    error::{Error, ResponseError, Result},
+    provider::{BoxedResponseStream, ResponseStream, StreamResponse},
+};
+
+/// Decodes raw byte streams, as returned by the `*_by_format` methods and the
+/// `send_raw` escape hatches, into typed records
+///
+/// The body of the stream has to match the decoder, i.e. it has to be requested
+/// as [`Format::JsonStream`](crate::Format::JsonStream) for
+/// [`decode_json`](DecodeStream::decode_json), while
+/// [`decode_csv`](DecodeStream::decode_csv) is meant for endpoints responding
+/// with CSV. Error messages sent by the server are returned as
+/// [`Error::ErrorResponse`].
+pub trait DecodeStream {
+    /// Decodes a stream of JSON lines
+    fn decode_json<T>(self) -> StreamResponse<T>
+    where
+        T: DeserializeOwned + Send + 'static;
+
+    /// Decodes a CSV stream with a header row
+    fn decode_csv<T>(self) -> StreamResponse<T>
+    where
+        T: DeserializeOwned + Send + 'static;
+}
+
+impl DecodeStream for ResponseStream<Bytes> {
+    fn decode_json<T>(self) -> StreamResponse<T>
+    where
+        T: DeserializeOwned + Send + 'static,
+    {
+        Ok(decode_json_stream(self))
+    }
+
+    fn decode_csv<T>(self) -> StreamResponse<T>
+    where
+        T: DeserializeOwned + Send + 'static,
+    {
+        Ok(decode_csv_stream(self))
+    }
+}
+
+impl DecodeStream for StreamResponse<Bytes> {
+    fn decode_json<T>(self) -> StreamResponse<T>
+    where
+        T: DeserializeOwned + Send + 'static,
+    {
+        self?.decode_json()
+    }
+
+    fn decode_csv<T>(self) -> StreamResponse<T>
+    where
+        T: DeserializeOwned + Send + 'static,
+    {
+        self?.decode_csv()
+    }
+}
+
+/// Erases the item type of a stream by encoding its items as JSON lines
+///
+/// This allows storing streams of different item types in one collection.
+/// The original items are restored with
+/// [`decode_json`](DecodeStream::decode_json).
+pub fn erase_item_type<T>(stream: ResponseStream<T>) -> BoxedResponseStream
+where
+    T: Serialize + 'static,
+{
+    ResponseStream::new(stream.map(|item| {
+        let mut line = serde_json::to_vec(&item?)?;
+        line.push(b'\n');
+        Ok(Bytes::from(line))
+    }))
+}
+
+/// Decodes a JSON lines byte stream into records
+///
+/// Chunks are not required to be aligned to records, partial records are
+/// buffered until their terminating newline or the end of the stream.
+pub(crate) fn decode_json_stream<R>(stream: ResponseStream<Bytes>) -> ResponseStream<R>
+where
+    R: DeserializeOwned + Send + 'static,
+{
+    let records = split_json_lines(stream)
+        .enumerate()
+        .map(|(row_index, record)| decode_record(row_index as u64, record?));
+    ResponseStream::new(records)
+}
+
+/// Decodes a single JSON record, the row index is only used for errors
+///
+/// With the `simd-json` feature records are parsed with `simd-json`. Records
+/// it rejects are parsed again with `serde_json`, so both parsers yield the
+/// same records and errors.
+///
+/// With the `strict-schema` feature a field of the record that the row type
+/// does not know fails the record like a missing field would, to surface
+/// schema drift of the server early. It is meant for integration tests: in
+/// production new fields of the server are expected and should be ignored.
+/// Rows decoded into untyped values such as `serde_json::Value` accept any
+/// field.
+pub(crate) fn decode_record<R>(row_index: u64, record: Vec<u8>) -> Result<R>
+where
+    R: DeserializeOwned,
+{
+    #[cfg(all(feature = "simd-json", not(feature = "strict-schema")))]
+    if let Some(decoded) = decode_record_simd(&record) {
+        return Ok(decoded);
+    }
+
+    let mut deserializer = serde_json::Deserializer::from_slice(&record);
+    #[cfg(feature = "strict-schema")]
+    let mut unknown_field = None;
+    #[cfg(feature = "strict-schema")]
+    let mut on_ignored = |path: serde_ignored::Path<'_>| {
+        unknown_field.get_or_insert_with(|| path.to_string());
+    };
+    #[cfg(feature = "strict-schema")]
+    let decoded = serde_path_to_error::deserialize::<_, R>(serde_ignored::Deserializer::new(
+        &mut deserializer,
+        &mut on_ignored,
+    ));
+    #[cfg(not(feature = "strict-schema"))]
+    let decoded = serde_path_to_error::deserialize::<_, R>(&mut deserializer);
+
+    let decoded = decoded
+        .map_err(|err| {
+            let field = err.path().to_string();
+            (Some(field).filter(|field| field != "."), err.into_inner())
+        })
+        .and_then(|decoded| {
+            deserializer.end().map_err(|err| (None, err))?;
+            Ok(decoded)
+        });
+    #[cfg(feature = "strict-schema")]
+    let decoded = decoded.and_then(|decoded| match unknown_field {
+        Some(field) => Err((
+            Some(field.clone()),
+            serde::de::Error::custom(format_args!("unknown field `{field}`")),
+        )),
+        None => Ok(decoded),
+    });
+
+    decoded.map_err(|(field, source)| Error::Decode {
+        row_index,
+        field,
+        source,
+        raw: record,
+    })
+}
+
+#[cfg(all(feature = "simd-json", not(feature = "strict-schema")))]
+thread_local! {
+    static SIMD_SCRATCH: std::cell::RefCell<Vec<u8>> = const { std::cell::RefCell::new(Vec::new()) };
+}
+
+/// Parses a record with `simd-json`, `None` if it can not be decoded
+///
+/// `simd-json` parses in place, so the record is copied into a reused buffer
+/// to keep it intact for the error reporting of the `serde_json` path.
+#[cfg(all(feature = "simd-json", not(feature = "strict-schema")))]
+fn decode_record_simd<R>(record: &[u8]) -> Option<R>
+where
+    R: DeserializeOwned,
+{
+    SIMD_SCRATCH.with(|scratch| {
+        let mut scratch = scratch.borrow_mut();
+        scratch.clear();
+        scratch.extend_from_slice(record);
+        simd_json::serde::from_slice(&mut scratch).ok()
+    })
+}
+
+/// Splits a JSON lines byte stream into its records, see
+/// [`decode_json_stream`]
+pub(crate) fn split_json_lines(stream: ResponseStream<Bytes>) -> ResponseStream<Vec<u8>> {
+    struct State {
+        stream: ResponseStream<Bytes>,
+        buffer: Vec<u8>,
+        records: VecDeque<Vec<u8>>,
+        done: bool,
+    }
+
+    let state = State {
+        stream: ResponseStream::new(ResponseError::map_stream(stream)),
+        buffer: Vec::new(),
+        records: VecDeque::new(),
+        done: false,
+    };
+
+    let records = futures::stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(record) = state.records.pop_front() {
+                return Some((Ok(record), state));
+            }
+            if state.done {
+                return None;
+            }
+
+            match state.stream.next().await {
+                Some(Ok(chunk)) => {
+                    state.buffer.extend_from_slice(&chunk);
+                    if let Some(last_newline) = state.buffer.iter().rposition(|b| *b == b'\n') {
+                        let rest = state.buffer.split_off(last_newline + 1);
+                        let complete = std::mem::replace(&mut state.buffer, rest);
+                        state.records.extend(
+                            complete
+                                .split(|b| *b == b'\n')
+                                .filter(|record| !record.is_empty())
+                                .map(<[u8]>::to_vec),
+                        );
+                    }
+                }
+                Some(Err(e)) => return Some((Err(e), state)),
+                None => {
+                    state.done = true;
+                    let rest = std::mem::take(&mut state.buffer);
+                    if !rest.iter().all(u8::is_ascii_whitespace) {
+                        state.records.push_back(rest);
+                    }
+                }
+            }
+        }
+    });
+
+    ResponseStream::new(records)
+}
+
+/// A streaming zstd decompressor
+///
+/// Chunks may split a compressed block, so the decoder state is kept between
+/// chunks.
+#[cfg(all(feature = "zstd", any(feature = "http", feature = "ws")))]
+pub(crate) struct ZstdStreamDecoder {
+    inner: zstd::stream::raw::Decoder<'static>,
+    /// The output buffer, reused for every chunk
+    chunk: Vec<u8>,
+}
+
+#[cfg(all(feature = "zstd", any(feature = "http", feature = "ws")))]
+impl ZstdStreamDecoder {
+    pub(crate) fn new() -> Result<Self> {
+        Ok(Self {
+            inner: zstd::stream::raw::Decoder::new()?,
+            chunk: vec![0; zstd::zstd_safe::DCtx::out_size()],
+        })
+    }
+
+    /// Decompresses a chunk and returns all bytes completed by it
+    pub(crate) fn decompress(&mut self, data: &[u8]) -> Result<Vec<u8>> {
+        use zstd::stream::raw::{InBuffer, Operation, OutBuffer};
+
+        let mut input = InBuffer::around(data);
+        let mut decompressed = Vec::new();
+
+        loop {
+            let mut output = OutBuffer::around(&mut self.chunk[..]);
+            self.inner.run(&mut input, &mut output)?;
+            let written = output.pos();
+            decompressed.extend_from_slice(&self.chunk[..written]);
+
+            if input.pos() == data.len() && written < self.chunk.len() {
+                break;
+            }
+        }
+
+        Ok(decompressed)
+    }
+}
+
+/// Decompresses a zstd compressed byte stream
+#[cfg(all(feature = "zstd", feature = "http"))]
+pub(crate) fn decompress_zstd_stream(stream: ResponseStream<Bytes>) -> ResponseStream<Bytes> {
+    let chunks = stream.scan(None, |decoder: &mut Option<ZstdStreamDecoder>, chunk| {
+        let item = chunk.and_then(|chunk| {
+            let decoder = match decoder {
+                Some(decoder) => decoder,
+                None => decoder.insert(ZstdStreamDecoder::new()?),
+            };
+            decoder.decompress(&chunk).map(Bytes::from)
+        });
+        futures::future::ready(Some(item))
+    });
+    ResponseStream::new(chunks)
+}
+
+/// Decodes a CSV byte stream with a header row into records
+fn decode_csv_stream<R>(stream: ResponseStream<Bytes>) -> ResponseStream<R>
+where
+    R: DeserializeOwned + Send + 'static,
+{
+    let reader = ResponseError::map_stream(stream)
+        .map_err(std::io::Error::other)
+        .into_async_read();
+
+    let records = csv_async::AsyncDeserializer::from_reader(reader)
+        .into_deserialize::<R>()
+        .map_err(Error::from);
+
+    ResponseStream::new(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+
+    use super::*;
+
+    fn stream_of(body: &'static str) -> ResponseStream<Bytes> {
+        ResponseStream::new(futures::stream::iter([Ok(Bytes::from_static(
+            body.as_bytes(),
+        ))]))
+    }
+
+    #[test]
+    fn malformed_row_carries_its_bytes() {
+        let rows: Vec<Result<serde_json::Value>> =
+            block_on(decode_json_stream(stream_of("{\"number\":1}\n{\"number\":0x2}\n")).collect());
+
+        let [Ok(_), Err(Error::Decode { row_index, raw, .. })] = rows.as_slice() else {
+            panic!("{rows:?}");
+        };
+        assert_eq!(*row_index, 1);
+        assert_eq!(raw, b"{\"number\":0x2}");
+    }
+
+    #[cfg(feature = "uniswap")]
+    #[test]
+    fn decode_error_names_the_failing_field() {
+        use crate::core::types::uniswap_v3::Price;
+
+        let rows: Vec<Result<Price>> = block_on(
+            decode_json_stream(stream_of("{\"block_number\":1}\n{\"price\":\"1.5\"}\n")).collect(),
+        );
+
+        let [Ok(_), Err(
+            error @ Error::Decode {
+                row_index, field, ..
+            },
+        )] = rows.as_slice()
+        else {
+            panic!("{rows:?}");
+        };
+        assert_eq!((*row_index, field.as_deref()), (1, Some("price")));
+        assert!(error.to_string().contains("at `price`"), "{error}");
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Row {
+        number: u64,
+        hash: String,
+    }
+
+    fn rows() -> Vec<Row> {
+        vec![
+            Row {
+                number: 1,
+                hash: "0xaa".into(),
+            },
+            Row {
+                number: 2,
+                hash: "0xbb".into(),
+            },
+        ]
+    }
+
+    #[test]
+    fn unknown_fields_only_fail_strict_schemas() {
+        let body = "{\"number\":1,\"hash\":\"0xaa\",\"extra\":true}\n";
+
+        let decoded: Vec<Result<Row>> = block_on(decode_json_stream(stream_of(body)).collect());
+        #[cfg(feature = "strict-schema")]
+        assert!(
+            matches!(
+                decoded.as_slice(),
+                [Err(Error::Decode { field: Some(field), .. })] if field == "extra"
+            ),
+            "{decoded:?}"
+        );
+        #[cfg(not(feature = "strict-schema"))]
+        assert_eq!(
+            decoded.into_iter().collect::<Result<Vec<_>>>().unwrap(),
+            rows()[..1]
+        );
+
+        // untyped rows accept any field
+        let values: Vec<serde_json::Value> =
+            block_on(decode_json_stream(stream_of(body)).try_collect()).unwrap();
+        assert_eq!(values[0]["extra"], true);
+    }
+
+    #[test]
+    fn decodes_a_raw_json_stream() {
+        // the second row is split across chunks
+        let chunks = [
+            "{\"number\":1,\"hash\":\"0xaa\"}\n{\"num",
+            "ber\":2,\"hash\":\"0xbb\"}\n",
+        ];
+        let stream = ResponseStream::new(futures::stream::iter(
+            chunks.map(|chunk| Ok(Bytes::from_static(chunk.as_bytes()))),
+        ));
+
+        let decoded: Vec<Row> = block_on(stream.decode_json().unwrap().try_collect()).unwrap();
+
+        assert_eq!(decoded, rows());
+    }
+
+    #[test]
+    fn decodes_a_raw_csv_stream() {
+        let stream: StreamResponse<Bytes> = Ok(stream_of("number,hash\n1,0xaa\n2,0xbb\n"));
+
+        let decoded: Vec<Row> = block_on(stream.decode_csv().unwrap().try_collect()).unwrap();
+
+        assert_eq!(decoded, rows());
+    }
+
+    #[cfg(all(feature = "simd-json", not(feature = "strict-schema")))]
+    #[test]
+    fn simd_json_decodes_like_serde_json() {
+        use crate::core::types::ethereum::Block;
+
+        let corpus = [
+            r#"{"chain":1,"number":12000000,"hash":"0x0101010101010101010101010101010101010101010101010101010101010101","timestamp":1615234816,"gas_used":"0xe4e1c0","transaction_count":233}"#,
+            r#"{"chain":1,"number":19500000,"base_fee_per_gas":"0x3b9aca00","withdrawals":[{"index":1,"validator_index":7,"address":"0x0202020202020202020202020202020202020202","amount":"0x10"}],"blob_gas_used":"0x20000"}"#,
+            r#"{"number":1,"size":512,"unknown":{"nested":[1,2.5,"x",null]}}"#,
+            r#"{"number":"1"}"#,
+        ];
+
+        for row in corpus {
+            let simd = decode_record_simd::<Block>(row.as_bytes());
+            let serde = serde_json::from_str::<Block>(row).ok();
+            assert_eq!(simd, serde, "{row}");
+            let decoded = decode_record::<Block>(0, row.as_bytes().to_vec());
+            assert_eq!(decoded.ok(), serde, "{row}");
+        }
+    }
+
+    #[cfg(all(feature = "zstd", feature = "http"))]
+    #[test]
+    fn decompresses_zstd_split_across_chunks() {
+        let rows = b"{\"number\":1}\n{\"number\":2}\n{\"number\":3}\n";
+        let compressed = zstd::encode_all(&rows[..], 0).unwrap();
+        let chunks: Vec<_> = compressed
+            .chunks(3)
+            .map(|chunk| Ok(Bytes::copy_from_slice(chunk)))
+            .collect();
+
+        let stream = decompress_zstd_stream(ResponseStream::new(futures::stream::iter(chunks)));
+        let records: Vec<serde_json::Value> =
+            block_on(decode_json_stream(stream).try_collect()).unwrap();
+
+        let numbers: Vec<_> = records
+            .iter()
+            .map(|record| record["number"].clone())
+            .collect();
+        assert_eq!(numbers, [1, 2, 3]);
+    }
+}
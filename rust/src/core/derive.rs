@@ -0,0 +1,83 @@
+//! Rows derived on the client from the streams of the server
+use futures::{Stream, StreamExt};
+
+use super::{
+    error::Result,
+    provider::ResponseStream,
+    types::uniswap_v2::{Price, Trade, TradeDecoder},
+};
+
+/// Reconstructs the trades of Uniswap V2 pairs from a stream of their prices
+///
+/// Only `Swap` rows produce trades, `Sync`, `Mint` and `Burn` rows are
+/// skipped. The rules follow [`TradeDecoder`]:
+/// - the volumes are the absolute values of `amount0` and `amount1`
+/// - the amounts are signed from the perspective of the pair, a positive
+///   `amount0` sold `token0` into the pair,
+///   [`Side::Sell`](super::types::uniswap_v2::Side), a negative one bought it
+///   out of the pair, [`Side::Buy`](super::types::uniswap_v2::Side)
+/// - swaps without `amount0` take the opposite side of the sign of
+///   `amount1`, swaps without any amount are skipped
+///
+/// Errors of the prices stream are passed through.
+pub fn trades_from_prices<S>(stream: S) -> ResponseStream<Trade>
+where
+    S: Stream<Item = Result<Price>> + Send + 'static,
+{
+    let trades = stream
+        .scan(TradeDecoder::default(), |decoder, price| {
+            let trade = price.map(|price| decoder.push(&price)).transpose();
+            futures::future::ready(Some(trade))
+        })
+        .filter_map(futures::future::ready);
+
+    ResponseStream::new(trades)
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{executor::block_on, stream, TryStreamExt};
+
+    use super::*;
+    use crate::core::types::uniswap_v2::{ReserveEvent, Side};
+
+    fn price(event: ReserveEvent, amount0: f64, amount1: f64) -> Result<Price> {
+        Ok(Price {
+            event,
+            amount0,
+            amount1,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn reconstructs_buys_and_sells() {
+        let prices = stream::iter([
+            price(ReserveEvent::Sync, 0.0, 0.0),
+            price(ReserveEvent::Swap, -1.0, 2.0),
+            price(ReserveEvent::Sync, 0.0, 0.0),
+            price(ReserveEvent::Swap, 3.0, -6.0),
+        ]);
+
+        let trades: Vec<_> = block_on(trades_from_prices(prices).try_collect()).unwrap();
+
+        assert_eq!(trades.len(), 2);
+        assert_eq!(trades[0].side, Side::Buy);
+        assert_eq!((trades[0].volume0, trades[0].volume1), (1.0, 2.0));
+        assert_eq!(trades[1].side, Side::Sell);
+        assert_eq!((trades[1].volume0, trades[1].volume1), (3.0, 6.0));
+    }
+
+    #[test]
+    fn skips_sync_mint_and_burn() {
+        let prices = stream::iter([
+            price(ReserveEvent::Sync, 1.0, 1.0),
+            price(ReserveEvent::Mint, 1.0, 1.0),
+            price(ReserveEvent::Burn, -1.0, -1.0),
+        ]);
+
+        let trades: Vec<_> = block_on(trades_from_prices(prices).try_collect()).unwrap();
+
+        assert!(trades.is_empty());
+    }
+}
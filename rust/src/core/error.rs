@@ -1,5 +1,7 @@
 use std::borrow::Cow;
 
+use bytes::Bytes;
+
 /// A Result alias, that uses [`Error`] as the default error
 pub type Result<T, E = Error> = std::result::Result<T, E>;
 
@@ -42,6 +44,43 @@ pub enum Error {
     /// The websocket connection was closed by the server
     #[error("The websocket connection was closed")]
     ConnectionClosed,
+    /// The server closed the websocket connection for a policy violation,
+    /// e.g. failed authentication, so it is not reconnected
+    #[error("The server rejected the connection: {0}")]
+    ConnectionRejected(String),
+    /// The stream ended before the server completed it
+    ///
+    /// This is the last item of a stream that was cut short. A stream that
+    /// ends without a preceding error was completed by the server.
+    #[error("The stream was closed: {0}")]
+    StreamClosed(CloseReason),
+    /// No item of a stream arrived within its stall timeout, see
+    /// [`ResponseStream::with_stall_timeout`](crate::provider::ResponseStream::with_stall_timeout)
+    #[error("No item arrived within {0:?}")]
+    StreamStalled(std::time::Duration),
+    /// One or more messages of a subscription were lost
+    ///
+    /// The server numbers the messages of every subscription consecutively
+    /// within an epoch. Receiving a non consecutive counter means data was
+    /// dropped between the previous and the current message.
+    #[error("Message gap detected: expected counter {expected}, received {received}")]
+    MessageGap { expected: u32, received: u32 },
+
+    /// A row of a response could not be decoded
+    ///
+    /// Carries the index of the row within its stream, the path of the field
+    /// that failed, e.g. `price` or `logs[2].topics`, and the raw bytes of the
+    /// row to help debugging schema mismatches.
+    #[error(
+        "Failed to decode row {row_index}{}: {source}",
+        field.as_ref().map(|field| format!(" at `{field}`")).unwrap_or_default()
+    )]
+    Decode {
+        row_index: u64,
+        field: Option<String>,
+        source: serde_json::Error,
+        raw: Vec<u8>,
+    },
 
     /// An error encountered during csv parsing
     #[error(transparent)]
@@ -50,17 +89,37 @@ pub enum Error {
     #[error(transparent)]
     IO(#[from] std::io::Error),
     /// An error encountered during making HTTP requests
+    #[cfg(feature = "http")]
     #[error(transparent)]
     Reqwest(#[from] reqwest::Error),
     /// An error encountered during cbor parsing
     #[error(transparent)]
     SerdeJson(#[from] serde_json::Error),
     /// An error encountered during websocket handling
+    ///
+    /// Boxed, as it is by far the largest variant and would bloat every
+    /// `Result` of this crate.
+    #[cfg(all(feature = "ws", not(target_arch = "wasm32")))]
     #[error(transparent)]
-    Tungstenite(#[from] tungstenite::Error),
+    Tungstenite(#[from] Box<tungstenite::Error>),
     /// An error encountered during url parsing
     #[error(transparent)]
     Url(#[from] url::ParseError),
+    /// An error encountered during query string encoding
+    #[error(transparent)]
+    UrlEncoded(#[from] serde_urlencoded::ser::Error),
+
+    /// The request is invalid and was not sent
+    #[error("invalid request: {0}")]
+    InvalidRequest(&'static str),
+
+    /// A string could not be parsed as a chain
+    #[error("unknown chain: {0}")]
+    UnknownChain(String),
+
+    /// A required environment variable is not set
+    #[error("missing environment variable {0}")]
+    MissingEnvVar(&'static str),
 
     #[error("an unexpected error occurred: {0}")]
     Custom(Cow<'static, str>),
@@ -73,10 +132,32 @@ pub enum Error {
 
     #[error(transparent)]
     RequestID(#[from] uuid::Error),
+
+    #[error("{0:?} has no known factory on the requested chains")]
+    UnknownFactory(crate::core::types::dex::Dex),
+
+    #[error("{0:?} does not match the protocol of the request")]
+    DexProtocolMismatch(crate::core::types::dex::Dex),
+}
+
+/// Why a stream ended before the server completed it, see
+/// [`Error::StreamClosed`]
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum CloseReason {
+    /// The server closed the connection and it could not be re-established
+    #[error("the server closed the connection ({code}): {reason}")]
+    ServerClosed { code: u16, reason: String },
+    /// The connection was lost and could not be re-established
+    #[error("the connection was lost and reconnecting failed")]
+    ReconnectFailed,
+    /// The subscription was stopped by the client, e.g. with
+    /// [`SubscribeCommand::Unsubscribe`](crate::SubscribeCommand::Unsubscribe)
+    #[error("the subscription was unsubscribed")]
+    Unsubscribed,
 }
 
 /// An error that is returned by the server if something goes wrong
-#[derive(Clone, Debug, thiserror::Error, serde::Deserialize)]
+#[derive(Clone, Debug, thiserror::Error, serde::Deserialize, serde::Serialize)]
 #[error("Request failed with ({status}): {error}")]
 pub struct ResponseError {
     /// The HTTP status code of the error
@@ -85,14 +166,41 @@ pub struct ResponseError {
     pub error: String,
 }
 
-type ResponseFuture = futures::future::Ready<Result<Vec<u8>>>;
-type MapResponse = fn(Vec<u8>) -> ResponseFuture;
+#[cfg(all(feature = "ws", not(target_arch = "wasm32")))]
+impl From<tungstenite::Error> for Error {
+    fn from(err: tungstenite::Error) -> Self {
+        Self::Tungstenite(Box::new(err))
+    }
+}
+
+impl Error {
+    /// Returns true if the error only affects a single row of a response,
+    /// i.e. the row could not be decoded, but the stream can continue
+    ///
+    /// All other errors, e.g. error responses of the server or connection
+    /// failures, end the stream.
+    pub fn is_row_error(&self) -> bool {
+        match self {
+            Self::Decode { .. } => true,
+            Self::CsvAsync(err) => matches!(
+                err.kind(),
+                csv_async::ErrorKind::Deserialize { .. }
+                    | csv_async::ErrorKind::Utf8 { .. }
+                    | csv_async::ErrorKind::UnequalLengths { .. }
+            ),
+            _ => false,
+        }
+    }
+}
+
+type ResponseFuture = futures::future::Ready<Result<Bytes>>;
+type MapResponse = fn(Bytes) -> ResponseFuture;
 type MapStream =
-    futures::stream::AndThen<crate::provider::ResponseStream<Vec<u8>>, ResponseFuture, MapResponse>;
+    futures::stream::AndThen<crate::provider::ResponseStream<Bytes>, ResponseFuture, MapResponse>;
 
 impl ResponseError {
     /// Try to deserialize all error messages from the stream
-    pub fn map_stream(stream: crate::provider::ResponseStream<Vec<u8>>) -> MapStream {
+    pub fn map_stream(stream: crate::provider::ResponseStream<Bytes>) -> MapStream {
         use futures::TryStreamExt;
 
         stream.and_then(|bytes| {
@@ -0,0 +1,172 @@
+use std::time::Duration;
+
+use futures::StreamExt;
+
+use super::{error::Error, provider::ResponseStream};
+
+/// An item of a stream with heartbeats, see
+/// [`Client::stream_with_heartbeat`](crate::Client::stream_with_heartbeat)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StreamItem<T> {
+    /// An item of the underlying stream
+    Item(T),
+    /// No item arrived within the heartbeat interval, but the connection is
+    /// alive
+    Heartbeat,
+}
+
+impl<T> StreamItem<T> {
+    /// Returns the item, `None` for heartbeats
+    pub fn into_item(self) -> Option<T> {
+        match self {
+            Self::Item(item) => Some(item),
+            Self::Heartbeat => None,
+        }
+    }
+}
+
+/// Yields a [`StreamItem::Heartbeat`] whenever `stream` yields nothing for
+/// `interval` and `is_alive` returns true
+///
+/// If `is_alive` returns false, the stream ends with
+/// [`Error::BackendShutDown`].
+pub fn with_heartbeat<T, F>(
+    stream: ResponseStream<T>,
+    interval: Duration,
+    is_alive: F,
+) -> ResponseStream<StreamItem<T>>
+where
+    T: Send + 'static,
+    F: Fn() -> bool + Send + 'static,
+{
+    let items = futures::stream::unfold(Some((stream, is_alive)), move |state| async move {
+        let (mut stream, is_alive) = state?;
+
+        tokio::select! {
+            item = stream.next() => {
+                let item = item?.map(StreamItem::Item);
+                Some((item, Some((stream, is_alive))))
+            }
+            _ = tokio::time::sleep(interval) => {
+                if is_alive() {
+                    Some((Ok(StreamItem::Heartbeat), Some((stream, is_alive))))
+                } else {
+                    Some((Err(Error::BackendShutDown), None))
+                }
+            }
+        }
+    });
+
+    ResponseStream::new(items)
+}
+
+/// An item of a stream wrapped with [`hold_last`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HeldItem<T> {
+    /// The item
+    pub value: T,
+    /// Whether this is a repetition of an earlier item, because no new item
+    /// arrived in time
+    pub stale: bool,
+}
+
+/// Re-emits the last item of `stream`, marked as [stale](HeldItem::stale),
+/// whenever `stream` yields nothing for `max_hold`
+///
+/// This keeps real time consumers, e.g. a UI showing the latest price, from
+/// presenting a value as live while the stream pauses, for example during a
+/// WebSocket reconnect. The stale item is repeated every `max_hold` until a
+/// new item arrives. Nothing is re-emitted before the first item, and errors
+/// are passed through without being held.
+///
+/// A pause is detected by time alone, so a quiet subscription whose filter
+/// rarely matches is reported as stale as well; pick `max_hold` above the
+/// expected interval between items.
+pub fn hold_last<T>(stream: ResponseStream<T>, max_hold: Duration) -> ResponseStream<HeldItem<T>>
+where
+    T: Clone + Send + 'static,
+{
+    let items = futures::stream::unfold((stream, None), move |(mut stream, last)| async move {
+        tokio::select! {
+            item = stream.next() => {
+                let item = item?;
+                let last = match &item {
+                    Ok(value) => Some(value.clone()),
+                    Err(_) => last,
+                };
+                let item = item.map(|value| HeldItem { value, stale: false });
+                Some((Some(item), (stream, last)))
+            }
+            _ = tokio::time::sleep(max_hold) => {
+                let item = last.clone().map(|value| Ok(HeldItem { value, stale: true }));
+                Some((item, (stream, last)))
+            }
+        }
+    })
+    .filter_map(futures::future::ready);
+
+    ResponseStream::new(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    };
+
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn quiet_stream_yields_heartbeats_while_alive() {
+        let quiet = futures::stream::once(async { Ok(1) }).chain(futures::stream::pending());
+        let alive = Arc::new(AtomicBool::new(true));
+        let mut stream = with_heartbeat(ResponseStream::new(quiet), Duration::from_secs(5), {
+            let alive = alive.clone();
+            move || alive.load(Ordering::SeqCst)
+        });
+
+        assert_eq!(stream.next().await.unwrap().unwrap(), StreamItem::Item(1));
+        let start = tokio::time::Instant::now();
+        for _ in 0..2 {
+            assert_eq!(stream.next().await.unwrap().unwrap(), StreamItem::Heartbeat);
+        }
+        assert_eq!(start.elapsed(), Duration::from_secs(10));
+
+        alive.store(false, Ordering::SeqCst);
+        assert!(matches!(
+            stream.next().await,
+            Some(Err(Error::BackendShutDown))
+        ));
+        assert!(stream.next().await.is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn gap_re_emits_the_last_item_as_stale() {
+        let (sender, items) = futures::channel::mpsc::unbounded();
+        let mut stream = hold_last(ResponseStream::new(items), Duration::from_secs(5));
+        let fresh = |value| HeldItem {
+            value,
+            stale: false,
+        };
+
+        sender.unbounded_send(Ok(1)).unwrap();
+        assert_eq!(stream.next().await.unwrap().unwrap(), fresh(1));
+
+        // a gap, e.g. during a reconnect
+        let start = tokio::time::Instant::now();
+        assert_eq!(
+            stream.next().await.unwrap().unwrap(),
+            HeldItem {
+                value: 1,
+                stale: true
+            }
+        );
+        assert_eq!(start.elapsed(), Duration::from_secs(5));
+
+        sender.unbounded_send(Ok(2)).unwrap();
+        assert_eq!(stream.next().await.unwrap().unwrap(), fresh(2));
+        drop(sender);
+        assert!(stream.next().await.is_none());
+    }
+}
@@ -0,0 +1,171 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use futures::StreamExt;
+use serde_json::Value;
+
+use super::provider::ResponseStream;
+
+/// Number of heights below the watermark whose logs are kept, so that blocks
+/// replaced by a reorg can still be joined with their logs
+const REORG_DEPTH: u64 = 64;
+
+enum Record {
+    Block(Value),
+    Log(Value),
+}
+
+struct State {
+    records: ResponseStream<Record>,
+    /// Blocks waiting for their logs to complete, by height
+    blocks: BTreeMap<u64, Value>,
+    /// Logs of the recent heights
+    logs: BTreeMap<u64, Vec<Value>>,
+    /// Height of the latest block
+    block_tip: u64,
+    /// Height of the latest log
+    log_tip: u64,
+    ready: VecDeque<(Value, Vec<Value>)>,
+    done: bool,
+}
+
+impl State {
+    fn push(&mut self, record: Record) {
+        match record {
+            Record::Block(block) => {
+                let height = height(&block, "number");
+                // A block at or below the tip means the chain was reorganized,
+                // so blocks above it are not complete anymore
+                if height <= self.block_tip {
+                    self.log_tip = self.log_tip.min(height);
+                }
+                self.block_tip = height;
+                self.blocks.insert(height, block);
+            }
+            Record::Log(log) => {
+                let height = height(&log, "block_number");
+                self.log_tip = self.log_tip.max(height);
+                self.logs.entry(height).or_default().push(log);
+            }
+        }
+    }
+
+    /// Returns the height below which all blocks are considered complete
+    ///
+    /// That is the case once a log of a greater height arrived, or two blocks
+    /// later, giving the logs stream one block of grace to catch up.
+    fn watermark(&self) -> u64 {
+        self.log_tip.max(self.block_tip.saturating_sub(1))
+    }
+
+    /// Moves all blocks below `watermark` to the ready queue
+    fn flush(&mut self, watermark: u64) {
+        let pending = self.blocks.split_off(&watermark);
+        let complete = std::mem::replace(&mut self.blocks, pending);
+
+        for (height, block) in complete {
+            let hash = block.get("hash");
+            let logs = self
+                .logs
+                .get(&height)
+                .into_iter()
+                .flatten()
+                .filter(|log| match (hash, log.get("block_hash")) {
+                    (Some(hash), Some(block_hash)) => hash == block_hash,
+                    _ => true,
+                })
+                .cloned()
+                .collect();
+            self.ready.push_back((block, logs));
+        }
+
+        self.logs = self.logs.split_off(&watermark.saturating_sub(REORG_DEPTH));
+    }
+}
+
+/// Joins a stream of blocks with a stream of logs into `(block, logs)` pairs
+///
+/// See [`Client::subscribe_blocks_with_logs`](crate::Client::subscribe_blocks_with_logs)
+/// for the semantics.
+pub(crate) fn join_blocks_with_logs(
+    blocks: ResponseStream<Value>,
+    logs: ResponseStream<Value>,
+) -> ResponseStream<(Value, Vec<Value>)> {
+    let records = ResponseStream::new(futures::stream::select(
+        blocks.map(|block| block.map(Record::Block)),
+        logs.map(|log| log.map(Record::Log)),
+    ));
+
+    let state = State {
+        records,
+        blocks: BTreeMap::new(),
+        logs: BTreeMap::new(),
+        block_tip: 0,
+        log_tip: 0,
+        ready: VecDeque::new(),
+        done: false,
+    };
+
+    let joined = futures::stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(joined) = state.ready.pop_front() {
+                return Some((Ok(joined), state));
+            }
+            if state.done {
+                return None;
+            }
+
+            match state.records.next().await {
+                Some(Ok(record)) => {
+                    state.push(record);
+                    state.flush(state.watermark());
+                }
+                Some(Err(e)) => return Some((Err(e), state)),
+                None => {
+                    state.done = true;
+                    state.flush(u64::MAX);
+                }
+            }
+        }
+    });
+
+    ResponseStream::new(joined)
+}
+
+fn height(record: &Value, field: &str) -> u64 {
+    record
+        .get(field)
+        .and_then(Value::as_u64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{executor::block_on, TryStreamExt};
+    use serde_json::json;
+
+    use super::*;
+
+    fn stream_of(records: Vec<Value>) -> ResponseStream<Value> {
+        ResponseStream::new(futures::stream::iter(records.into_iter().map(Ok)))
+    }
+
+    #[test]
+    fn groups_logs_by_their_block() {
+        let blocks = (10..13).map(|number| json!({ "number": number })).collect();
+        let logs = vec![
+            json!({ "block_number": 10, "log_index": 0 }),
+            json!({ "block_number": 10, "log_index": 1 }),
+            json!({ "block_number": 12, "log_index": 0 }),
+        ];
+
+        let joined: Vec<_> =
+            block_on(join_blocks_with_logs(stream_of(blocks), stream_of(logs)).try_collect())
+                .unwrap();
+
+        let grouped: Vec<_> = joined
+            .iter()
+            .map(|(block, logs)| (block["number"].as_u64().unwrap(), logs.len()))
+            .collect();
+        assert_eq!(grouped, [(10, 2), (11, 0), (12, 1)]);
+    }
+}
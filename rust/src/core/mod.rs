@@ -1,7 +1,24 @@
+pub mod agg;
+#[cfg(feature = "hmac")]
+pub mod auth;
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+pub mod blocking;
 pub mod builder;
 pub mod client;
+pub mod decode;
+#[cfg(feature = "uniswap")]
+pub mod derive;
 pub mod error;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod heartbeat;
+mod join;
+pub mod progress;
 pub mod provider;
+// tokio timers need a runtime with a time driver, which wasm32 lacks
+#[cfg(all(any(feature = "http", feature = "ws"), not(target_arch = "wasm32")))]
+pub(crate) mod rate_limit;
 pub mod requests;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod sink;
 pub mod types;
 pub mod utils;
@@ -0,0 +1,162 @@
+use futures::StreamExt;
+
+#[cfg(feature = "uniswap")]
+use super::types::{uniswap_v2, uniswap_v3};
+use super::{
+    provider::ResponseStream,
+    types::{erc721, ethereum},
+};
+
+/// The minimum increase of [`Progress::fraction`] between two reports
+const PROGRESS_STEP: f64 = 0.01;
+
+/// A row that belongs to a block
+pub trait HasBlockNumber {
+    /// Height of the block the row belongs to
+    fn block_number(&self) -> u64;
+}
+
+macro_rules! impl_has_block_number {
+    ($($row:ty),+ $(,)?) => {
+        $(
+            impl HasBlockNumber for $row {
+                fn block_number(&self) -> u64 {
+                    self.block_number
+                }
+            }
+        )+
+    };
+}
+
+impl HasBlockNumber for ethereum::Block {
+    fn block_number(&self) -> u64 {
+        self.number
+    }
+}
+
+impl_has_block_number!(
+    erc721::Erc721Transfer,
+    ethereum::Receipt,
+    ethereum::InternalTx,
+    ethereum::ContractCreation,
+    ethereum::Withdrawal,
+    ethereum::Balance,
+);
+#[cfg(feature = "uniswap")]
+impl_has_block_number!(
+    uniswap_v2::Pair,
+    uniswap_v2::Price,
+    uniswap_v2::Trade,
+    uniswap_v3::Pool,
+    uniswap_v3::Price
+);
+
+/// Reads the `block_number` field of a row, or the `number` field of a block
+impl HasBlockNumber for serde_json::Value {
+    fn block_number(&self) -> u64 {
+        self.get("block_number")
+            .or_else(|| self.get("number"))
+            .and_then(serde_json::Value::as_u64)
+            .unwrap_or_default()
+    }
+}
+
+/// The progress of a stream through a block range
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Progress {
+    /// Block of the latest row
+    pub current_block: u64,
+    /// Inclusive lower bound of the range
+    pub start_block: u64,
+    /// Exclusive upper bound of the range
+    pub end_block: u64,
+    /// Share of the range up to and including the current block, from 0 to 1
+    pub fraction: f64,
+}
+
+impl Progress {
+    fn new(current_block: u64, start_block: u64, end_block: u64) -> Self {
+        let total = end_block.saturating_sub(start_block);
+        let done = (current_block + 1).saturating_sub(start_block).min(total);
+        let fraction = if total == 0 {
+            1.0
+        } else {
+            done as f64 / total as f64
+        };
+        Self {
+            current_block,
+            start_block,
+            end_block,
+            fraction,
+        }
+    }
+}
+
+impl<T> ResponseStream<T>
+where
+    T: HasBlockNumber + Send + 'static,
+{
+    /// Reports the progress of the stream through the block range
+    /// `start_block..end_block` to `on_progress`
+    ///
+    /// The progress is derived from the block of each row, so it assumes the
+    /// rows arrive in ascending order, as they do by default. Reports are
+    /// throttled to every percent of progress and the first row at or past
+    /// the last block of the range, which reports a fraction of 1.
+    pub fn with_progress<F>(self, start_block: u64, end_block: u64, mut on_progress: F) -> Self
+    where
+        F: FnMut(Progress) + Send + 'static,
+    {
+        let mut reported: Option<f64> = None;
+        Self::new(self.into_inner().inspect(move |item| {
+            let Ok(row) = item else {
+                return;
+            };
+            let progress = Progress::new(row.block_number(), start_block, end_block);
+            let due = match reported {
+                Some(reported) => {
+                    progress.fraction >= reported + PROGRESS_STEP
+                        || (progress.fraction >= 1.0 && reported < 1.0)
+                }
+                None => true,
+            };
+            if due {
+                reported = Some(progress.fraction);
+                on_progress(progress);
+            }
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    #[test]
+    fn reports_increasing_fractions() {
+        let rows = (1000..2000).map(|block| Ok(serde_json::json!({ "block_number": block })));
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let recorded = reports.clone();
+
+        let stream = ResponseStream::new(futures::stream::iter(rows)).with_progress(
+            1000,
+            2000,
+            move |progress| {
+                recorded.lock().unwrap().push(progress.fraction);
+            },
+        );
+        assert_eq!(futures::executor::block_on(stream.count()), 1000);
+
+        let reports = reports.lock().unwrap();
+        assert!(
+            reports.windows(2).all(|pair| pair[0] < pair[1]),
+            "{reports:?}"
+        );
+        assert_eq!(reports.first(), Some(&0.001));
+        assert_eq!(reports.last(), Some(&1.0));
+        // throttled to every percent instead of every row
+        assert!((90..=101).contains(&reports.len()), "{}", reports.len());
+    }
+}
@@ -1,33 +1,442 @@
-use std::pin::Pin;
+#[cfg(not(target_arch = "wasm32"))]
+use std::num::NonZeroU32;
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
 
 use async_trait::async_trait;
-use futures::Stream;
+use bytes::Bytes;
+use futures::{future::BoxFuture, stream::BoxStream, Stream, StreamExt};
+use serde::de::DeserializeOwned;
+use tracing::warn;
 
 use super::{
-    error::Result,
+    decode::decode_json_stream,
+    error::{Error, Result},
     requests::{
-        self,
-        blocks::GetBlocksRequest,
-        btc::{GetBtcBlocksRequest, GetBtcTxsRequest},
-        fuel::{GetFuelReceiptsRequest, GetUtxoRequest},
-        logs::GetLogsRequest,
-        txs::GetTxsRequest,
-        uniswap_v2::GetPairsRequest,
-        uniswap_v3::GetPoolsRequest,
+        balances::GetBalancesRequest, blocks::GetBlocksRequest,
+        contracts::GetContractCreationsRequest, erc721::GetErc721TransfersRequest,
+        internal_txs::GetInternalTxsRequest, logs::GetLogsRequest,
+        pending_txs::GetPendingTxsRequest, receipts::GetReceiptsRequest, txs::GetTxsRequest,
+        withdrawals::GetWithdrawalsRequest,
     },
 };
+#[cfg(feature = "btc")]
+use crate::requests::btc::{GetBtcBlocksRequest, GetBtcTxsRequest};
+#[cfg(feature = "curve")]
+use crate::requests::curve::{GetCrvPoolRequest, GetCrvPriceRequest, GetCrvTokenRequest};
+#[cfg(feature = "fuel")]
+use crate::requests::fuel::{
+    GetFuelBlocksRequest, GetFuelLogsRequest, GetFuelReceiptsRequest, GetFuelTxsRequest,
+    GetSparkOrderRequest, GetUtxoRequest,
+};
+#[cfg(feature = "uniswap")]
+use crate::requests::{self, uniswap_v2::GetPairsRequest, uniswap_v3::GetPoolsRequest};
 use crate::{
     requests::{
-        curve::{GetCrvPoolRequest, GetCrvPriceRequest, GetCrvTokenRequest},
         erc20::{GetErc20ApprovalsRequest, GetErc20Request, GetErc20TransferssRequest},
-        fuel::{GetFuelBlocksRequest, GetFuelLogsRequest, GetFuelTxsRequest, GetSparkOrderRequest},
         transfers::GetTransfersRequest,
     },
     Format,
 };
 
-pub type ResponseStream<T> = Pin<Box<dyn Stream<Item = Result<T>> + Send>>;
+/// A stream of response items
+///
+/// Every stream returned by this crate is `Send + Unpin + 'static`, so it can
+/// be moved into a spawned task and polled without pinning it first. Wrapping
+/// a `ResponseStream` in another one reuses its allocation instead of boxing
+/// it again.
+pub struct ResponseStream<T> {
+    inner: BoxStream<'static, Result<T>>,
+}
+
+impl<T> ResponseStream<T> {
+    /// Wraps a stream
+    pub fn new<S>(stream: S) -> Self
+    where
+        S: Stream<Item = Result<T>> + Send + 'static,
+    {
+        Self {
+            inner: stream.boxed(),
+        }
+    }
+
+    /// Ends the stream once `fut` completes
+    pub fn take_until<F>(self, fut: F) -> Self
+    where
+        T: 'static,
+        F: Future + Send + 'static,
+        F::Output: Send,
+    {
+        Self::new(self.inner.take_until(fut))
+    }
+
+    /// Ends the stream with [`Error::StreamStalled`] if no item arrives
+    /// within `timeout`, the timer restarts with every item
+    ///
+    /// Unlike the idle timeout of the WebSocket connection, this applies to
+    /// this stream alone, e.g. to a subscription that should always produce
+    /// data. It can not tell a subscription whose filter matches nothing
+    /// from a dead one, so the timeout should exceed the longest expected
+    /// pause between matching rows. The inner stream is dropped on timeout,
+    /// which ends the subscription.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_stall_timeout(self, timeout: Duration) -> Self
+    where
+        T: Send + 'static,
+    {
+        Self::new(futures::stream::unfold(
+            Some(self.inner),
+            move |inner| async move {
+                let mut inner = inner?;
+                match tokio::time::timeout(timeout, inner.next()).await {
+                    Ok(item) => Some((item?, Some(inner))),
+                    Err(_) => Some((Err(Error::StreamStalled(timeout)), None)),
+                }
+            },
+        ))
+    }
+
+    /// Skips rows that can not be decoded instead of yielding them as errors
+    ///
+    /// Skipped rows are logged as warnings. Only errors of single rows are
+    /// skipped, see [`Error::is_row_error`](crate::Error::is_row_error), all
+    /// other errors, e.g. error responses of the server, are still yielded.
+    pub fn resilient(self) -> Self
+    where
+        T: Send + 'static,
+    {
+        self.resilient_with(|_| {})
+    }
+
+    /// Like [`resilient`](Self::resilient), but also reports every skipped
+    /// row to `on_skip`, e.g. to count them
+    pub fn resilient_with<F>(self, mut on_skip: F) -> Self
+    where
+        T: Send + 'static,
+        F: FnMut(&Error) + Send + 'static,
+    {
+        Self::new(self.inner.filter_map(move |item| {
+            let item = match item {
+                Err(err) if err.is_row_error() => {
+                    warn!("Skipping row: {err}");
+                    on_skip(&err);
+                    None
+                }
+                item => Some(item),
+            };
+            futures::future::ready(item)
+        }))
+    }
+
+    /// Returns the type erased stream
+    pub fn into_inner(self) -> BoxStream<'static, Result<T>> {
+        self.inner
+    }
+
+    /// Returns the type erased stream, e.g. to store it next to streams
+    /// that are not `ResponseStream`s
+    pub fn boxed(self) -> BoxStream<'static, Result<T>> {
+        self.inner
+    }
+}
+
+impl ResponseStream<Bytes> {
+    /// Decodes a stream of JSON lines, see
+    /// [`DecodeStream`](crate::core::decode::DecodeStream)
+    pub fn decode<R>(self) -> ResponseStream<R>
+    where
+        R: DeserializeOwned + Send + 'static,
+    {
+        decode_json_stream(self)
+    }
+}
+
+impl<T> From<BoxStream<'static, Result<T>>> for ResponseStream<T> {
+    fn from(inner: BoxStream<'static, Result<T>>) -> Self {
+        Self { inner }
+    }
+}
+
+impl<T> Stream for ResponseStream<T> {
+    type Item = Result<T>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.poll_next_unpin(cx)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<T> fmt::Debug for ResponseStream<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResponseStream").finish_non_exhaustive()
+    }
+}
+
 pub type StreamResponse<T> = Result<ResponseStream<T>>;
+/// A stream of raw response bytes as returned by the `*_by_format` methods
+///
+/// Streams of different item types can be stored together after erasing
+/// their item type with [`erase_item_type`](crate::core::decode::erase_item_type)
+/// and decoded again with [`DecodeStream`](crate::core::decode::DecodeStream).
+pub type BoxedResponseStream = ResponseStream<Bytes>;
+
+/// The default `User-Agent` header, `superchain-client/<version>`
+pub const DEFAULT_USER_AGENT: &str =
+    concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+/// How the WebSocket provider assigns ids to its requests
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IdStrategy {
+    /// A random id for every request
+    #[default]
+    Random,
+    /// An id derived from the operation, its parameters, the format and
+    /// `deltas`, so the same query always gets the same id
+    ///
+    /// This allows the server to recognize a re-established subscription,
+    /// e.g. after the provider was rebuilt. The same query can only be
+    /// subscribed once at a time per provider though, subscribing it again
+    /// while the previous subscription is running fails with
+    /// [`Error::InvalidRequest`].
+    Deterministic,
+}
+
+/// Spawns the background tasks of a provider, e.g. the connection worker of
+/// the WebSocket provider
+///
+/// The spawner must poll every future to completion, dropping a future ends
+/// the task early, which for the WebSocket provider closes the connection.
+/// The tasks use Tokio's timers and sockets, so they must run within a Tokio
+/// runtime context, though not necessarily a multi threaded one. Defaults to
+/// [`tokio::spawn`].
+#[derive(Clone)]
+pub struct Spawner(Arc<dyn Fn(BoxFuture<'static, ()>) + Send + Sync>);
+
+impl Spawner {
+    /// Creates a spawner from a function that drives the given futures
+    pub fn new<F>(spawn: F) -> Self
+    where
+        F: Fn(BoxFuture<'static, ()>) + Send + Sync + 'static,
+    {
+        Self(Arc::new(spawn))
+    }
+
+    /// Spawns a future with this spawner
+    pub fn spawn<F>(&self, future: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        (self.0)(Box::pin(future))
+    }
+}
+
+impl Default for Spawner {
+    fn default() -> Self {
+        Self::new(|future| {
+            #[cfg(not(target_arch = "wasm32"))]
+            tokio::spawn(future);
+            #[cfg(target_arch = "wasm32")]
+            wasm_bindgen_futures::spawn_local(future);
+        })
+    }
+}
+
+impl fmt::Debug for Spawner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Spawner").finish_non_exhaustive()
+    }
+}
+
+/// The configuration a provider is created with
+///
+/// The password and the values of credential headers, e.g.
+/// `Authorization`, are redacted in the `Debug` output.
+#[derive(Clone)]
+pub struct ProviderConfig {
+    pub endpoint: String,
+    /// All endpoints of a [`FailoverProvider`](crate::FailoverProvider), in
+    /// the order they are failed over to, or of a
+    /// [`LoadBalancedProvider`](crate::LoadBalancedProvider). Ignored by the
+    /// other providers, which only connect to `endpoint`
+    pub endpoints: Vec<String>,
+    pub is_secure: bool,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// The `User-Agent` header sent with every HTTP request and the WebSocket
+    /// handshake
+    pub user_agent: String,
+    /// Additional headers sent with every HTTP request and the WebSocket
+    /// handshake, validated when the provider is created
+    pub headers: Vec<(String, String)>,
+    /// Hosts for which TLS certificates are not verified, only meant for
+    /// development against self-hosted gateways
+    pub insecure_hosts: Vec<String>,
+    /// Maximum size of a WebSocket message after reassembling its frames,
+    /// `None` for the tungstenite default of 64 MiB
+    pub ws_max_message_size: Option<usize>,
+    /// Maximum size of a single WebSocket frame, `None` for the tungstenite
+    /// default of 16 MiB
+    pub ws_max_frame_size: Option<usize>,
+    /// Time without any received frame after which the WebSocket connection
+    /// is considered dead and reconnected, `None` to wait indefinitely
+    pub ws_idle_timeout: Option<Duration>,
+    /// Time after which connecting to a single address of the WebSocket
+    /// endpoint is given up in favor of the next one, `None` to use the
+    /// system defaults
+    pub ws_connect_timeout: Option<Duration>,
+    /// Number of consecutive unanswered pings after which the WebSocket
+    /// connection is considered dead and reconnected
+    pub ws_max_missed_pongs: u32,
+    /// Time the WebSocket worker waits for further operations after one was
+    /// queued to send them together in one batched frame, `None` to send
+    /// every operation right away. See
+    /// [`ClientBuilder::ws_batch_window`](crate::ClientBuilder::ws_batch_window)
+    /// for the batch format.
+    pub ws_batch_window: Option<Duration>,
+    /// Sustained rate and burst size of outgoing requests, `None` for no
+    /// client-side rate limit
+    #[cfg(not(target_arch = "wasm32"))]
+    pub rate_limit: Option<(NonZeroU32, NonZeroU32)>,
+    /// How the WebSocket provider assigns request ids
+    pub id_strategy: IdStrategy,
+    /// Spawns the background tasks of the provider
+    pub spawner: Spawner,
+    /// Signs every HTTP request and WebSocket handshake, `None` to not sign
+    /// requests
+    #[cfg(feature = "hmac")]
+    pub hmac_signer: Option<crate::core::auth::HmacSigner>,
+    /// Capacity and time to live of the in-memory cache of bounded HTTP
+    /// responses, `None` to disable caching
+    #[cfg(all(feature = "cache", not(target_arch = "wasm32")))]
+    pub http_cache: Option<(std::num::NonZeroUsize, Duration)>,
+}
+
+impl ProviderConfig {
+    /// Creates a configuration with all optional settings left at their
+    /// defaults
+    pub fn new(
+        endpoint: String,
+        is_secure: bool,
+        username: Option<String>,
+        password: Option<String>,
+    ) -> Self {
+        Self {
+            endpoint,
+            endpoints: Vec::new(),
+            is_secure,
+            username,
+            password,
+            user_agent: DEFAULT_USER_AGENT.to_owned(),
+            headers: Vec::new(),
+            insecure_hosts: Vec::new(),
+            ws_max_message_size: None,
+            ws_max_frame_size: None,
+            ws_idle_timeout: None,
+            ws_connect_timeout: None,
+            ws_max_missed_pongs: 2,
+            ws_batch_window: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            rate_limit: None,
+            id_strategy: IdStrategy::Random,
+            spawner: Spawner::default(),
+            #[cfg(feature = "hmac")]
+            hmac_signer: None,
+            #[cfg(all(feature = "cache", not(target_arch = "wasm32")))]
+            http_cache: None,
+        }
+    }
+}
+
+/// Replaces secrets in `Debug` output
+const REDACTED: &str = "***";
+
+/// Returns whether the value of a header is a credential
+fn is_credential_header(name: &str) -> bool {
+    [
+        "authorization",
+        "proxy-authorization",
+        "cookie",
+        "x-api-key",
+    ]
+    .iter()
+    .any(|credential| name.eq_ignore_ascii_case(credential))
+}
+
+impl fmt::Debug for ProviderConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let headers: Vec<_> = self
+            .headers
+            .iter()
+            .map(|(name, value)| {
+                let value = if is_credential_header(name) {
+                    REDACTED
+                } else {
+                    value.as_str()
+                };
+                (name.as_str(), value)
+            })
+            .collect();
+
+        let mut debug = f.debug_struct("ProviderConfig");
+        debug
+            .field("endpoint", &self.endpoint)
+            .field("endpoints", &self.endpoints)
+            .field("is_secure", &self.is_secure)
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| REDACTED))
+            .field("user_agent", &self.user_agent)
+            .field("headers", &headers)
+            .field("insecure_hosts", &self.insecure_hosts)
+            .field("ws_max_message_size", &self.ws_max_message_size)
+            .field("ws_max_frame_size", &self.ws_max_frame_size)
+            .field("ws_idle_timeout", &self.ws_idle_timeout)
+            .field("ws_connect_timeout", &self.ws_connect_timeout)
+            .field("ws_max_missed_pongs", &self.ws_max_missed_pongs)
+            .field("ws_batch_window", &self.ws_batch_window)
+            .field("id_strategy", &self.id_strategy)
+            .field("spawner", &self.spawner);
+        #[cfg(not(target_arch = "wasm32"))]
+        debug.field("rate_limit", &self.rate_limit);
+        #[cfg(feature = "hmac")]
+        debug.field("hmac_signer", &self.hmac_signer);
+        #[cfg(all(feature = "cache", not(target_arch = "wasm32")))]
+        debug.field("http_cache", &self.http_cache);
+        debug.finish()
+    }
+}
+
+impl ProviderConfig {
+    /// Returns whether the host of the endpoint is one of the insecure hosts,
+    /// i.e. its TLS certificate must not be verified
+    ///
+    /// IPv6 hosts match with or without brackets, e.g. `::1` and `[::1]`.
+    pub fn skips_tls_verification(&self) -> bool {
+        // the endpoint has no scheme, any one works to parse the host
+        let Ok(url) = url::Url::parse(&format!("https://{}", self.endpoint)) else {
+            return false;
+        };
+        let Some(host) = url.host_str() else {
+            return false;
+        };
+        self.insecure_hosts
+            .iter()
+            .any(|insecure| unbracketed(insecure).eq_ignore_ascii_case(unbracketed(host)))
+    }
+}
+
+/// Strips the brackets of an IPv6 host
+fn unbracketed(host: &str) -> &str {
+    host.trim_start_matches('[').trim_end_matches(']')
+}
 
 #[async_trait]
 pub trait Provider: Sized {
@@ -38,9 +447,36 @@ pub trait Provider: Sized {
         password: Option<String>,
     ) -> Result<Self>;
 
-    async fn get_status_by_format(&self, format: Format) -> StreamResponse<Vec<u8>>;
+    /// Creates the provider from the full configuration
+    ///
+    /// Defaults to [`Provider::try_new`], ignoring all settings the provider
+    /// does not support.
+    async fn try_new_with_config(config: ProviderConfig) -> Result<Self> {
+        Self::try_new(
+            config.endpoint,
+            config.is_secure,
+            config.username,
+            config.password,
+        )
+        .await
+    }
+
+    async fn get_status_by_format(&self, format: Format) -> StreamResponse<Bytes>;
+
+    /// Returns whether the provider is able to serve requests
+    ///
+    /// Defaults to true for providers without a persistent connection.
+    fn ready(&self) -> bool {
+        true
+    }
 }
 
+/// The datasets of EVM chains
+///
+/// Blocks, logs, transactions and transfers are served by every provider. The
+/// other datasets were added later and default to an
+/// [`Error::InvalidRequest`], so implementors
+/// outside of this crate keep compiling and only override what they support.
 #[async_trait]
 pub trait ChainProvider {
     async fn get_blocks_by_format(
@@ -48,28 +484,98 @@ pub trait ChainProvider {
         request: GetBlocksRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>>;
+    ) -> StreamResponse<Bytes>;
     async fn get_logs_by_format(
         &self,
         request: GetLogsRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>>;
+    ) -> StreamResponse<Bytes>;
     async fn get_txs_by_format(
         &self,
         request: GetTxsRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>>;
+    ) -> StreamResponse<Bytes>;
 
     async fn get_transfers_by_format(
         &self,
         request: GetTransfersRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>>;
+    ) -> StreamResponse<Bytes>;
+
+    async fn get_receipts_by_format(
+        &self,
+        _request: GetReceiptsRequest,
+        _format: Format,
+        _deltas: bool,
+    ) -> StreamResponse<Bytes> {
+        Err(Error::InvalidRequest(
+            "receipts are not supported by this provider",
+        ))
+    }
+
+    async fn get_internal_txs_by_format(
+        &self,
+        _request: GetInternalTxsRequest,
+        _format: Format,
+        _deltas: bool,
+    ) -> StreamResponse<Bytes> {
+        Err(Error::InvalidRequest(
+            "internal transactions are not supported by this provider",
+        ))
+    }
+
+    async fn get_contract_creations_by_format(
+        &self,
+        _request: GetContractCreationsRequest,
+        _format: Format,
+        _deltas: bool,
+    ) -> StreamResponse<Bytes> {
+        Err(Error::InvalidRequest(
+            "contract creations are not supported by this provider",
+        ))
+    }
+
+    async fn get_withdrawals_by_format(
+        &self,
+        _request: GetWithdrawalsRequest,
+        _format: Format,
+        _deltas: bool,
+    ) -> StreamResponse<Bytes> {
+        Err(Error::InvalidRequest(
+            "withdrawals are not supported by this provider",
+        ))
+    }
+
+    async fn get_balances_by_format(
+        &self,
+        _request: GetBalancesRequest,
+        _format: Format,
+        _deltas: bool,
+    ) -> StreamResponse<Bytes> {
+        Err(Error::InvalidRequest(
+            "balances are not supported by this provider",
+        ))
+    }
+
+    /// Streams the transactions entering the mempool
+    ///
+    /// Only supported over WebSocket, see [`GetPendingTxsRequest`] for the
+    /// semantics of mempool data.
+    async fn get_pending_txs_by_format(
+        &self,
+        _request: GetPendingTxsRequest,
+        _format: Format,
+    ) -> StreamResponse<Bytes> {
+        Err(Error::InvalidRequest(
+            "pending transactions are not supported by this provider",
+        ))
+    }
 }
 
+#[cfg(feature = "uniswap")]
 #[async_trait]
 pub trait UniswapV2Provider {
     async fn get_pairs_by_format(
@@ -77,15 +583,16 @@ pub trait UniswapV2Provider {
         request: GetPairsRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>>;
+    ) -> StreamResponse<Bytes>;
     async fn get_prices_by_format(
         &self,
         request: requests::uniswap_v2::GetPricesRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>>;
+    ) -> StreamResponse<Bytes>;
 }
 
+#[cfg(feature = "uniswap")]
 #[async_trait]
 pub trait UniswapV3Provider {
     async fn get_pools_by_format(
@@ -93,15 +600,16 @@ pub trait UniswapV3Provider {
         request: GetPoolsRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>>;
+    ) -> StreamResponse<Bytes>;
     async fn get_prices_by_format(
         &self,
         request: requests::uniswap_v3::GetPricesRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>>;
+    ) -> StreamResponse<Bytes>;
 }
 
+#[cfg(feature = "curve")]
 #[async_trait]
 pub trait CurveProvider {
     async fn get_tokens_by_format(
@@ -109,19 +617,19 @@ pub trait CurveProvider {
         request: GetCrvTokenRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>>;
+    ) -> StreamResponse<Bytes>;
     async fn get_pools_by_format(
         &self,
         request: GetCrvPoolRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>>;
+    ) -> StreamResponse<Bytes>;
     async fn get_prices_by_format(
         &self,
         request: GetCrvPriceRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>>;
+    ) -> StreamResponse<Bytes>;
 }
 
 #[async_trait]
@@ -131,23 +639,34 @@ pub trait Erc20Provider {
         request: GetErc20Request,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>>;
+    ) -> StreamResponse<Bytes>;
 
     async fn get_erc20_approval_by_format(
         &self,
         request: GetErc20ApprovalsRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>>;
+    ) -> StreamResponse<Bytes>;
 
     async fn get_erc20_transfers_by_format(
         &self,
         request: GetErc20TransferssRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>>;
+    ) -> StreamResponse<Bytes>;
+}
+
+#[async_trait]
+pub trait Erc721Provider {
+    async fn get_erc721_transfers_by_format(
+        &self,
+        request: GetErc721TransfersRequest,
+        format: Format,
+        deltas: bool,
+    ) -> StreamResponse<Bytes>;
 }
 
+#[cfg(feature = "fuel")]
 #[async_trait]
 pub trait FuelProvider {
     async fn get_fuel_blocks_by_format(
@@ -155,44 +674,45 @@ pub trait FuelProvider {
         request: GetFuelBlocksRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>>;
+    ) -> StreamResponse<Bytes>;
 
     async fn get_fuel_logs_by_format(
         &self,
         request: GetFuelLogsRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>>;
+    ) -> StreamResponse<Bytes>;
 
     async fn get_fuel_txs_by_format(
         &self,
         request: GetFuelTxsRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>>;
+    ) -> StreamResponse<Bytes>;
 
     async fn get_fuel_receipts_by_format(
         &self,
         request: GetFuelReceiptsRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>>;
+    ) -> StreamResponse<Bytes>;
 
     async fn get_fuel_unspent_utxos_by_format(
         &self,
         request: GetUtxoRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>>;
+    ) -> StreamResponse<Bytes>;
 
     async fn get_fuel_spark_orders_by_format(
         &self,
         request: GetSparkOrderRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>>;
+    ) -> StreamResponse<Bytes>;
 }
 
+#[cfg(feature = "btc")]
 #[async_trait]
 pub trait BtcProvider {
     async fn get_btc_blocks_by_format(
@@ -200,12 +720,192 @@ pub trait BtcProvider {
         request: GetBtcBlocksRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>>;
+    ) -> StreamResponse<Bytes>;
 
     async fn get_btc_txs_by_format(
         &self,
         request: GetBtcTxsRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>>;
+    ) -> StreamResponse<Bytes>;
+}
+
+/// An item of a resumable stream together with the cursor to resume after it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cursored<T> {
+    pub item: T,
+    /// The cursor to resume the stream right after this item, `None` if the
+    /// stream can not be resumed at this item
+    pub cursor: Option<String>,
+    /// The epoch of the server stream the item belongs to, `None` if unknown
+    ///
+    /// The server resets a stream by starting a new epoch, e.g. after a
+    /// restart, and continues with the data of the new epoch. Cursors are
+    /// scoped to an epoch, so when the epoch of an item differs from the
+    /// previous one, all cursors received before it can no longer be used.
+    /// Streams without cursors learn about the change from the events of the
+    /// WebSocket provider.
+    pub epoch: Option<u64>,
+}
+
+/// Streams that can be resumed from a cursor persisted by a previous process
+#[async_trait]
+pub trait ReplayProvider {
+    /// Streams the JSON records of the requested logs, starting right after
+    /// `cursor` if given
+    async fn replay_logs_records(
+        &self,
+        request: GetLogsRequest,
+        cursor: Option<String>,
+    ) -> StreamResponse<Cursored<Bytes>>;
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::TryStreamExt;
+
+    use super::*;
+
+    /// A provider that only implements the required methods
+    struct MinimalProvider;
+
+    #[async_trait]
+    impl ChainProvider for MinimalProvider {
+        async fn get_blocks_by_format(
+            &self,
+            _: GetBlocksRequest,
+            _: Format,
+            _: bool,
+        ) -> StreamResponse<Bytes> {
+            Ok(ResponseStream::new(futures::stream::empty()))
+        }
+
+        async fn get_logs_by_format(
+            &self,
+            _: GetLogsRequest,
+            _: Format,
+            _: bool,
+        ) -> StreamResponse<Bytes> {
+            Ok(ResponseStream::new(futures::stream::empty()))
+        }
+
+        async fn get_txs_by_format(
+            &self,
+            _: GetTxsRequest,
+            _: Format,
+            _: bool,
+        ) -> StreamResponse<Bytes> {
+            Ok(ResponseStream::new(futures::stream::empty()))
+        }
+
+        async fn get_transfers_by_format(
+            &self,
+            _: GetTransfersRequest,
+            _: Format,
+            _: bool,
+        ) -> StreamResponse<Bytes> {
+            Ok(ResponseStream::new(futures::stream::empty()))
+        }
+    }
+
+    fn insecure(endpoint: &str, host: &str) -> bool {
+        let mut config = ProviderConfig::new(endpoint.to_owned(), true, None, None);
+        config.insecure_hosts.push(host.to_owned());
+        config.skips_tls_verification()
+    }
+
+    #[test]
+    fn tls_verification_is_skipped_only_for_insecure_hosts() {
+        assert!(insecure("localhost:8443/api", "localhost"));
+        assert!(insecure("LocalHost", "localhost"));
+        assert!(insecure("[::1]:8443", "::1"));
+        assert!(insecure("[::1]:8443", "[::1]"));
+        assert!(!insecure("[::1]:8443", "::"));
+        assert!(!insecure("app.superchain.network", "localhost"));
+        assert!(!insecure("localhost.evil.com", "localhost"));
+    }
+
+    #[test]
+    fn optional_datasets_default_to_invalid_request() {
+        let provider = MinimalProvider;
+
+        let result = futures::executor::block_on(provider.get_receipts_by_format(
+            Default::default(),
+            Format::JsonStream,
+            false,
+        ));
+
+        assert!(matches!(result, Err(Error::InvalidRequest(_))));
+    }
+
+    #[test]
+    fn inherent_methods_keep_the_concrete_type() {
+        let lines = futures::stream::iter([Ok(Bytes::from_static(b"{\"n\":1}\n{\"n\":2}\n"))]);
+        let decoded: ResponseStream<serde_json::Value> = ResponseStream::new(lines).decode();
+        let items: Vec<_> =
+            futures::executor::block_on(decoded.into_inner().try_collect()).unwrap();
+        assert_eq!(
+            items,
+            [serde_json::json!({ "n": 1 }), serde_json::json!({ "n": 2 })]
+        );
+
+        let endless = ResponseStream::new(futures::stream::pending::<Result<u64>>());
+        let stopped = endless.take_until(futures::future::ready(()));
+        assert!(futures::executor::block_on(stopped.collect::<Vec<_>>()).is_empty());
+    }
+
+    #[test]
+    fn resilient_stream_skips_malformed_rows() {
+        let lines = futures::stream::iter([
+            Ok(Bytes::from_static(b"{\"n\":1}\n{\"n\":\n{\"n\":3}\n")),
+            Err(Error::ErrorResponse(crate::core::error::ResponseError {
+                status: 500,
+                error: "internal".into(),
+            })),
+        ]);
+        let skipped = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = skipped.clone();
+        let decoded = ResponseStream::new(lines)
+            .decode::<serde_json::Value>()
+            .resilient_with(move |_| {
+                counter.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            });
+
+        let items = futures::executor::block_on(decoded.collect::<Vec<_>>());
+
+        let [Ok(first), Ok(second), Err(Error::ErrorResponse(_))] = items.as_slice() else {
+            panic!("{items:?}");
+        };
+        assert_eq!(
+            (first, second),
+            (
+                &serde_json::json!({ "n": 1 }),
+                &serde_json::json!({ "n": 3 })
+            )
+        );
+        // the error response is yielded, not skipped
+        assert_eq!(skipped.load(std::sync::atomic::Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn silent_stream_stalls_after_the_timeout() {
+        let (sender, items) = futures::channel::mpsc::unbounded();
+        let timeout = Duration::from_secs(5);
+        let mut stream = ResponseStream::new(items).with_stall_timeout(timeout);
+
+        // every item restarts the timer
+        for item in 1..=3 {
+            tokio::time::sleep(Duration::from_secs(4)).await;
+            sender.unbounded_send(Ok(item)).unwrap();
+            assert_eq!(stream.next().await.unwrap().unwrap(), item);
+        }
+
+        let start = tokio::time::Instant::now();
+        assert!(matches!(
+            stream.next().await,
+            Some(Err(Error::StreamStalled(stalled))) if stalled == timeout
+        ));
+        assert_eq!(start.elapsed(), timeout);
+        assert!(stream.next().await.is_none());
+    }
 }
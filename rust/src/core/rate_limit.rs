@@ -0,0 +1,139 @@
+use std::{
+    num::NonZeroU32,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use tokio::time::Instant;
+
+/// A token bucket limiting the rate of outgoing requests
+///
+/// The bucket holds up to `burst` tokens and is refilled with
+/// `requests_per_sec` tokens per second. Every request takes one token and
+/// waits for the next one if the bucket is empty.
+#[derive(Clone, Debug)]
+pub(crate) struct RateLimiter {
+    requests_per_sec: f64,
+    burst: f64,
+    bucket: Arc<Mutex<Bucket>>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+    /// Set when the server asked to back off, no tokens are handed out until
+    /// then
+    blocked_until: Option<Instant>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(requests_per_sec: NonZeroU32, burst: NonZeroU32) -> Self {
+        let burst = f64::from(burst.get());
+        Self {
+            requests_per_sec: f64::from(requests_per_sec.get()),
+            burst,
+            bucket: Arc::new(Mutex::new(Bucket {
+                tokens: burst,
+                last_refill: Instant::now(),
+                blocked_until: None,
+            })),
+        }
+    }
+
+    /// Waits until a request may be sent
+    pub(crate) async fn acquire(&self) {
+        loop {
+            let wait = {
+                let mut bucket = self.bucket.lock().unwrap();
+                let now = Instant::now();
+
+                // the bucket is not refilled while backing off
+                let refill_from = bucket
+                    .blocked_until
+                    .map_or(bucket.last_refill, |until| until.max(bucket.last_refill));
+                let elapsed = now.saturating_duration_since(refill_from).as_secs_f64();
+                bucket.tokens = (bucket.tokens + elapsed * self.requests_per_sec).min(self.burst);
+                bucket.last_refill = now.max(refill_from);
+
+                match bucket.blocked_until {
+                    Some(until) if until > now => until - now,
+                    _ if bucket.tokens >= 1.0 => {
+                        bucket.tokens -= 1.0;
+                        return;
+                    }
+                    _ => Duration::from_secs_f64((1.0 - bucket.tokens) / self.requests_per_sec),
+                }
+            };
+
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Stops handing out tokens for the given duration and drains the bucket
+    /// down to at most one token for the retry, e.g. after the server responded
+    /// with `429 Too Many Requests`
+    #[cfg(feature = "http")]
+    pub(crate) fn back_off(&self, duration: Duration) {
+        let mut bucket = self.bucket.lock().unwrap();
+        let until = Instant::now() + duration;
+
+        bucket.tokens = bucket.tokens.min(1.0);
+        bucket.blocked_until = Some(bucket.blocked_until.map_or(until, |u| u.max(until)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(requests_per_sec: u32, burst: u32) -> RateLimiter {
+        RateLimiter::new(
+            NonZeroU32::new(requests_per_sec).unwrap(),
+            NonZeroU32::new(burst).unwrap(),
+        )
+    }
+
+    /// Acquires `n` tokens and returns when each was handed out
+    async fn acquire(limiter: &RateLimiter, n: usize) -> Vec<Duration> {
+        let start = Instant::now();
+        let mut times = Vec::new();
+        for _ in 0..n {
+            limiter.acquire().await;
+            times.push(start.elapsed());
+        }
+        times
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn spaces_requests_after_the_burst() {
+        let limiter = limiter(2, 2);
+
+        let times = acquire(&limiter, 4).await;
+
+        assert_eq!(times, [0, 0, 500, 1000].map(Duration::from_millis));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn refills_up_to_the_burst() {
+        let limiter = limiter(2, 2);
+        acquire(&limiter, 2).await;
+
+        tokio::time::sleep(Duration::from_secs(10)).await;
+        let times = acquire(&limiter, 3).await;
+
+        assert_eq!(times, [0, 0, 500].map(Duration::from_millis));
+    }
+
+    #[cfg(feature = "http")]
+    #[tokio::test(start_paused = true)]
+    async fn backs_off_for_the_retry_after() {
+        let limiter = limiter(2, 2);
+
+        limiter.back_off(Duration::from_secs(3));
+        let times = acquire(&limiter, 2).await;
+
+        // the bucket was drained, so only the retry is sent right away
+        assert_eq!(times, [3000, 3500].map(Duration::from_millis));
+    }
+}
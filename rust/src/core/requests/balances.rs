@@ -0,0 +1,90 @@
+use std::collections::HashSet;
+
+use ethers_core::types::Address;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    core::types::{default_chains, ChainId},
+    query::Bound,
+    utils::{deserialize_comma_separated, serialize_comma_separated},
+};
+
+/// The balances of accounts at a single block
+///
+/// Returns the native balance of every address if `token__in` is empty,
+/// otherwise the balances of the given ERC-20 tokens.
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq, Eq)]
+#[allow(non_snake_case)]
+pub struct GetBalancesRequest {
+    #[serde(default = "default_chains")]
+    #[serde(
+        serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
+        skip_serializing_if = "HashSet::is_empty"
+    )]
+    pub chains: HashSet<ChainId>,
+
+    // The block the balances are read at, the latest block by default
+    #[serde(default)]
+    pub at_block: Bound,
+
+    /// The accounts to return the balances of
+    #[serde(default)]
+    #[serde(
+        serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
+        skip_serializing_if = "HashSet::is_empty"
+    )]
+    pub address__in: HashSet<Address>,
+    /// The ERC-20 tokens to return the balances of, the native balance if
+    /// empty
+    #[serde(default)]
+    #[serde(
+        serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
+        skip_serializing_if = "HashSet::is_empty"
+    )]
+    pub token__in: HashSet<Address>,
+}
+
+impl Default for GetBalancesRequest {
+    fn default() -> Self {
+        Self {
+            chains: default_chains(),
+            at_block: Bound::default(),
+            address__in: HashSet::new(),
+            token__in: HashSet::new(),
+        }
+    }
+}
+
+impl_query_hash!(GetBalancesRequest);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filters_are_serialized() {
+        let native = GetBalancesRequest {
+            chains: HashSet::from([ChainId::ETH]),
+            at_block: Bound::Exact(17_000_000),
+            address__in: HashSet::from([Address::repeat_byte(1)]),
+            ..Default::default()
+        };
+        let erc20 = GetBalancesRequest {
+            token__in: HashSet::from([Address::repeat_byte(2)]),
+            ..native.clone()
+        };
+
+        let prefix = concat!(
+            "chains=ETH&at_block=17000000",
+            "&address__in=0x0101010101010101010101010101010101010101",
+        );
+        assert_eq!(serde_urlencoded::to_string(&native).unwrap(), prefix);
+        assert_eq!(
+            serde_urlencoded::to_string(&erc20).unwrap(),
+            format!("{prefix}&token__in=0x0202020202020202020202020202020202020202")
+        );
+    }
+}
@@ -4,16 +4,17 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     core::types::{default_chains, ChainId},
-    query::Bound,
-    utils::serialize_comma_separated,
+    query::{Bound, Order},
+    utils::{deserialize_comma_separated, serialize_comma_separated},
 };
 
-#[derive(Clone, Deserialize, Serialize, Debug)]
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq, Eq)]
 #[allow(non_snake_case)]
 pub struct GetBlocksRequest {
     #[serde(default = "default_chains")]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub chains: HashSet<ChainId>,
@@ -31,6 +32,22 @@ pub struct GetBlocksRequest {
     // Exclusive upper bound if is Some for block timestamp
     #[serde(default)]
     pub to_timestamp: Option<i64>,
+
+    /// The columns to return, all if empty. Requires server support for
+    /// projections. Columns that were not selected are left at their default
+    /// when decoding into the typed rows.
+    #[serde(default)]
+    #[serde(
+        serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
+        skip_serializing_if = "HashSet::is_empty"
+    )]
+    pub fields: HashSet<String>,
+
+    /// The order of the results, ascending if `None`. Descending order is
+    /// only valid for bounded ranges.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order: Option<Order>,
 }
 
 impl Default for GetBlocksRequest {
@@ -41,6 +58,12 @@ impl Default for GetBlocksRequest {
             to_block: Bound::default(),
             from_timestamp: None,
             to_timestamp: None,
+            fields: HashSet::new(),
+            order: None,
         }
     }
 }
+
+impl_block_range!(GetBlocksRequest);
+impl_projection!(GetBlocksRequest);
+impl_query_hash!(GetBlocksRequest);
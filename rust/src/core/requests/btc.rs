@@ -4,16 +4,17 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     core::types::{default_chains, ChainId},
-    query::Bound,
-    utils::serialize_comma_separated,
+    query::{Bound, Order},
+    utils::{deserialize_comma_separated, serialize_comma_separated},
 };
 
-#[derive(Clone, Deserialize, Serialize, Debug)]
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq, Eq)]
 #[allow(non_snake_case)]
 pub struct GetBtcBlocksRequest {
     #[serde(default = "default_chains")]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub chains: HashSet<ChainId>,
@@ -24,6 +25,11 @@ pub struct GetBtcBlocksRequest {
     // Exclusive upper bound if is Some for block number
     #[serde(default)]
     pub to_block: Bound,
+
+    /// The order of the results, ascending if `None`. Descending order is
+    /// only valid for bounded ranges.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order: Option<Order>,
 }
 
 impl Default for GetBtcBlocksRequest {
@@ -32,16 +38,18 @@ impl Default for GetBtcBlocksRequest {
             chains: default_chains(),
             from_block: Bound::default(),
             to_block: Bound::default(),
+            order: None,
         }
     }
 }
 
-#[derive(Clone, Deserialize, Serialize, Debug)]
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq, Eq)]
 #[allow(non_snake_case)]
 pub struct GetBtcTxsRequest {
     #[serde(default = "default_chains")]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub chains: HashSet<ChainId>,
@@ -52,6 +60,11 @@ pub struct GetBtcTxsRequest {
     // Exclusive upper bound if is Some for block number
     #[serde(default)]
     pub to_block: Bound,
+
+    /// The order of the results, ascending if `None`. Descending order is
+    /// only valid for bounded ranges.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order: Option<Order>,
 }
 
 impl Default for GetBtcTxsRequest {
@@ -60,6 +73,10 @@ impl Default for GetBtcTxsRequest {
             chains: default_chains(),
             from_block: Bound::default(),
             to_block: Bound::default(),
+            order: None,
         }
     }
 }
+
+impl_block_range!(GetBtcBlocksRequest, GetBtcTxsRequest);
+impl_query_hash!(GetBtcBlocksRequest, GetBtcTxsRequest);
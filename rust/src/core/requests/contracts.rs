@@ -0,0 +1,92 @@
+use std::collections::HashSet;
+
+use ethers_core::types::Address;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    core::types::{default_chains, ChainId},
+    query::{Bound, Order},
+    utils::{deserialize_comma_separated, serialize_comma_separated},
+};
+
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq, Eq)]
+#[allow(non_snake_case)]
+pub struct GetContractCreationsRequest {
+    #[serde(default = "default_chains")]
+    #[serde(
+        serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
+        skip_serializing_if = "HashSet::is_empty"
+    )]
+    pub chains: HashSet<ChainId>,
+
+    // Inclusive lower bound if is Some for block number
+    #[serde(default)]
+    pub from_block: Bound,
+    // Exclusive upper bound if is Some for block number
+    #[serde(default)]
+    pub to_block: Bound,
+
+    #[serde(default)]
+    #[serde(
+        serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
+        skip_serializing_if = "HashSet::is_empty"
+    )]
+    pub address__in: HashSet<Address>,
+
+    #[serde(default)]
+    #[serde(
+        serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
+        skip_serializing_if = "HashSet::is_empty"
+    )]
+    pub creator__in: HashSet<Address>,
+
+    /// The order of the results, ascending if `None`. Descending order is
+    /// only valid for bounded ranges.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order: Option<Order>,
+}
+
+impl Default for GetContractCreationsRequest {
+    fn default() -> Self {
+        Self {
+            chains: default_chains(),
+            from_block: Bound::default(),
+            to_block: Bound::default(),
+            address__in: HashSet::new(),
+            creator__in: HashSet::new(),
+            order: None,
+        }
+    }
+}
+
+impl_block_range!(GetContractCreationsRequest);
+impl_query_hash!(GetContractCreationsRequest);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_deployments_of_a_creator_are_serialized() {
+        let request = GetContractCreationsRequest {
+            chains: HashSet::from([ChainId::ETH]),
+            from_block: Bound::Latest,
+            to_block: Bound::Subscribe,
+            creator__in: HashSet::from([Address::repeat_byte(1)]),
+            ..Default::default()
+        };
+
+        let query = serde_urlencoded::to_string(&request).unwrap();
+
+        assert_eq!(
+            query,
+            concat!(
+                "chains=ETH&from_block=latest&to_block=none",
+                "&creator__in=0x0101010101010101010101010101010101010101",
+            )
+        );
+    }
+}
@@ -5,16 +5,17 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     core::types::{default_chains, ChainId},
-    query::Bound,
-    utils::serialize_comma_separated,
+    query::{Bound, Order},
+    utils::{deserialize_comma_separated, serialize_comma_separated},
 };
 
-#[derive(Clone, Deserialize, Serialize, Debug)]
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq, Eq)]
 #[allow(non_snake_case)]
 pub struct GetCrvTokenRequest {
     #[serde(default = "default_chains")]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub chains: HashSet<ChainId>,
@@ -29,6 +30,7 @@ pub struct GetCrvTokenRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub address__in: HashSet<Address>,
@@ -36,6 +38,7 @@ pub struct GetCrvTokenRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub symbol__in: HashSet<String>,
@@ -43,6 +46,7 @@ pub struct GetCrvTokenRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub name__in: HashSet<String>,
@@ -50,6 +54,7 @@ pub struct GetCrvTokenRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub pool_address__in: HashSet<Address>,
@@ -59,6 +64,11 @@ pub struct GetCrvTokenRequest {
 
     #[serde(default)]
     pub decimals__lte: Option<u8>,
+
+    /// The order of the results, ascending if `None`. Descending order is
+    /// only valid for bounded ranges.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order: Option<Order>,
 }
 
 impl Default for GetCrvTokenRequest {
@@ -73,16 +83,18 @@ impl Default for GetCrvTokenRequest {
             pool_address__in: HashSet::new(),
             decimals__gte: None,
             decimals__lte: None,
+            order: None,
         }
     }
 }
 
-#[derive(Clone, Deserialize, Serialize, Debug)]
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq, Eq)]
 #[allow(non_snake_case)]
 pub struct GetCrvPoolRequest {
     #[serde(default = "default_chains")]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub chains: HashSet<ChainId>,
@@ -97,6 +109,7 @@ pub struct GetCrvPoolRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub pool_address__in: HashSet<Address>,
@@ -104,6 +117,7 @@ pub struct GetCrvPoolRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub token__in: HashSet<Address>,
@@ -111,6 +125,7 @@ pub struct GetCrvPoolRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub owner__in: HashSet<Address>,
@@ -118,6 +133,7 @@ pub struct GetCrvPoolRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub base_pool__in: HashSet<Address>,
@@ -125,6 +141,7 @@ pub struct GetCrvPoolRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub coins__in: HashSet<Address>,
@@ -132,6 +149,7 @@ pub struct GetCrvPoolRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub base_coins__in: HashSet<Address>,
@@ -177,6 +195,11 @@ pub struct GetCrvPoolRequest {
 
     #[serde(default)]
     pub n_coins__lte: Option<u8>,
+
+    /// The order of the results, ascending if `None`. Descending order is
+    /// only valid for bounded ranges.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order: Option<Order>,
 }
 
 impl Default for GetCrvPoolRequest {
@@ -205,6 +228,7 @@ impl Default for GetCrvPoolRequest {
             future_a_time__lte: None,
             n_coins__gte: None,
             n_coins__lte: None,
+            order: None,
         }
     }
 }
@@ -215,6 +239,7 @@ pub struct GetCrvPriceRequest {
     #[serde(default = "default_chains")]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub chains: HashSet<ChainId>,
@@ -229,6 +254,7 @@ pub struct GetCrvPriceRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub pool_address__in: HashSet<Address>,
@@ -236,6 +262,7 @@ pub struct GetCrvPriceRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub buyer__in: HashSet<Address>,
@@ -243,6 +270,7 @@ pub struct GetCrvPriceRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub tokens_address__in: HashSet<Address>,
@@ -250,6 +278,7 @@ pub struct GetCrvPriceRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub tokens_symbol__in: HashSet<String>,
@@ -257,6 +286,7 @@ pub struct GetCrvPriceRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub sold_address__in: HashSet<Address>,
@@ -264,6 +294,7 @@ pub struct GetCrvPriceRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub sold_symbol__in: HashSet<String>,
@@ -277,6 +308,7 @@ pub struct GetCrvPriceRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub bought_address__in: HashSet<Address>,
@@ -284,6 +316,7 @@ pub struct GetCrvPriceRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub bought_symbol__in: HashSet<String>,
@@ -311,6 +344,11 @@ pub struct GetCrvPriceRequest {
 
     #[serde(default)]
     pub tokens_bought__lte: Option<f64>,
+
+    /// The order of the results, ascending if `None`. Descending order is
+    /// only valid for bounded ranges.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order: Option<Order>,
 }
 
 impl Default for GetCrvPriceRequest {
@@ -337,6 +375,11 @@ impl Default for GetCrvPriceRequest {
             tokens_sold__lte: None,
             tokens_bought__gte: None,
             tokens_bought__lte: None,
+            order: None,
         }
     }
 }
+
+impl_block_range!(GetCrvTokenRequest, GetCrvPoolRequest, GetCrvPriceRequest);
+impl_query_hash!(GetCrvTokenRequest, GetCrvPoolRequest);
+impl_query_eq!(GetCrvPriceRequest);
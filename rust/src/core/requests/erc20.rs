@@ -5,16 +5,17 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     core::types::{default_chains, ChainId},
-    query::Bound,
-    utils::serialize_comma_separated,
+    query::{Bound, Order},
+    utils::{deserialize_comma_separated, serialize_comma_separated},
 };
 
-#[derive(Clone, Deserialize, Serialize, Debug)]
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq, Eq)]
 #[allow(non_snake_case)]
 pub struct GetErc20Request {
     #[serde(default = "default_chains")]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub chains: HashSet<ChainId>,
@@ -29,6 +30,7 @@ pub struct GetErc20Request {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub address__in: HashSet<Address>,
@@ -36,6 +38,7 @@ pub struct GetErc20Request {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub symbol__in: HashSet<String>,
@@ -43,6 +46,7 @@ pub struct GetErc20Request {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub name__in: HashSet<String>,
@@ -52,6 +56,11 @@ pub struct GetErc20Request {
 
     #[serde(default)]
     pub decimals__lte: Option<u8>,
+
+    /// The order of the results, ascending if `None`. Descending order is
+    /// only valid for bounded ranges.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order: Option<Order>,
 }
 
 impl Default for GetErc20Request {
@@ -65,6 +74,7 @@ impl Default for GetErc20Request {
             name__in: HashSet::new(),
             decimals__gte: None,
             decimals__lte: None,
+            order: None,
         }
     }
 }
@@ -75,6 +85,7 @@ pub struct GetErc20ApprovalsRequest {
     #[serde(default = "default_chains")]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub chains: HashSet<ChainId>,
@@ -89,6 +100,7 @@ pub struct GetErc20ApprovalsRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub address__in: HashSet<Address>,
@@ -96,6 +108,7 @@ pub struct GetErc20ApprovalsRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub symbol__in: HashSet<String>,
@@ -103,6 +116,7 @@ pub struct GetErc20ApprovalsRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub name__in: HashSet<String>,
@@ -116,6 +130,7 @@ pub struct GetErc20ApprovalsRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub owner__in: HashSet<Address>,
@@ -123,6 +138,7 @@ pub struct GetErc20ApprovalsRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub spender__in: HashSet<Address>,
@@ -132,6 +148,11 @@ pub struct GetErc20ApprovalsRequest {
 
     #[serde(default)]
     pub value__gte: Option<f64>,
+
+    /// The order of the results, ascending if `None`. Descending order is
+    /// only valid for bounded ranges.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order: Option<Order>,
 }
 
 impl Default for GetErc20ApprovalsRequest {
@@ -149,6 +170,7 @@ impl Default for GetErc20ApprovalsRequest {
             address__in: HashSet::new(),
             value__lte: None,
             value__gte: None,
+            order: None,
         }
     }
 }
@@ -159,6 +181,7 @@ pub struct GetErc20TransferssRequest {
     #[serde(default = "default_chains")]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub chains: HashSet<ChainId>,
@@ -173,6 +196,7 @@ pub struct GetErc20TransferssRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub address__in: HashSet<Address>,
@@ -180,6 +204,7 @@ pub struct GetErc20TransferssRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub symbol__in: HashSet<String>,
@@ -187,6 +212,7 @@ pub struct GetErc20TransferssRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub name__in: HashSet<String>,
@@ -200,6 +226,7 @@ pub struct GetErc20TransferssRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub from__in: HashSet<Address>,
@@ -207,6 +234,7 @@ pub struct GetErc20TransferssRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub to__in: HashSet<Address>,
@@ -216,6 +244,11 @@ pub struct GetErc20TransferssRequest {
 
     #[serde(default)]
     pub value__gte: Option<f64>,
+
+    /// The order of the results, ascending if `None`. Descending order is
+    /// only valid for bounded ranges.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order: Option<Order>,
 }
 
 impl Default for GetErc20TransferssRequest {
@@ -233,6 +266,15 @@ impl Default for GetErc20TransferssRequest {
             decimals__lte: None,
             from__in: HashSet::new(),
             to__in: HashSet::new(),
+            order: None,
         }
     }
 }
+
+impl_block_range!(
+    GetErc20Request,
+    GetErc20ApprovalsRequest,
+    GetErc20TransferssRequest
+);
+impl_query_hash!(GetErc20Request);
+impl_query_eq!(GetErc20ApprovalsRequest, GetErc20TransferssRequest);
@@ -0,0 +1,112 @@
+use std::collections::HashSet;
+
+use ethers_core::types::{Address, U256};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    core::types::{default_chains, ChainId},
+    query::{Bound, Order},
+    utils::{deserialize_comma_separated, serialize_comma_separated},
+};
+
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq, Eq)]
+#[allow(non_snake_case)]
+pub struct GetErc721TransfersRequest {
+    #[serde(default = "default_chains")]
+    #[serde(
+        serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
+        skip_serializing_if = "HashSet::is_empty"
+    )]
+    pub chains: HashSet<ChainId>,
+
+    // Inclusive lower bound if is Some for block number
+    #[serde(default)]
+    pub from_block: Bound,
+    // Exclusive upper bound if is Some for block number
+    #[serde(default)]
+    pub to_block: Bound,
+
+    #[serde(default)]
+    #[serde(
+        serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
+        skip_serializing_if = "HashSet::is_empty"
+    )]
+    pub contract__in: HashSet<Address>,
+
+    #[serde(default)]
+    #[serde(
+        serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
+        skip_serializing_if = "HashSet::is_empty"
+    )]
+    pub token_id__in: HashSet<U256>,
+
+    #[serde(default)]
+    #[serde(
+        serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
+        skip_serializing_if = "HashSet::is_empty"
+    )]
+    pub from__in: HashSet<Address>,
+
+    #[serde(default)]
+    #[serde(
+        serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
+        skip_serializing_if = "HashSet::is_empty"
+    )]
+    pub to__in: HashSet<Address>,
+
+    /// The order of the results, ascending if `None`. Descending order is
+    /// only valid for bounded ranges.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order: Option<Order>,
+}
+
+impl Default for GetErc721TransfersRequest {
+    fn default() -> Self {
+        Self {
+            chains: default_chains(),
+            from_block: Bound::default(),
+            to_block: Bound::default(),
+            contract__in: HashSet::new(),
+            token_id__in: HashSet::new(),
+            from__in: HashSet::new(),
+            to__in: HashSet::new(),
+            order: None,
+        }
+    }
+}
+
+impl_block_range!(GetErc721TransfersRequest);
+impl_query_hash!(GetErc721TransfersRequest);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_ids_are_serialized() {
+        let request = GetErc721TransfersRequest {
+            chains: HashSet::from([ChainId::ETH]),
+            from_block: Bound::Exact(10),
+            to_block: Bound::Exact(20),
+            contract__in: HashSet::from([Address::repeat_byte(1)]),
+            token_id__in: HashSet::from([U256::from(7), U256::from(255)]),
+            ..Default::default()
+        };
+
+        let query = serde_urlencoded::to_string(&request).unwrap();
+
+        assert_eq!(
+            query,
+            concat!(
+                "chains=ETH&from_block=10&to_block=20",
+                "&contract__in=0x0101010101010101010101010101010101010101",
+                "&token_id__in=0x7%2C0xff",
+            )
+        );
+    }
+}
@@ -9,16 +9,17 @@ use crate::{
         fuel::{OrderChangeType, OrderType, ReceiptType, TransactionType},
         ChainId,
     },
-    query::Bound,
-    utils::serialize_comma_separated,
+    query::{Bound, Order},
+    utils::{deserialize_comma_separated, serialize_comma_separated},
 };
 
-#[derive(Clone, Deserialize, Serialize, Debug)]
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq, Eq)]
 #[allow(non_snake_case)]
 pub struct GetFuelBlocksRequest {
     #[serde(default = "default_chains")]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub chains: HashSet<ChainId>,
@@ -37,6 +38,11 @@ pub struct GetFuelBlocksRequest {
     #[serde(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub da_block_number__lte: Option<u64>,
+
+    /// The order of the results, ascending if `None`. Descending order is
+    /// only valid for bounded ranges.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order: Option<Order>,
 }
 
 impl Default for GetFuelBlocksRequest {
@@ -47,16 +53,18 @@ impl Default for GetFuelBlocksRequest {
             to_block: Bound::default(),
             da_block_number__gte: None,
             da_block_number__lte: None,
+            order: None,
         }
     }
 }
 
-#[derive(Clone, Deserialize, Serialize, Debug)]
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq, Eq)]
 #[allow(non_snake_case)]
 pub struct GetFuelLogsRequest {
     #[serde(default = "default_chains")]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub chains: HashSet<ChainId>,
@@ -71,6 +79,7 @@ pub struct GetFuelLogsRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub id__in: HashSet<H256>,
@@ -78,6 +87,7 @@ pub struct GetFuelLogsRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub ra__in: HashSet<u64>,
@@ -85,9 +95,15 @@ pub struct GetFuelLogsRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub rb__in: HashSet<u64>,
+
+    /// The order of the results, ascending if `None`. Descending order is
+    /// only valid for bounded ranges.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order: Option<Order>,
 }
 
 impl Default for GetFuelLogsRequest {
@@ -99,16 +115,18 @@ impl Default for GetFuelLogsRequest {
             id__in: HashSet::new(),
             ra__in: HashSet::new(),
             rb__in: HashSet::new(),
+            order: None,
         }
     }
 }
 
-#[derive(Clone, Deserialize, Serialize, Debug)]
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq, Eq)]
 #[allow(non_snake_case)]
 pub struct GetFuelTxsRequest {
     #[serde(default = "default_chains")]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub chains: HashSet<ChainId>,
@@ -123,6 +141,7 @@ pub struct GetFuelTxsRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub transaction_type__in: HashSet<TransactionType>,
@@ -130,6 +149,7 @@ pub struct GetFuelTxsRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub metadata_contract_id__in: HashSet<H256>,
@@ -137,6 +157,7 @@ pub struct GetFuelTxsRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub input_contract_contract_id__in: HashSet<H256>,
@@ -144,6 +165,7 @@ pub struct GetFuelTxsRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub mint_asset_id__in: HashSet<H256>,
@@ -153,6 +175,11 @@ pub struct GetFuelTxsRequest {
 
     #[serde(default)]
     pub mint_amount__gte: Option<u64>,
+
+    /// The order of the results, ascending if `None`. Descending order is
+    /// only valid for bounded ranges.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order: Option<Order>,
 }
 
 impl Default for GetFuelTxsRequest {
@@ -167,16 +194,18 @@ impl Default for GetFuelTxsRequest {
             mint_asset_id__in: HashSet::new(),
             mint_amount__lte: None,
             mint_amount__gte: None,
+            order: None,
         }
     }
 }
 
-#[derive(Clone, Deserialize, Serialize, Debug)]
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq, Eq)]
 #[allow(non_snake_case)]
 pub struct GetFuelReceiptsRequest {
     #[serde(default = "default_chains")]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub chains: HashSet<ChainId>,
@@ -191,9 +220,15 @@ pub struct GetFuelReceiptsRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub receipt_type__in: HashSet<ReceiptType>,
+
+    /// The order of the results, ascending if `None`. Descending order is
+    /// only valid for bounded ranges.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order: Option<Order>,
 }
 
 impl Default for GetFuelReceiptsRequest {
@@ -203,16 +238,18 @@ impl Default for GetFuelReceiptsRequest {
             from_block: Bound::default(),
             to_block: Bound::default(),
             receipt_type__in: HashSet::new(),
+            order: None,
         }
     }
 }
 
-#[derive(Clone, Deserialize, Serialize, Debug)]
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq, Eq)]
 #[allow(non_snake_case)]
 pub struct GetSparkOrderRequest {
     #[serde(default = "default_chains")]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub chains: HashSet<ChainId>,
@@ -227,6 +264,7 @@ pub struct GetSparkOrderRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub order_id__in: HashSet<H256>,
@@ -234,38 +272,49 @@ pub struct GetSparkOrderRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub order_type__in: HashSet<OrderType>,
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub state_type__in: HashSet<OrderChangeType>,
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub user__in: HashSet<H256>,
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub owner__in: HashSet<H256>,
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub asset__in: HashSet<H256>,
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub address__in: HashSet<H256>,
+
+    /// The order of the results, ascending if `None`. Descending order is
+    /// only valid for bounded ranges.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order: Option<Order>,
 }
 
 impl Default for GetSparkOrderRequest {
@@ -281,16 +330,18 @@ impl Default for GetSparkOrderRequest {
             owner__in: HashSet::new(),
             asset__in: HashSet::new(),
             address__in: HashSet::new(),
+            order: None,
         }
     }
 }
 
-#[derive(Clone, Deserialize, Serialize, Debug)]
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq, Eq)]
 #[allow(non_snake_case)]
 pub struct GetUtxoRequest {
     #[serde(default = "default_chains")]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub chains: HashSet<ChainId>,
@@ -308,9 +359,15 @@ pub struct GetUtxoRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub address__in: HashSet<H256>,
+
+    /// The order of the results, ascending if `None`. Descending order is
+    /// only valid for bounded ranges.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order: Option<Order>,
 }
 
 impl Default for GetUtxoRequest {
@@ -321,6 +378,24 @@ impl Default for GetUtxoRequest {
             to_block: Bound::default(),
             unspent_at: Bound::default(),
             address__in: HashSet::new(),
+            order: None,
         }
     }
 }
+
+impl_block_range!(
+    GetFuelBlocksRequest,
+    GetFuelLogsRequest,
+    GetFuelTxsRequest,
+    GetFuelReceiptsRequest,
+    GetSparkOrderRequest,
+    GetUtxoRequest
+);
+impl_query_hash!(
+    GetFuelBlocksRequest,
+    GetFuelLogsRequest,
+    GetFuelTxsRequest,
+    GetFuelReceiptsRequest,
+    GetSparkOrderRequest,
+    GetUtxoRequest
+);
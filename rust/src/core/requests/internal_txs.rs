@@ -0,0 +1,110 @@
+use std::collections::HashSet;
+
+use ethers_core::types::{Address, U256};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    core::types::{default_chains, ethereum::CallType, ChainId},
+    query::{Bound, Order},
+    utils::{deserialize_comma_separated, serialize_comma_separated},
+};
+
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq, Eq)]
+#[allow(non_snake_case)]
+pub struct GetInternalTxsRequest {
+    #[serde(default = "default_chains")]
+    #[serde(
+        serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
+        skip_serializing_if = "HashSet::is_empty"
+    )]
+    pub chains: HashSet<ChainId>,
+
+    // Inclusive lower bound if is Some for block number
+    #[serde(default)]
+    pub from_block: Bound,
+    // Exclusive upper bound if is Some for block number
+    #[serde(default)]
+    pub to_block: Bound,
+
+    #[serde(default)]
+    #[serde(
+        serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
+        skip_serializing_if = "HashSet::is_empty"
+    )]
+    pub from__in: HashSet<Address>,
+    #[serde(default)]
+    #[serde(
+        serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
+        skip_serializing_if = "HashSet::is_empty"
+    )]
+    pub to__in: HashSet<Address>,
+
+    #[serde(default)]
+    pub value__gte: Option<U256>,
+    #[serde(default)]
+    pub value__lte: Option<U256>,
+
+    #[serde(default)]
+    #[serde(
+        serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
+        skip_serializing_if = "HashSet::is_empty"
+    )]
+    pub call_type__in: HashSet<CallType>,
+
+    /// The order of the results, ascending if `None`. Descending order is
+    /// only valid for bounded ranges.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order: Option<Order>,
+}
+
+impl Default for GetInternalTxsRequest {
+    fn default() -> Self {
+        Self {
+            chains: default_chains(),
+            from_block: Bound::default(),
+            to_block: Bound::default(),
+            from__in: HashSet::new(),
+            to__in: HashSet::new(),
+            value__gte: None,
+            value__lte: None,
+            call_type__in: HashSet::new(),
+            order: None,
+        }
+    }
+}
+
+impl_block_range!(GetInternalTxsRequest);
+impl_query_hash!(GetInternalTxsRequest);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filters_are_serialized() {
+        let request = GetInternalTxsRequest {
+            chains: HashSet::from([ChainId::ETH]),
+            from_block: Bound::Exact(10),
+            to_block: Bound::Exact(20),
+            from__in: HashSet::from([Address::repeat_byte(1)]),
+            value__gte: Some(U256::from(1_000)),
+            call_type__in: HashSet::from([CallType::DelegateCall, CallType::Call]),
+            ..Default::default()
+        };
+
+        let query = serde_urlencoded::to_string(&request).unwrap();
+
+        assert_eq!(
+            query,
+            concat!(
+                "chains=ETH&from_block=10&to_block=20",
+                "&from__in=0x0101010101010101010101010101010101010101",
+                "&value__gte=0x3e8&call_type__in=CALL%2CDELEGATECALL",
+            )
+        );
+    }
+}
@@ -5,16 +5,17 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     core::types::{default_chains, ChainId},
-    query::Bound,
-    utils::serialize_comma_separated,
+    query::{Bound, Order},
+    utils::{deserialize_comma_separated, serialize_comma_separated},
 };
 
-#[derive(Clone, Deserialize, Serialize, Debug)]
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq, Eq)]
 #[allow(non_snake_case)]
 pub struct GetLogsRequest {
     #[serde(default = "default_chains")]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub chains: HashSet<ChainId>,
@@ -29,6 +30,7 @@ pub struct GetLogsRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub address__in: HashSet<Address>,
@@ -36,27 +38,52 @@ pub struct GetLogsRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub topic0__in: HashSet<H256>,
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub topic1__in: HashSet<H256>,
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub topic2__in: HashSet<H256>,
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub topic3__in: HashSet<H256>,
+
+    /// Only return the number of matching rows instead of the rows
+    /// themselves. Requires server support for the `count_only` flag.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub count_only: bool,
+
+    /// The columns to return, all if empty. Requires server support for
+    /// projections. Columns that were not selected are left at their default
+    /// when decoding into the typed rows.
+    #[serde(default)]
+    #[serde(
+        serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
+        skip_serializing_if = "HashSet::is_empty"
+    )]
+    pub fields: HashSet<String>,
+
+    /// The order of the results, ascending if `None`. Descending order is
+    /// only valid for bounded ranges.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order: Option<Order>,
 }
 
 impl Default for GetLogsRequest {
@@ -70,6 +97,36 @@ impl Default for GetLogsRequest {
             topic1__in: HashSet::new(),
             topic2__in: HashSet::new(),
             topic3__in: HashSet::new(),
+            count_only: false,
+            fields: HashSet::new(),
+            order: None,
         }
     }
 }
+
+impl_block_range!(GetLogsRequest);
+impl_projection!(GetLogsRequest);
+impl_query_hash!(GetLogsRequest);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_string_round_trips() {
+        let request = GetLogsRequest {
+            chains: HashSet::from([ChainId::ETH]),
+            from_block: Bound::Exact(17_000_000),
+            to_block: Bound::FromLatest(10),
+            address__in: HashSet::from([Address::repeat_byte(1), Address::repeat_byte(2)]),
+            topic0__in: HashSet::from([H256::repeat_byte(3)]),
+            order: Some(Order::Desc),
+            ..Default::default()
+        };
+
+        let query = serde_urlencoded::to_string(&request).unwrap();
+        let decoded: GetLogsRequest = serde_urlencoded::from_str(&query).unwrap();
+
+        assert_eq!(decoded, request);
+    }
+}
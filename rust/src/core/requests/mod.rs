@@ -1,10 +1,227 @@
+/// Implements the block range helpers shared by all request structs
+macro_rules! impl_block_range {
+    ($($request:ty),+ $(,)?) => {
+        $(
+            impl $request {
+                /// Requests the last `n` blocks, from `latest - n` up to the
+                /// latest block
+                pub fn last_n_blocks(mut self, n: u64) -> Self {
+                    self.from_block = $crate::query::Bound::FromLatest(n);
+                    self.to_block = $crate::query::Bound::Latest;
+                    self
+                }
+
+                /// Sets both `from_block` and `to_block` from the given range
+                pub fn range(mut self, range: $crate::query::BlockRange) -> Self {
+                    self.from_block = range.from;
+                    self.to_block = range.to;
+                    self
+                }
+
+                /// Returns the results newest first
+                ///
+                /// Only valid for bounded ranges, see [`Self::validate`].
+                pub fn descending(mut self) -> Self {
+                    self.order = Some($crate::query::Order::Desc);
+                    self
+                }
+
+                /// Checks the request for combinations the server rejects
+                ///
+                /// A subscription never ends, so it can not be returned in
+                /// descending order.
+                pub fn validate(&self) -> $crate::Result<()> {
+                    if self.order == Some($crate::query::Order::Desc)
+                        && self.to_block == $crate::query::Bound::Subscribe
+                    {
+                        return Err($crate::Error::InvalidRequest(
+                            "descending order is not supported for subscriptions",
+                        ));
+                    }
+                    Ok(())
+                }
+
+                /// Renders the query string the HTTP provider sends for this
+                /// request, without the `format` parameter
+                pub fn to_query(&self) -> $crate::Result<String> {
+                    Ok(serde_urlencoded::to_string(self)?)
+                }
+            }
+
+            /// Renders the query string, see `to_query`
+            impl std::fmt::Display for $request {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    let query = self.to_query().map_err(|_| std::fmt::Error)?;
+                    f.write_str(&query)
+                }
+            }
+        )+
+    };
+}
+
+/// Implements the projection helper for request structs with a `fields` set
+macro_rules! impl_projection {
+    ($($request:ty),+ $(,)?) => {
+        $(
+            impl $request {
+                /// Only requests the given columns instead of full rows
+                ///
+                /// Requires server support for projections. The typed rows
+                /// still have every field, the columns that were not
+                /// selected are left at their `Default` rather than `None`.
+                pub fn select<I, S>(mut self, fields: I) -> Self
+                where
+                    I: IntoIterator<Item = S>,
+                    S: Into<String>,
+                {
+                    self.fields = fields.into_iter().map(Into::into).collect();
+                    self
+                }
+            }
+        )+
+    };
+}
+
+/// Implements `Hash` over the query string of a request
+///
+/// The sets of a request are not `Hash`, but they are serialized sorted, so
+/// the query string is canonical: requests that are equal have the same
+/// query string.
+macro_rules! impl_query_hash {
+    ($($request:ty),+ $(,)?) => {
+        $(
+            impl std::hash::Hash for $request {
+                fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+                    serde_urlencoded::to_string(self).ok().hash(state);
+                }
+            }
+        )+
+    };
+}
+
+/// Implements `PartialEq`, `Eq` and `Hash` over the query string of a request
+///
+/// Used for requests with float filters, which can not derive `Eq`. Comparing
+/// the rendered floats makes every request equal to itself, including ones
+/// with `NaN` filters.
+macro_rules! impl_query_eq {
+    ($($request:ty),+ $(,)?) => {
+        $(
+            impl PartialEq for $request {
+                fn eq(&self, other: &Self) -> bool {
+                    serde_urlencoded::to_string(self).ok() == serde_urlencoded::to_string(other).ok()
+                }
+            }
+
+            impl Eq for $request {}
+        )+
+
+        impl_query_hash!($($request),+);
+    };
+}
+
+pub mod balances;
 pub mod blocks;
+#[cfg(feature = "btc")]
 pub mod btc;
+pub mod contracts;
+#[cfg(feature = "curve")]
 pub mod curve;
 pub mod erc20;
+pub mod erc721;
+#[cfg(feature = "fuel")]
 pub mod fuel;
+pub mod internal_txs;
 pub mod logs;
+pub mod pending_txs;
+pub mod receipts;
 pub mod transfers;
 pub mod txs;
+#[cfg(feature = "uniswap")]
 pub mod uniswap_v2;
+#[cfg(feature = "uniswap")]
 pub mod uniswap_v3;
+pub mod withdrawals;
+
+#[cfg(test)]
+mod tests {
+    use super::blocks::GetBlocksRequest;
+    use crate::core::types::ethereum::Block;
+
+    #[test]
+    fn selected_fields_are_serialized() {
+        let request = GetBlocksRequest::default().select(["number", "hash"]);
+
+        let query = request.to_query().unwrap();
+        assert!(query.contains("fields=hash%2Cnumber"), "{query}");
+    }
+
+    #[test]
+    fn projected_row_decodes_with_defaults() {
+        let block: Block = serde_json::from_str(r#"{"number":17000000}"#).unwrap();
+
+        assert_eq!(
+            block,
+            Block {
+                number: 17_000_000,
+                ..Default::default()
+            }
+        );
+    }
+
+    /// Hashes a request with a fixed hasher, so equal requests hash alike
+    fn hash_of<T: std::hash::Hash>(request: &T) -> u64 {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        request.hash(&mut hasher);
+        std::hash::Hasher::finish(&hasher)
+    }
+
+    #[test]
+    fn requests_with_reordered_sets_are_equal_keys() {
+        use std::collections::{HashMap, HashSet};
+
+        use super::logs::GetLogsRequest;
+        use crate::core::types::ChainId;
+
+        // the same chains inserted in opposite orders into sets of different
+        // capacities, which iterate them in different orders
+        let mut forward = HashSet::with_capacity(1);
+        forward.extend(ChainId::all().iter().copied());
+        let mut backward = HashSet::with_capacity(64);
+        backward.extend(ChainId::all().iter().rev().copied());
+        let first = GetLogsRequest {
+            chains: forward,
+            ..Default::default()
+        };
+        let second = GetLogsRequest {
+            chains: backward,
+            ..Default::default()
+        };
+
+        assert_eq!(first, second);
+        assert_eq!(hash_of(&first), hash_of(&second));
+
+        let mut cache = HashMap::new();
+        cache.insert(first, 1);
+        cache.insert(second.clone(), 2);
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache[&second], 2);
+    }
+
+    #[test]
+    fn float_filters_compare_by_their_query() {
+        use super::erc20::GetErc20TransferssRequest;
+
+        let request = |value| GetErc20TransferssRequest {
+            value__gte: value,
+            ..Default::default()
+        };
+
+        assert_eq!(request(Some(1.5)), request(Some(1.5)));
+        assert_eq!(hash_of(&request(Some(1.5))), hash_of(&request(Some(1.5))));
+        assert_ne!(request(Some(1.5)), request(Some(2.5)));
+        assert_ne!(request(Some(1.5)), request(None));
+        // unlike the float itself, a request with a NaN filter equals itself
+        assert_eq!(request(Some(f64::NAN)), request(Some(f64::NAN)));
+    }
+}
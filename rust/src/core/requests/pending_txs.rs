@@ -0,0 +1,63 @@
+use std::collections::HashSet;
+
+use ethers_core::types::{Address, Bytes};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    core::types::{default_chains, ChainId},
+    utils::{deserialize_comma_separated, serialize_comma_separated},
+};
+
+/// A subscription to the transactions in the mempool
+///
+/// Mempool data is inherently live, so there is no block range and no order:
+/// transactions are streamed as the node sees them, which is not necessarily
+/// the order they are mined in. Transactions may be dropped or replaced
+/// without ever being mined, and transactions seen while the connection is
+/// down are not replayed after a reconnect.
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq, Eq)]
+#[allow(non_snake_case)]
+pub struct GetPendingTxsRequest {
+    #[serde(default = "default_chains")]
+    #[serde(
+        serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
+        skip_serializing_if = "HashSet::is_empty"
+    )]
+    pub chains: HashSet<ChainId>,
+
+    #[serde(default)]
+    #[serde(
+        serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
+        skip_serializing_if = "HashSet::is_empty"
+    )]
+    pub from__in: HashSet<Address>,
+    #[serde(default)]
+    #[serde(
+        serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
+        skip_serializing_if = "HashSet::is_empty"
+    )]
+    pub to__in: HashSet<Address>,
+    /// The first four bytes of the input of the transactions
+    #[serde(default)]
+    #[serde(
+        serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
+        skip_serializing_if = "HashSet::is_empty"
+    )]
+    pub method_id__in: HashSet<Bytes>,
+}
+
+impl Default for GetPendingTxsRequest {
+    fn default() -> Self {
+        Self {
+            chains: default_chains(),
+            from__in: HashSet::new(),
+            to__in: HashSet::new(),
+            method_id__in: HashSet::new(),
+        }
+    }
+}
+impl_query_hash!(GetPendingTxsRequest);
@@ -0,0 +1,75 @@
+use std::collections::HashSet;
+
+use ethers_core::types::Address;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    core::types::{default_chains, ChainId},
+    query::{Bound, Order},
+    utils::{deserialize_comma_separated, serialize_comma_separated},
+};
+
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq, Eq)]
+#[allow(non_snake_case)]
+pub struct GetReceiptsRequest {
+    #[serde(default = "default_chains")]
+    #[serde(
+        serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
+        skip_serializing_if = "HashSet::is_empty"
+    )]
+    pub chains: HashSet<ChainId>,
+
+    // Inclusive lower bound if is Some for block number
+    #[serde(default)]
+    pub from_block: Bound,
+    // Exclusive upper bound if is Some for block number
+    #[serde(default)]
+    pub to_block: Bound,
+
+    #[serde(default)]
+    #[serde(
+        serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
+        skip_serializing_if = "HashSet::is_empty"
+    )]
+    pub address__in: HashSet<Address>,
+
+    // 1 for successful and 0 for failed transactions
+    #[serde(default)]
+    pub status__eq: Option<u64>,
+
+    /// The columns to return, all if empty. Requires server support for
+    /// projections. Columns that were not selected are left at their default
+    /// when decoding into the typed rows.
+    #[serde(default)]
+    #[serde(
+        serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
+        skip_serializing_if = "HashSet::is_empty"
+    )]
+    pub fields: HashSet<String>,
+
+    /// The order of the results, ascending if `None`. Descending order is
+    /// only valid for bounded ranges.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order: Option<Order>,
+}
+
+impl Default for GetReceiptsRequest {
+    fn default() -> Self {
+        Self {
+            chains: default_chains(),
+            from_block: Bound::default(),
+            to_block: Bound::default(),
+            address__in: HashSet::new(),
+            status__eq: None,
+            fields: HashSet::new(),
+            order: None,
+        }
+    }
+}
+
+impl_block_range!(GetReceiptsRequest);
+impl_projection!(GetReceiptsRequest);
+impl_query_hash!(GetReceiptsRequest);
@@ -5,16 +5,17 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     core::types::{default_chains, ChainId},
-    query::Bound,
-    utils::serialize_comma_separated,
+    query::{Bound, Order},
+    utils::{deserialize_comma_separated, serialize_comma_separated},
 };
 
-#[derive(Clone, Deserialize, Serialize, Debug)]
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq, Eq)]
 #[allow(non_snake_case)]
 pub struct GetTransfersRequest {
     #[serde(default = "default_chains")]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub chains: HashSet<ChainId>,
@@ -29,6 +30,7 @@ pub struct GetTransfersRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub address__in: HashSet<Address>,
@@ -36,6 +38,7 @@ pub struct GetTransfersRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub to__in: HashSet<Address>,
@@ -43,6 +46,7 @@ pub struct GetTransfersRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub from__in: HashSet<Address>,
@@ -52,6 +56,27 @@ pub struct GetTransfersRequest {
 
     #[serde(default)]
     pub value__gte: Option<U256>,
+
+    /// Only return the number of matching rows instead of the rows
+    /// themselves. Requires server support for the `count_only` flag.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub count_only: bool,
+
+    /// The columns to return, all if empty. Requires server support for
+    /// projections. Columns that were not selected are left at their default
+    /// when decoding into the typed rows.
+    #[serde(default)]
+    #[serde(
+        serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
+        skip_serializing_if = "HashSet::is_empty"
+    )]
+    pub fields: HashSet<String>,
+
+    /// The order of the results, ascending if `None`. Descending order is
+    /// only valid for bounded ranges.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order: Option<Order>,
 }
 
 impl Default for GetTransfersRequest {
@@ -65,6 +90,13 @@ impl Default for GetTransfersRequest {
             from__in: HashSet::default(),
             value__lte: None,
             value__gte: None,
+            count_only: false,
+            fields: HashSet::new(),
+            order: None,
         }
     }
 }
+
+impl_block_range!(GetTransfersRequest);
+impl_projection!(GetTransfersRequest);
+impl_query_hash!(GetTransfersRequest);
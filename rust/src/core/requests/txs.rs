@@ -6,16 +6,17 @@ use ethers_core::types::{Address, U256};
 
 use crate::{
     core::types::{default_chains, ChainId},
-    query::Bound,
-    utils::serialize_comma_separated,
+    query::{Bound, Order},
+    utils::{deserialize_comma_separated, serialize_comma_separated},
 };
 
-#[derive(Clone, Deserialize, Serialize, Debug)]
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq, Eq)]
 #[allow(non_snake_case)]
 pub struct GetTxsRequest {
     #[serde(default = "default_chains")]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub chains: HashSet<ChainId>,
@@ -30,12 +31,14 @@ pub struct GetTxsRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub from__in: HashSet<Address>,
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub to__in: HashSet<Address>,
@@ -64,6 +67,27 @@ pub struct GetTxsRequest {
     pub max_priority_fee_per_gas__gte: Option<U256>,
     #[serde(default)]
     pub max_priority_fee_per_gas__lte: Option<U256>,
+
+    /// Only return the number of matching rows instead of the rows
+    /// themselves. Requires server support for the `count_only` flag.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub count_only: bool,
+
+    /// The columns to return, all if empty. Requires server support for
+    /// projections. Columns that were not selected are left at their default
+    /// when decoding into the typed rows.
+    #[serde(default)]
+    #[serde(
+        serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
+        skip_serializing_if = "HashSet::is_empty"
+    )]
+    pub fields: HashSet<String>,
+
+    /// The order of the results, ascending if `None`. Descending order is
+    /// only valid for bounded ranges.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order: Option<Order>,
 }
 
 impl Default for GetTxsRequest {
@@ -84,6 +108,13 @@ impl Default for GetTxsRequest {
             max_fee_per_gas__lte: None,
             max_priority_fee_per_gas__gte: None,
             max_priority_fee_per_gas__lte: None,
+            count_only: false,
+            fields: HashSet::new(),
+            order: None,
         }
     }
 }
+
+impl_block_range!(GetTxsRequest);
+impl_projection!(GetTxsRequest);
+impl_query_hash!(GetTxsRequest);
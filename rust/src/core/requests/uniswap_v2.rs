@@ -5,17 +5,26 @@ use serde::{Deserialize, Serialize};
 use ethers_core::types::{Address, U128};
 
 use crate::{
-    core::types::{default_chains, uniswap_v2::ReserveEvent, ChainId},
-    query::Bound,
-    utils::serialize_comma_separated,
+    core::{
+        error::{Error, Result},
+        types::{
+            default_chains,
+            dex::Dex,
+            uniswap_v2::{Pair, ReserveEvent},
+            ChainId,
+        },
+    },
+    query::{Bound, Order},
+    utils::{deserialize_comma_separated, serialize_comma_separated},
 };
 
-#[derive(Clone, Deserialize, Serialize, Debug)]
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq, Eq)]
 #[allow(non_snake_case)]
 pub struct GetPairsRequest {
     #[serde(default = "default_chains")]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub chains: HashSet<ChainId>,
@@ -30,6 +39,7 @@ pub struct GetPairsRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub pair_address__in: HashSet<Address>,
@@ -37,6 +47,7 @@ pub struct GetPairsRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub factory_address__in: HashSet<Address>,
@@ -44,6 +55,7 @@ pub struct GetPairsRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub token0__in: HashSet<Address>,
@@ -51,6 +63,7 @@ pub struct GetPairsRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub token1__in: HashSet<Address>,
@@ -58,9 +71,15 @@ pub struct GetPairsRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub tokens__in: HashSet<Address>,
+
+    /// The order of the results, ascending if `None`. Descending order is
+    /// only valid for bounded ranges.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order: Option<Order>,
 }
 
 impl Default for GetPairsRequest {
@@ -74,16 +93,45 @@ impl Default for GetPairsRequest {
             token0__in: HashSet::new(),
             token1__in: HashSet::new(),
             tokens__in: HashSet::new(),
+            order: None,
         }
     }
 }
 
+impl GetPairsRequest {
+    /// Restricts the request to the pairs of the given exchange on the
+    /// requested chains
+    ///
+    /// Chains the exchange is not deployed on are skipped. Fails if the
+    /// exchange is not a Uniswap V2 style exchange or has no known factory on
+    /// any of the requested chains, as an empty filter would match all
+    /// pairs instead.
+    pub fn dex(mut self, dex: Dex) -> Result<Self> {
+        if dex.is_v3() {
+            return Err(Error::DexProtocolMismatch(dex));
+        }
+
+        let factories: Vec<_> = self
+            .chains
+            .iter()
+            .filter_map(|chain| dex.factory(*chain))
+            .collect();
+        if factories.is_empty() {
+            return Err(Error::UnknownFactory(dex));
+        }
+
+        self.factory_address__in.extend(factories);
+        Ok(self)
+    }
+}
+
 #[derive(Clone, Deserialize, Serialize, Debug)]
 #[allow(non_snake_case)]
 pub struct GetPricesRequest {
     #[serde(default = "default_chains")]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub chains: HashSet<ChainId>,
@@ -98,6 +146,7 @@ pub struct GetPricesRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub pair_address__in: HashSet<Address>,
@@ -105,6 +154,7 @@ pub struct GetPricesRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub pair_factory_address__in: HashSet<Address>,
@@ -112,6 +162,7 @@ pub struct GetPricesRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub event__in: HashSet<ReserveEvent>,
@@ -137,6 +188,7 @@ pub struct GetPricesRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub sender__in: HashSet<Address>,
@@ -144,6 +196,7 @@ pub struct GetPricesRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub receiver__in: HashSet<Address>,
@@ -175,6 +228,7 @@ pub struct GetPricesRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub token0_address__in: HashSet<Address>,
@@ -182,6 +236,7 @@ pub struct GetPricesRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub token0_symbol__in: HashSet<String>,
@@ -189,6 +244,7 @@ pub struct GetPricesRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub token1_address__in: HashSet<Address>,
@@ -196,6 +252,7 @@ pub struct GetPricesRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub token1_symbol__in: HashSet<String>,
@@ -203,6 +260,7 @@ pub struct GetPricesRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub tokens_address__in: HashSet<Address>,
@@ -210,9 +268,26 @@ pub struct GetPricesRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub tokens_symbol__in: HashSet<String>,
+
+    /// The columns to return, all if empty. Requires server support for
+    /// projections. Columns that were not selected are left at their default
+    /// when decoding into the typed rows.
+    #[serde(default)]
+    #[serde(
+        serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
+        skip_serializing_if = "HashSet::is_empty"
+    )]
+    pub fields: HashSet<String>,
+
+    /// The order of the results, ascending if `None`. Descending order is
+    /// only valid for bounded ranges.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order: Option<Order>,
 }
 
 impl Default for GetPricesRequest {
@@ -246,6 +321,133 @@ impl Default for GetPricesRequest {
             token1_symbol__in: HashSet::new(),
             tokens_address__in: HashSet::new(),
             tokens_symbol__in: HashSet::new(),
+            fields: HashSet::new(),
+            order: None,
+        }
+    }
+}
+
+impl GetPricesRequest {
+    /// Creates a request for the prices of the given pairs
+    ///
+    /// All other filters are left at their defaults, e.g. the default chains.
+    /// Without any pairs the request is not filtered by pair and matches the
+    /// prices of all pairs.
+    pub fn for_pairs(pairs: impl IntoIterator<Item = Address>) -> Self {
+        Self {
+            pair_address__in: pairs.into_iter().collect(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Requests the prices of a pair on its chain
+impl From<&Pair> for GetPricesRequest {
+    fn from(pair: &Pair) -> Self {
+        Self {
+            chains: HashSet::from([pair.chain]),
+            pair_address__in: HashSet::from([pair.pair_address]),
+            ..Default::default()
+        }
+    }
+}
+
+/// Requests the prices of several pairs on their chains
+///
+/// Like [`GetPricesRequest::for_pairs`], an empty iterator yields a request
+/// for the prices of all pairs.
+impl<'a> FromIterator<&'a Pair> for GetPricesRequest {
+    fn from_iter<I: IntoIterator<Item = &'a Pair>>(iter: I) -> Self {
+        let mut request = Self {
+            chains: HashSet::new(),
+            ..Default::default()
+        };
+        for pair in iter {
+            request.chains.insert(pair.chain);
+            request.pair_address__in.insert(pair.pair_address);
+        }
+        request
+    }
+}
+
+/// Requests the prices of the pairs matching the filters of a pairs
+/// request
+///
+/// Only the pair, factory and token filters carry over, the block range
+/// of the pairs request refers to the creation of the pairs and is not
+/// applied to the prices.
+impl From<&GetPairsRequest> for GetPricesRequest {
+    fn from(request: &GetPairsRequest) -> Self {
+        Self {
+            chains: request.chains.clone(),
+            pair_address__in: request.pair_address__in.clone(),
+            pair_factory_address__in: request.factory_address__in.clone(),
+            token0_address__in: request.token0__in.clone(),
+            token1_address__in: request.token1__in.clone(),
+            tokens_address__in: request.tokens__in.clone(),
+            ..Default::default()
         }
     }
 }
+
+impl_block_range!(GetPairsRequest, GetPricesRequest);
+impl_projection!(GetPricesRequest);
+impl_query_hash!(GetPairsRequest);
+impl_query_eq!(GetPricesRequest);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uniswap_v2_on_eth_filters_by_its_factory() {
+        let request = GetPairsRequest {
+            chains: HashSet::from([ChainId::ETH]),
+            ..Default::default()
+        }
+        .dex(Dex::UniswapV2)
+        .unwrap();
+
+        let factory: Address = "0x5C69bEe701ef814a2B6a3EDD4B1652CB9cc5aA6f"
+            .parse()
+            .unwrap();
+        assert_eq!(request.factory_address__in, HashSet::from([factory]));
+    }
+
+    #[test]
+    fn pairs_convert_to_a_prices_request_for_them() {
+        let pairs = [
+            Pair {
+                chain: ChainId::ETH,
+                pair_address: Address::repeat_byte(1),
+                ..Default::default()
+            },
+            Pair {
+                chain: ChainId::ARB,
+                pair_address: Address::repeat_byte(2),
+                ..Default::default()
+            },
+        ];
+
+        let request: GetPricesRequest = pairs.iter().collect();
+        assert_eq!(request.chains, HashSet::from([ChainId::ETH, ChainId::ARB]));
+        assert_eq!(
+            request.pair_address__in,
+            HashSet::from([Address::repeat_byte(1), Address::repeat_byte(2)])
+        );
+
+        let request = GetPricesRequest::from(&pairs[1]);
+        assert_eq!(request.chains, HashSet::from([ChainId::ARB]));
+        assert_eq!(
+            request.pair_address__in,
+            HashSet::from([Address::repeat_byte(2)])
+        );
+
+        let request = GetPricesRequest::for_pairs([Address::repeat_byte(3)]);
+        assert_eq!(
+            request.pair_address__in,
+            HashSet::from([Address::repeat_byte(3)])
+        );
+        assert_eq!(request.chains, default_chains());
+    }
+}
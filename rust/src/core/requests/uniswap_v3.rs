@@ -1,12 +1,15 @@
-use std::collections::HashSet;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 
 use ethers_core::types::Address;
 
 use crate::{
-    core::types::{default_chains, ChainId},
-    query::Bound,
-    utils::serialize_comma_separated,
+    core::{
+        error::{Error, Result},
+        types::{default_chains, dex::Dex, uniswap_v3::Pool, ChainId},
+    },
+    query::{Bound, Order},
+    utils::{deserialize_comma_separated, serialize_comma_separated},
 };
 
 #[derive(Clone, Deserialize, Serialize, Debug)]
@@ -15,6 +18,7 @@ pub struct GetPoolsRequest {
     #[serde(default = "default_chains")]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub chains: HashSet<ChainId>,
@@ -29,6 +33,7 @@ pub struct GetPoolsRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub pool_address__in: HashSet<Address>,
@@ -36,6 +41,7 @@ pub struct GetPoolsRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub factory_address__in: HashSet<Address>,
@@ -43,6 +49,7 @@ pub struct GetPoolsRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub token0__in: HashSet<Address>,
@@ -50,6 +57,7 @@ pub struct GetPoolsRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub token1__in: HashSet<Address>,
@@ -57,6 +65,7 @@ pub struct GetPoolsRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub tokens__in: HashSet<Address>,
@@ -80,6 +89,11 @@ pub struct GetPoolsRequest {
     pub tick_spacing__gte: Option<i32>,
     #[serde(default)]
     pub tick_spacing__lte: Option<i32>,
+
+    /// The order of the results, ascending if `None`. Descending order is
+    /// only valid for bounded ranges.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order: Option<Order>,
 }
 
 impl Default for GetPoolsRequest {
@@ -101,7 +115,35 @@ impl Default for GetPoolsRequest {
             price__lte: None,
             tick_spacing__gte: None,
             tick_spacing__lte: None,
+            order: None,
+        }
+    }
+}
+
+impl GetPoolsRequest {
+    /// Restricts the request to the pools of the given exchange on the
+    /// requested chains
+    ///
+    /// Chains the exchange is not deployed on are skipped. Fails if the
+    /// exchange is not a Uniswap V3 style exchange or has no known factory on
+    /// any of the requested chains, as an empty filter would match all
+    /// pools instead.
+    pub fn dex(mut self, dex: Dex) -> Result<Self> {
+        if !dex.is_v3() {
+            return Err(Error::DexProtocolMismatch(dex));
         }
+
+        let factories: Vec<_> = self
+            .chains
+            .iter()
+            .filter_map(|chain| dex.factory(*chain))
+            .collect();
+        if factories.is_empty() {
+            return Err(Error::UnknownFactory(dex));
+        }
+
+        self.factory_address__in.extend(factories);
+        Ok(self)
     }
 }
 
@@ -111,6 +153,7 @@ pub struct GetPricesRequest {
     #[serde(default = "default_chains")]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub chains: HashSet<ChainId>,
@@ -125,6 +168,7 @@ pub struct GetPricesRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub pool_address__in: HashSet<Address>,
@@ -132,6 +176,7 @@ pub struct GetPricesRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub pool_factory_address__in: HashSet<Address>,
@@ -157,6 +202,7 @@ pub struct GetPricesRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub sender__in: HashSet<Address>,
@@ -164,6 +210,7 @@ pub struct GetPricesRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub receiver__in: HashSet<Address>,
@@ -195,6 +242,7 @@ pub struct GetPricesRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub token0_address__in: HashSet<Address>,
@@ -202,6 +250,7 @@ pub struct GetPricesRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub token0_symbol__in: HashSet<String>,
@@ -209,6 +258,7 @@ pub struct GetPricesRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub token1_address__in: HashSet<Address>,
@@ -216,6 +266,7 @@ pub struct GetPricesRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub token1_symbol__in: HashSet<String>,
@@ -223,6 +274,7 @@ pub struct GetPricesRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub tokens_address__in: HashSet<Address>,
@@ -230,9 +282,26 @@ pub struct GetPricesRequest {
     #[serde(default)]
     #[serde(
         serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
         skip_serializing_if = "HashSet::is_empty"
     )]
     pub tokens_symbol__in: HashSet<String>,
+
+    /// The columns to return, all if empty. Requires server support for
+    /// projections. Columns that were not selected are left at their default
+    /// when decoding into the typed rows.
+    #[serde(default)]
+    #[serde(
+        serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated",
+        skip_serializing_if = "HashSet::is_empty"
+    )]
+    pub fields: HashSet<String>,
+
+    /// The order of the results, ascending if `None`. Descending order is
+    /// only valid for bounded ranges.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order: Option<Order>,
 }
 
 impl Default for GetPricesRequest {
@@ -265,6 +334,111 @@ impl Default for GetPricesRequest {
             liquidity__lte: None,
             tick__gte: None,
             tick__lte: None,
+            fields: HashSet::new(),
+            order: None,
         }
     }
 }
+
+impl GetPricesRequest {
+    /// Creates a request for the prices of the given pools
+    ///
+    /// All other filters are left at their defaults, e.g. the default chains.
+    /// Without any pools the request is not filtered by pool and matches the
+    /// prices of all pools.
+    pub fn for_pools(pools: impl IntoIterator<Item = Address>) -> Self {
+        Self {
+            pool_address__in: pools.into_iter().collect(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Requests the prices of a pool on its chain
+impl From<&Pool> for GetPricesRequest {
+    fn from(pool: &Pool) -> Self {
+        Self {
+            chains: HashSet::from([pool.chain]),
+            pool_address__in: HashSet::from([pool.pool_address]),
+            ..Default::default()
+        }
+    }
+}
+
+/// Requests the prices of several pools on their chains
+///
+/// Like [`GetPricesRequest::for_pools`], an empty iterator yields a request
+/// for the prices of all pools.
+impl<'a> FromIterator<&'a Pool> for GetPricesRequest {
+    fn from_iter<I: IntoIterator<Item = &'a Pool>>(iter: I) -> Self {
+        let mut request = Self {
+            chains: HashSet::new(),
+            ..Default::default()
+        };
+        for pool in iter {
+            request.chains.insert(pool.chain);
+            request.pool_address__in.insert(pool.pool_address);
+        }
+        request
+    }
+}
+
+/// Requests the prices of the pools matching the filters of a pools
+/// request
+///
+/// Only the pool, factory and token filters carry over, the block range
+/// of the pools request refers to the creation of the pools and is not
+/// applied to the prices.
+impl From<&GetPoolsRequest> for GetPricesRequest {
+    fn from(request: &GetPoolsRequest) -> Self {
+        Self {
+            chains: request.chains.clone(),
+            pool_address__in: request.pool_address__in.clone(),
+            pool_factory_address__in: request.factory_address__in.clone(),
+            token0_address__in: request.token0__in.clone(),
+            token1_address__in: request.token1__in.clone(),
+            tokens_address__in: request.tokens__in.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+impl_block_range!(GetPoolsRequest, GetPricesRequest);
+impl_projection!(GetPricesRequest);
+impl_query_eq!(GetPoolsRequest, GetPricesRequest);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pools_convert_to_a_prices_request_for_them() {
+        let pools = [
+            Pool {
+                chain: ChainId::ETH,
+                pool_address: Address::repeat_byte(1),
+                ..Default::default()
+            },
+            Pool {
+                chain: ChainId::ETH,
+                pool_address: Address::repeat_byte(2),
+                ..Default::default()
+            },
+        ];
+
+        let request: GetPricesRequest = pools.iter().collect();
+        assert_eq!(request.chains, HashSet::from([ChainId::ETH]));
+        assert_eq!(
+            request.pool_address__in,
+            HashSet::from([Address::repeat_byte(1), Address::repeat_byte(2)])
+        );
+
+        let factory = Address::repeat_byte(3);
+        let request = GetPricesRequest::from(&GetPoolsRequest {
+            factory_address__in: HashSet::from([factory]),
+            ..Default::default()
+        });
+        assert_eq!(request.pool_factory_address__in, HashSet::from([factory]));
+        assert!(request.pool_address__in.is_empty());
+    }
+}
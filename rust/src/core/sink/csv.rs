@@ -0,0 +1,95 @@
+use std::{fs::OpenOptions, path::Path};
+
+use futures::{io::AllowStdIo, Stream, StreamExt};
+use serde::Serialize;
+
+use crate::core::error::Result;
+
+/// Writes the rows of a stream to a CSV file, returning the number of rows
+/// written
+///
+/// The file is created if it does not exist. With `append`, rows are added
+/// to the end of an existing file and the header row is only written if the
+/// file is empty, so an indexer can resume into the same file after a
+/// restart. Without `append`, an existing file is truncated first.
+///
+/// The header is derived from the first row, so nothing is written for an
+/// empty stream. Rows must serialize to flat records, nested fields such as
+/// lists are not supported by CSV. Writes go to the file directly and block
+/// the current thread for their duration, they are buffered to keep that
+/// short.
+///
+/// Stops at the first error of the stream, rows received before it are
+/// flushed to the file.
+pub async fn write_csv<T, S>(stream: S, path: impl AsRef<Path>, append: bool) -> Result<u64>
+where
+    T: Serialize,
+    S: Stream<Item = Result<T>>,
+{
+    let file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(append)
+        .truncate(!append)
+        .open(path)?;
+    let has_rows = file.metadata()?.len() > 0;
+
+    let mut serializer = csv_async::AsyncWriterBuilder::new()
+        .has_headers(!has_rows)
+        .create_serializer(AllowStdIo::new(file));
+
+    let mut stream = std::pin::pin!(stream);
+    let mut written = 0;
+    let result = loop {
+        match stream.next().await {
+            Some(Ok(row)) => {
+                if let Err(err) = serializer.serialize(row).await {
+                    break Err(err.into());
+                }
+                written += 1;
+            }
+            Some(Err(err)) => break Err(err),
+            None => break Ok(written),
+        }
+    };
+    serializer.flush().await?;
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Row {
+        number: u64,
+        hash: &'static str,
+    }
+
+    fn rows(numbers: std::ops::Range<u64>) -> impl Stream<Item = Result<Row>> {
+        futures::stream::iter(numbers.map(|number| {
+            Ok(Row {
+                number,
+                hash: "0xaa",
+            })
+        }))
+    }
+
+    #[tokio::test]
+    async fn appended_batches_share_one_header() {
+        let path = std::env::temp_dir().join(format!("superchain-csv-{}.csv", std::process::id()));
+
+        assert_eq!(write_csv(rows(1..3), &path, true).await.unwrap(), 2);
+        assert_eq!(write_csv(rows(3..4), &path, true).await.unwrap(), 1);
+        let appended = std::fs::read_to_string(&path).unwrap();
+
+        // without append the file starts over
+        assert_eq!(write_csv(rows(5..6), &path, false).await.unwrap(), 1);
+        let truncated = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(appended, "number,hash\n1,0xaa\n2,0xaa\n3,0xaa\n");
+        assert_eq!(truncated, "number,hash\n5,0xaa\n");
+    }
+}
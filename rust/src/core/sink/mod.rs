@@ -0,0 +1,2 @@
+//! Sinks persisting response streams
+pub mod csv;
@@ -0,0 +1,47 @@
+use ethers_core::types::Address;
+use strum::AsRefStr;
+
+use super::ChainId;
+
+/// Well-known decentralized exchanges, identified by their factory contracts
+#[derive(AsRefStr, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Dex {
+    UniswapV2,
+    UniswapV3,
+    SushiSwap,
+    PancakeSwapV2,
+    PancakeSwapV3,
+}
+
+impl Dex {
+    /// Returns the factory address of the exchange on the given chain, if it
+    /// is deployed there
+    pub fn factory(&self, chain: ChainId) -> Option<Address> {
+        let address = match (self, chain) {
+            (Self::UniswapV2, ChainId::ETH) => "0x5C69bEe701ef814a2B6a3EDD4B1652CB9cc5aA6f",
+            (Self::UniswapV3, ChainId::ETH | ChainId::OPT | ChainId::ARB | ChainId::MATIC) => {
+                "0x1F98431c8aD98523631AE4a59f267346ea31F984"
+            }
+            (Self::UniswapV3, ChainId::BNB) => "0xdB1d10011AD0Ff90774D0C6Bb92e5C5c8b4461F7",
+            (Self::UniswapV3, ChainId::AVAX) => "0x740b1c1de25031C31FF4fC9A62f554A55cdC1baD",
+            (Self::SushiSwap, ChainId::ETH) => "0xC0AEe478e3658e2610c5F7A4A2E1777cE9e4f2Ac",
+            (Self::SushiSwap, ChainId::BNB | ChainId::MATIC | ChainId::ARB | ChainId::AVAX) => {
+                "0xc35DADB65012eC5796536bD9864eD8773aBc74C4"
+            }
+            (Self::PancakeSwapV2, ChainId::BNB) => "0xcA143Ce32Fe78f1f7019d7d551a6402fC5350c73",
+            (Self::PancakeSwapV2, ChainId::ETH) => "0x1097053Fd2ea711dad45caCcc45EfF7548fCB362",
+            (Self::PancakeSwapV3, ChainId::BNB | ChainId::ETH | ChainId::ARB) => {
+                "0x0BFbCF9fa4f9C56B0F40a671Ad40E0805A091865"
+            }
+            _ => return None,
+        };
+
+        Some(address.parse().expect("factory addresses are valid"))
+    }
+
+    /// Returns true if the exchange uses Uniswap V3 style concentrated
+    /// liquidity pools, false for Uniswap V2 style pairs
+    pub fn is_v3(&self) -> bool {
+        matches!(self, Self::UniswapV3 | Self::PancakeSwapV3)
+    }
+}
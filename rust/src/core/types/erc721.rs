@@ -0,0 +1,21 @@
+use ethers_core::types::{Address, H256, U256};
+use serde::{Deserialize, Serialize};
+
+use super::ChainId;
+
+/// A transfer of a non-fungible ERC-721 token
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Erc721Transfer {
+    pub chain: ChainId,
+    pub block_number: u64,
+    pub transaction_hash: H256,
+    pub log_index: u64,
+    /// The address of the token contract
+    pub contract: Address,
+    pub token_id: U256,
+    /// The zero address for mints
+    pub from: Address,
+    /// The zero address for burns
+    pub to: Address,
+}
@@ -0,0 +1,310 @@
+use ethers_core::types::{Address, Bloom, Bytes, H256, U256};
+use serde::{Deserialize, Serialize};
+use strum::AsRefStr;
+
+use super::ChainId;
+
+/// A block header of an EVM chain
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Block {
+    pub chain: ChainId,
+    pub number: u64,
+    pub hash: H256,
+    pub parent_hash: H256,
+    /// Unix timestamp of the block in seconds
+    pub timestamp: i64,
+    pub miner: Address,
+    pub gas_used: U256,
+    pub gas_limit: U256,
+    /// `None` for blocks before the London fork, which introduced EIP-1559
+    pub base_fee_per_gas: Option<U256>,
+    pub transaction_count: u64,
+    /// Size of the block in bytes
+    pub size: u64,
+    /// `None` for blocks before the Shanghai fork
+    pub withdrawals_root: Option<H256>,
+    /// The validator withdrawals processed in the block, `None` for blocks
+    /// before the Shanghai fork
+    pub withdrawals: Option<Vec<Withdrawal>>,
+    /// `None` for blocks before the Cancun fork, which introduced blobs
+    pub blob_gas_used: Option<U256>,
+    /// `None` for blocks before the Cancun fork
+    pub excess_blob_gas: Option<U256>,
+}
+
+/// A withdrawal from the beacon chain to the execution layer
+///
+/// Returned both as part of a [`Block`] and as the rows of the withdrawals
+/// dataset.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Withdrawal {
+    /// The block the withdrawal was processed in, only set for rows of the
+    /// withdrawals dataset
+    pub block_number: u64,
+    pub index: u64,
+    pub validator_index: u64,
+    pub address: Address,
+    /// The withdrawn amount in Gwei
+    pub amount: U256,
+}
+
+/// A transaction receipt of an EVM chain
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Receipt {
+    pub chain: ChainId,
+    pub block_number: u64,
+    pub block_hash: H256,
+    pub transaction_hash: H256,
+    pub transaction_index: u64,
+    pub from: Address,
+    /// `None` for contract creations
+    pub to: Option<Address>,
+    /// The address of the created contract, if the transaction created one
+    pub contract_address: Option<Address>,
+    /// 1 for successful and 0 for failed transactions
+    pub status: u64,
+    pub gas_used: U256,
+    pub cumulative_gas_used: U256,
+    pub effective_gas_price: U256,
+    pub logs_bloom: Bloom,
+}
+
+/// The kind of call of an internal transaction
+#[derive(AsRefStr, Clone, Copy, Debug, Default, Hash, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum CallType {
+    #[default]
+    Call,
+    CallCode,
+    DelegateCall,
+    StaticCall,
+    Create,
+    Create2,
+    SelfDestruct,
+}
+
+/// An internal transaction, i.e. a call made by a contract while executing a
+/// transaction
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct InternalTx {
+    pub chain: ChainId,
+    pub block_number: u64,
+    /// The hash of the top-level transaction the call was made in
+    pub parent_tx_hash: H256,
+    pub from: Address,
+    pub to: Address,
+    pub value: U256,
+    pub call_type: CallType,
+    /// The position of the call in the call tree of the transaction, e.g.
+    /// `[0, 2]` is the third call made by the first call of the transaction
+    pub trace_address: Vec<u32>,
+}
+
+/// A deployment of a contract
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ContractCreation {
+    pub chain: ChainId,
+    pub block_number: u64,
+    pub timestamp: u64,
+    /// The address of the created contract
+    pub address: Address,
+    /// The account or contract that deployed the contract
+    pub creator: Address,
+    pub creation_tx_hash: H256,
+    /// The keccak256 hash of the init code
+    pub init_code_hash: H256,
+}
+
+/// A transaction in the mempool that was not mined yet
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PendingTx {
+    pub chain: ChainId,
+    pub hash: H256,
+    pub from: Address,
+    /// `None` for contract creations
+    pub to: Option<Address>,
+    pub nonce: u64,
+    pub value: U256,
+    pub gas: U256,
+    /// Only set for legacy transactions
+    pub gas_price: Option<U256>,
+    pub max_fee_per_gas: Option<U256>,
+    pub max_priority_fee_per_gas: Option<U256>,
+    pub input: Bytes,
+}
+
+/// The balance of an account at a block
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Balance {
+    pub chain: ChainId,
+    pub block_number: u64,
+    pub address: Address,
+    /// The ERC-20 token of the balance, `None` for the native balance
+    pub token: Option<Address>,
+    /// The balance in the smallest unit of the token, e.g. wei
+    pub amount: U256,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_a_nested_delegatecall() {
+        let row = r#"{
+            "chain": 1,
+            "block_number": 17000000,
+            "parent_tx_hash": "0x0202020202020202020202020202020202020202020202020202020202020202",
+            "from": "0x0101010101010101010101010101010101010101",
+            "to": "0x0303030303030303030303030303030303030303",
+            "value": "0x0",
+            "call_type": "DELEGATECALL",
+            "trace_address": [0, 2, 1]
+        }"#;
+
+        let internal_tx: InternalTx = serde_json::from_str(row).unwrap();
+
+        assert_eq!(
+            internal_tx,
+            InternalTx {
+                chain: ChainId::ETH,
+                block_number: 17_000_000,
+                parent_tx_hash: H256::repeat_byte(2),
+                from: Address::repeat_byte(1),
+                to: Address::repeat_byte(3),
+                value: U256::zero(),
+                call_type: CallType::DelegateCall,
+                trace_address: vec![0, 2, 1],
+            }
+        );
+    }
+
+    #[test]
+    fn decodes_a_contract_creation() {
+        let row = r#"{
+            "chain": 1,
+            "block_number": 17000000,
+            "timestamp": 1681338455,
+            "address": "0x0303030303030303030303030303030303030303",
+            "creator": "0x0101010101010101010101010101010101010101",
+            "creation_tx_hash": "0x0202020202020202020202020202020202020202020202020202020202020202",
+            "init_code_hash": "0x0404040404040404040404040404040404040404040404040404040404040404"
+        }"#;
+
+        let creation: ContractCreation = serde_json::from_str(row).unwrap();
+
+        assert_eq!(
+            creation,
+            ContractCreation {
+                chain: ChainId::ETH,
+                block_number: 17_000_000,
+                timestamp: 1_681_338_455,
+                address: Address::repeat_byte(3),
+                creator: Address::repeat_byte(1),
+                creation_tx_hash: H256::repeat_byte(2),
+                init_code_hash: H256::repeat_byte(4),
+            }
+        );
+    }
+
+    #[test]
+    fn decodes_blocks_before_and_after_london() {
+        let pre_london = r#"{
+            "chain": 1,
+            "number": 12000000,
+            "hash": "0x0101010101010101010101010101010101010101010101010101010101010101",
+            "parent_hash": "0x0202020202020202020202020202020202020202020202020202020202020202",
+            "timestamp": 1615234816,
+            "miner": "0x0303030303030303030303030303030303030303",
+            "gas_used": "0xe4e1c0",
+            "gas_limit": "0xe4e1c0",
+            "transaction_count": 233,
+            "size": 52000
+        }"#;
+        let post_london = r#"{
+            "chain": 1,
+            "number": 13000000,
+            "gas_used": "0x1c9c380",
+            "gas_limit": "0x1c9c380",
+            "base_fee_per_gas": "0x12a05f200"
+        }"#;
+
+        let block: Block = serde_json::from_str(pre_london).unwrap();
+        assert_eq!(
+            block,
+            Block {
+                chain: ChainId::ETH,
+                number: 12_000_000,
+                hash: H256::repeat_byte(1),
+                parent_hash: H256::repeat_byte(2),
+                timestamp: 1_615_234_816,
+                miner: Address::repeat_byte(3),
+                gas_used: 15_000_000.into(),
+                gas_limit: 15_000_000.into(),
+                base_fee_per_gas: None,
+                transaction_count: 233,
+                size: 52_000,
+                ..Default::default()
+            }
+        );
+
+        let block: Block = serde_json::from_str(post_london).unwrap();
+        assert_eq!(block.number, 13_000_000);
+        assert_eq!(block.gas_limit, 30_000_000.into());
+        assert_eq!(block.base_fee_per_gas, Some(5_000_000_000u64.into()));
+    }
+
+    #[test]
+    fn decodes_a_cancun_block() {
+        let row = r#"{
+            "chain": 1,
+            "number": 19500000,
+            "base_fee_per_gas": "0x3b9aca00",
+            "withdrawals_root": "0x0505050505050505050505050505050505050505050505050505050505050505",
+            "withdrawals": [
+                {
+                    "index": 40000000,
+                    "validator_index": 1000,
+                    "address": "0x0606060606060606060606060606060606060606",
+                    "amount": "0x11e1a300"
+                }
+            ],
+            "blob_gas_used": "0x60000",
+            "excess_blob_gas": "0x4b00000"
+        }"#;
+
+        let block: Block = serde_json::from_str(row).unwrap();
+
+        assert_eq!(block.withdrawals_root, Some(H256::repeat_byte(5)));
+        assert_eq!(
+            block.withdrawals,
+            Some(vec![Withdrawal {
+                block_number: 0,
+                index: 40_000_000,
+                validator_index: 1000,
+                address: Address::repeat_byte(6),
+                amount: 300_000_000.into(),
+            }])
+        );
+        assert_eq!(block.blob_gas_used, Some(0x60000.into()));
+        assert_eq!(block.excess_blob_gas, Some(0x4b00000.into()));
+
+        // blocks before the forks have none of the fields
+        let block: Block = serde_json::from_str(r#"{"number": 12000000}"#).unwrap();
+        assert_eq!(
+            (
+                block.withdrawals_root,
+                block.withdrawals,
+                block.blob_gas_used
+            ),
+            (None, None, None)
+        );
+    }
+}
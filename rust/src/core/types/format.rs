@@ -1,3 +1,7 @@
+#[cfg(not(feature = "zstd"))]
+use crate::core::error::Error;
+use crate::core::error::Result;
+
 #[derive(Clone, Copy, Debug, Default, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum Format {
@@ -11,4 +15,24 @@ pub enum Format {
     Arrow,
     /// Arrow IPC Stream format
     ArrowStream,
+    /// Json Lines compressed with zstd
+    ///
+    /// The providers decompress the payloads, so the returned stream carries
+    /// plain Json Lines. Requires the `zstd` feature, without it requests in
+    /// this format fail.
+    ZstdJsonStream,
+}
+
+impl Format {
+    /// Fails for formats this build of the client can not decode
+    #[cfg_attr(not(any(feature = "http", feature = "ws")), allow(dead_code))]
+    pub(crate) fn ensure_supported(self) -> Result<()> {
+        #[cfg(not(feature = "zstd"))]
+        if let Self::ZstdJsonStream = self {
+            return Err(Error::InvalidRequest(
+                "the zstd_json_stream format requires the zstd feature",
+            ));
+        }
+        Ok(())
+    }
 }
@@ -6,16 +6,33 @@ use serde::{
     de::{self, Visitor},
     Deserialize, Deserializer, Serialize, Serializer,
 };
-use strum::{AsRefStr, EnumString};
+use strum::{AsRefStr, EnumIter, EnumString, IntoEnumIterator};
 
+pub mod dex;
+pub mod erc721;
+pub mod ethereum;
 pub mod format;
+#[cfg(feature = "fuel")]
 pub mod fuel;
 pub mod query;
 pub mod status;
+#[cfg(feature = "uniswap")]
 pub mod uniswap_v2;
+#[cfg(feature = "uniswap")]
+pub mod uniswap_v3;
 
 #[derive(
-    AsRefStr, EnumString, Debug, Clone, Copy, PartialEq, Eq, Hash, Default, FromPrimitiveDerive,
+    AsRefStr,
+    EnumString,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    Default,
+    FromPrimitiveDerive,
+    EnumIter,
 )]
 #[allow(clippy::upper_case_acronyms)]
 pub enum ChainId {
@@ -41,10 +58,51 @@ pub enum ChainId {
     #[strum(to_string = "AVAX")]
     AVAX = 43114,
     #[strum(to_string = "SEPETH")]
-    SEPETH = 1115511,
+    SEPETH = 11155111,
 }
 
 impl ChainId {
+    const ALL: [Self; 10] = [
+        Self::ETH,
+        Self::OPT,
+        Self::BNB,
+        Self::FUEL,
+        Self::MATIC,
+        Self::BTC,
+        Self::MEVM,
+        Self::ARB,
+        Self::AVAX,
+        Self::SEPETH,
+    ];
+
+    /// Parses a chain from user input such as CLI arguments or config files
+    ///
+    /// Unlike [`FromStr`] and `TryFrom<&str>`, which only accept the exact
+    /// code, this accepts the numeric chain id (`"1"`), the chain code
+    /// (`"ETH"`) and the chain name (`"Ethereum"`), the latter two
+    /// case-insensitive.
+    pub fn parse(s: &str) -> crate::Result<Self> {
+        let s = s.trim();
+        if let Ok(id) = s.parse::<i32>() {
+            return Self::from_i32(id).ok_or_else(|| crate::Error::UnknownChain(s.to_owned()));
+        }
+
+        Self::iter()
+            .find(|chain| {
+                chain.chain_code().eq_ignore_ascii_case(s)
+                    || chain.as_ref().eq_ignore_ascii_case(s)
+                    || chain.chain_name().eq_ignore_ascii_case(s)
+            })
+            .ok_or_else(|| crate::Error::UnknownChain(s.to_owned()))
+    }
+
+    /// Returns every supported chain, excluding [`ChainId::Any`]
+    ///
+    /// Use [`ChainId::iter`] to iterate over all variants including it.
+    pub fn all() -> &'static [Self] {
+        &Self::ALL
+    }
+
     pub fn is_any(&self) -> bool {
         matches!(self, Self::Any)
     }
@@ -76,10 +134,58 @@ impl ChainId {
             Self::MATIC => "Polygon".to_string(),
             Self::FUEL => "Fuel".to_string(),
             Self::MEVM => "MEVM".to_string(),
-            Self::SEPETH => "Sepior".to_string(),
+            Self::SEPETH => "Sepolia".to_string(),
             Self::BTC => "Bitcoin".to_string(),
         }
     }
+
+    /// Returns the symbol of the native currency, empty for [`ChainId::Any`]
+    pub fn native_symbol(&self) -> &'static str {
+        match self {
+            Self::Any => "",
+            Self::ETH | Self::OPT | Self::ARB | Self::SEPETH | Self::FUEL => "ETH",
+            Self::AVAX => "AVAX",
+            Self::BNB => "BNB",
+            Self::MATIC => "MATIC",
+            Self::MEVM => "MOVE",
+            Self::BTC => "BTC",
+        }
+    }
+
+    /// Returns the number of decimals of the native currency, 0 for
+    /// [`ChainId::Any`]
+    pub fn native_decimals(&self) -> u8 {
+        match self {
+            Self::Any => 0,
+            Self::ETH
+            | Self::OPT
+            | Self::ARB
+            | Self::SEPETH
+            | Self::AVAX
+            | Self::BNB
+            | Self::MATIC
+            | Self::MEVM => 18,
+            Self::FUEL => 9,
+            Self::BTC => 8,
+        }
+    }
+
+    /// Returns the base URL of the most common block explorer of the chain,
+    /// without a trailing slash
+    pub fn block_explorer_base_url(&self) -> Option<&'static str> {
+        match self {
+            Self::Any | Self::MEVM => None,
+            Self::ETH => Some("https://etherscan.io"),
+            Self::AVAX => Some("https://snowtrace.io"),
+            Self::BNB => Some("https://bscscan.com"),
+            Self::OPT => Some("https://optimistic.etherscan.io"),
+            Self::ARB => Some("https://arbiscan.io"),
+            Self::MATIC => Some("https://polygonscan.com"),
+            Self::FUEL => Some("https://app.fuel.network"),
+            Self::SEPETH => Some("https://sepolia.etherscan.io"),
+            Self::BTC => Some("https://mempool.space"),
+        }
+    }
 }
 
 impl Serialize for ChainId {
@@ -182,3 +288,67 @@ impl<'de> Deserialize<'de> for ChainId {
 pub fn default_chains() -> HashSet<ChainId> {
     HashSet::from([ChainId::ETH])
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserializes_sepolia() {
+        let from_id: ChainId = serde_json::from_str("11155111").unwrap();
+        let from_code: ChainId = serde_json::from_str(r#""SEPETH""#).unwrap();
+
+        assert_eq!(from_id, ChainId::SEPETH);
+        assert_eq!(from_code, ChainId::SEPETH);
+        assert_eq!(ChainId::SEPETH.chain_name(), "Sepolia");
+    }
+
+    #[test]
+    fn parses_ids_codes_and_names_of_every_chain() {
+        for &chain in ChainId::all() {
+            let id = (chain as i32).to_string();
+            let code = chain.chain_code().to_lowercase();
+            let name = chain.chain_name().to_uppercase();
+
+            for input in [&id, &code, &name] {
+                assert_eq!(ChainId::parse(input).unwrap(), chain, "{input}");
+            }
+        }
+        assert_eq!(ChainId::parse("eth").unwrap(), ChainId::ETH);
+        assert_eq!(ChainId::parse("ethereum").unwrap(), ChainId::ETH);
+        assert!(matches!(
+            ChainId::parse("dogecoin"),
+            Err(crate::Error::UnknownChain(_))
+        ));
+    }
+
+    #[test]
+    fn all_chains_have_unique_codes() {
+        let codes: HashSet<_> = ChainId::all().iter().map(ChainId::chain_code).collect();
+
+        assert_eq!(ChainId::all().len(), ChainId::iter().count() - 1);
+        assert_eq!(codes.len(), ChainId::all().len());
+        assert!(!ChainId::all().contains(&ChainId::Any));
+    }
+
+    #[test]
+    fn native_currency_metadata() {
+        let metadata = [ChainId::ETH, ChainId::MATIC, ChainId::BTC, ChainId::ARB].map(|chain| {
+            (
+                chain.native_symbol(),
+                chain.native_decimals(),
+                chain.block_explorer_base_url(),
+            )
+        });
+
+        assert_eq!(
+            metadata,
+            [
+                ("ETH", 18, Some("https://etherscan.io")),
+                ("MATIC", 18, Some("https://polygonscan.com")),
+                ("BTC", 8, Some("https://mempool.space")),
+                ("ETH", 18, Some("https://arbiscan.io")),
+            ]
+        );
+    }
+}
@@ -5,13 +5,24 @@ use regex::Regex;
 #[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
 pub enum Bound {
     /// The range starts/ends at this block height (inclusive)
+    ///
+    /// Sent on the wire as the plain block height, e.g. `17000000`.
     Exact(i64),
     /// The range should start/end at the latest block height
+    ///
+    /// Sent on the wire as `"latest"`.
     #[default]
     Latest,
-    // The bound is defined from the latest block height
+    /// The range starts/ends `n` blocks before the latest block height,
+    /// i.e. at `latest - n`
+    ///
+    /// Sent on the wire as a negative integer, e.g. `FromLatest(100)` is
+    /// encoded as `-100`.
     FromLatest(u64),
-    /// Real-time 
+    /// The range has no upper end and keeps streaming new blocks in
+    /// real-time
+    ///
+    /// Sent on the wire as `"none"`.
     Subscribe,
 }
 
@@ -22,6 +33,46 @@ impl Bound {
     pub const fn none() -> Self {
         Self::Subscribe
     }
+
+    /// A bound `n` blocks before the latest block height
+    pub const fn from_latest(n: u64) -> Self {
+        Self::FromLatest(n)
+    }
+
+    /// A bound at `latest - n`, an alias of [`Bound::from_latest`]
+    pub const fn latest_minus(n: u64) -> Self {
+        Self::from_latest(n)
+    }
+
+    /// Resolves the bound to an absolute block height given the latest known
+    /// block height
+    ///
+    /// Returns `None` for [`Bound::Subscribe`] as it has no fixed height.
+    pub fn resolve(&self, latest_height: i64) -> Option<i64> {
+        match self {
+            Self::Exact(n) => Some(*n),
+            Self::FromLatest(n) => Some(latest_height.saturating_sub_unsigned(*n)),
+            Self::Latest => Some(latest_height),
+            Self::Subscribe => None,
+        }
+    }
+
+    /// Compares two bounds after resolving them against the latest known
+    /// block height
+    ///
+    /// Unlike [`PartialOrd`], this can order mixed bounds such as
+    /// `Exact(n)` and `FromLatest(m)`. [`Bound::Subscribe`] is greater than
+    /// every other bound.
+    pub fn cmp_at(&self, other: &Self, latest_height: i64) -> Option<std::cmp::Ordering> {
+        use std::cmp::Ordering;
+
+        match (self.resolve(latest_height), other.resolve(latest_height)) {
+            (Some(lhs), Some(rhs)) => Some(lhs.cmp(&rhs)),
+            (Some(_), None) => Some(Ordering::Less),
+            (None, Some(_)) => Some(Ordering::Greater),
+            (None, None) => None,
+        }
+    }
 }
 
 impl PartialOrd for Bound {
@@ -39,6 +90,34 @@ impl PartialOrd for Bound {
     }
 }
 
+/// The order of the results of a request
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Order {
+    /// Oldest first
+    #[default]
+    Asc,
+    /// Newest first, only valid for bounded ranges
+    Desc,
+}
+
+/// A block range made of a lower and an upper [`Bound`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BlockRange {
+    /// Inclusive lower bound
+    pub from: Bound,
+    /// Exclusive upper bound
+    pub to: Bound,
+}
+
+impl BlockRange {
+    pub const fn new(from: Bound, to: Bound) -> Self {
+        Self { from, to }
+    }
+}
+
 impl serde::Serialize for Bound {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -87,14 +166,11 @@ impl<'de> serde::Deserialize<'de> for Bound {
                     }
                 }
 
-                if let Ok(exact) = v.parse::<i64>() {
-                    return Ok(Bound::Exact(exact));
-                }
-
                 if let Ok(exact) = v.parse::<i64>() {
                     if exact < 0 {
                         return Ok(Bound::FromLatest(exact.unsigned_abs()));
                     }
+                    return Ok(Bound::Exact(exact));
                 }
 
                 Err(E::invalid_value(serde::de::Unexpected::Str(v), &self))
@@ -110,6 +186,15 @@ impl<'de> serde::Deserialize<'de> for Bound {
                     Ok(Bound::Exact(v))
                 }
             }
+
+            fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                i64::try_from(v)
+                    .map(Bound::Exact)
+                    .map_err(|_| E::invalid_value(serde::de::Unexpected::Unsigned(v), &self))
+            }
         }
 
         deserializer.deserialize_any(Visitor)
@@ -126,3 +211,97 @@ impl std::fmt::Debug for Bound {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::requests::blocks::GetBlocksRequest;
+
+    #[test]
+    fn from_latest_serializes_as_negative_height() {
+        assert_eq!(
+            serde_json::to_value(Bound::from_latest(5)).unwrap(),
+            serde_json::json!(-5)
+        );
+        assert_eq!(
+            serde_json::to_value(Bound::latest_minus(5)).unwrap(),
+            serde_json::json!(-5)
+        );
+    }
+
+    #[test]
+    fn deserializes_relative_and_exact_bounds() {
+        let parse = |v: &str| serde_json::from_str::<Bound>(v).unwrap();
+
+        assert_eq!(parse(r#""-5""#), Bound::FromLatest(5));
+        assert_eq!(parse(r#""5""#), Bound::Exact(5));
+        assert_eq!(parse(r#""latest - 3""#), Bound::FromLatest(3));
+        assert_eq!(parse(r#""none""#), Bound::Subscribe);
+    }
+
+    #[test]
+    fn resolves_bounds_against_the_latest_height() {
+        let latest = 100;
+
+        assert_eq!(Bound::Exact(42).resolve(latest), Some(42));
+        assert_eq!(Bound::FromLatest(10).resolve(latest), Some(90));
+        assert_eq!(Bound::Latest.resolve(latest), Some(100));
+        assert_eq!(Bound::Subscribe.resolve(latest), None);
+    }
+
+    #[test]
+    fn last_n_blocks_sets_both_bounds() {
+        let request = GetBlocksRequest::default().last_n_blocks(10);
+
+        assert_eq!(request.from_block, Bound::FromLatest(10));
+        assert_eq!(request.to_block, Bound::Latest);
+
+        let query = request.to_query().unwrap();
+        assert!(query.contains("from_block=-10&to_block=latest"), "{query}");
+    }
+
+    #[test]
+    fn range_sets_both_bounds() {
+        let range = BlockRange::new(Bound::Exact(10), Bound::FromLatest(5));
+        let request = GetBlocksRequest::default().range(range);
+
+        assert_eq!(request.from_block, Bound::Exact(10));
+        assert_eq!(request.to_block, Bound::FromLatest(5));
+    }
+
+    #[test]
+    fn order_is_only_serialized_when_set() {
+        let request = GetBlocksRequest {
+            from_block: Bound::Exact(10),
+            to_block: Bound::Exact(20),
+            ..Default::default()
+        };
+        assert!(!request.to_query().unwrap().contains("order"));
+
+        let request = GetBlocksRequest {
+            order: Some(Order::Desc),
+            ..request
+        };
+        assert!(request.to_query().unwrap().ends_with("&order=desc"));
+    }
+
+    #[test]
+    fn validate_rejects_descending_subscriptions() {
+        let request = GetBlocksRequest {
+            from_block: Bound::Exact(10),
+            to_block: Bound::Subscribe,
+            order: Some(Order::Desc),
+            ..Default::default()
+        };
+        assert!(matches!(
+            request.validate(),
+            Err(crate::Error::InvalidRequest(_))
+        ));
+
+        let bounded = GetBlocksRequest {
+            to_block: Bound::Exact(20),
+            ..request
+        };
+        assert!(bounded.validate().is_ok());
+    }
+}
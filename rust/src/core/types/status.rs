@@ -1,3 +1,8 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
 use serde::{Deserialize, Serialize};
 use strum_macros::{AsRefStr, EnumString};
 
@@ -37,3 +42,104 @@ pub struct Status {
     pub timestamp: u64,
     pub status: HealthStatus,
 }
+
+/// The health of all chains, derived from a snapshot of the status stream
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct HealthReport {
+    /// Whether every chain is healthy
+    pub overall_ok: bool,
+    pub per_chain: HashMap<ChainId, ChainHealth>,
+    /// The age of the latest block of the chain that is most behind
+    pub stalest_block_age: Duration,
+}
+
+/// The health of the services of a single chain
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ChainHealth {
+    /// Whether all services report [`HealthStatus::Ok`] and the latest block
+    /// is younger than the maximum age
+    pub ok: bool,
+    /// The highest block any service of the chain has reached
+    pub latest_block_height: u64,
+    /// Time since the timestamp of the latest block
+    pub block_age: Duration,
+    /// Services that do not report [`HealthStatus::Ok`]
+    pub failing_services: Vec<String>,
+}
+
+impl HealthReport {
+    /// Builds a report from status rows, flagging chains whose latest block
+    /// is older than `max_block_age` at `now`
+    ///
+    /// Status timestamps are unix timestamps in seconds.
+    pub fn from_statuses(
+        statuses: impl IntoIterator<Item = Status>,
+        now: SystemTime,
+        max_block_age: Duration,
+    ) -> Self {
+        let now = now.duration_since(UNIX_EPOCH).unwrap_or_default();
+        let mut latest_timestamps = HashMap::new();
+        let mut per_chain: HashMap<ChainId, ChainHealth> = HashMap::new();
+        for status in statuses {
+            let chain = per_chain.entry(status.chain).or_default();
+            chain.latest_block_height = chain.latest_block_height.max(status.latest_block_height);
+            let timestamp = latest_timestamps.entry(status.chain).or_insert(0);
+            *timestamp = status.timestamp.max(*timestamp);
+            if status.status != HealthStatus::Ok {
+                chain.failing_services.push(status.service);
+            }
+        }
+
+        let mut report = Self {
+            overall_ok: true,
+            ..Self::default()
+        };
+        for (chain_id, mut chain) in per_chain {
+            chain.block_age = now.saturating_sub(Duration::from_secs(latest_timestamps[&chain_id]));
+            chain.ok = chain.failing_services.is_empty() && chain.block_age <= max_block_age;
+            report.overall_ok &= chain.ok;
+            report.stalest_block_age = report.stalest_block_age.max(chain.block_age);
+            report.per_chain.insert(chain_id, chain);
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(chain: ChainId, service: &str, height: u64, timestamp: u64) -> Status {
+        Status {
+            chain,
+            service: service.into(),
+            latest_block_height: height,
+            timestamp,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn flags_the_stale_chain() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_000);
+        let statuses = [
+            status(ChainId::ETH, "blocks", 100, 990),
+            status(ChainId::ETH, "logs", 99, 985),
+            status(ChainId::ARB, "blocks", 500, 400),
+        ];
+
+        let report = HealthReport::from_statuses(statuses, now, Duration::from_secs(60));
+
+        assert!(!report.overall_ok);
+        assert_eq!(report.stalest_block_age, Duration::from_secs(600));
+
+        let eth = &report.per_chain[&ChainId::ETH];
+        assert!(eth.ok);
+        assert_eq!(eth.latest_block_height, 100);
+        assert_eq!(eth.block_age, Duration::from_secs(10));
+
+        let arb = &report.per_chain[&ChainId::ARB];
+        assert!(!arb.ok);
+        assert!(arb.failing_services.is_empty());
+    }
+}
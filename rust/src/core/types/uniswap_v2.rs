@@ -1,6 +1,12 @@
+use std::{cmp::Ordering, collections::HashMap};
+
+use ethers_core::types::{Address, H256, U128};
 use serde::{Deserialize, Serialize};
 use strum::AsRefStr;
 
+use super::ChainId;
+use crate::core::agg::PricePoint;
+
 /// LpEvent is the event type of uniswap v2
 #[derive(AsRefStr, Clone, Copy, Debug, Default, Hash, Deserialize, Serialize, PartialEq, Eq)]
 pub enum ReserveEvent {
@@ -39,3 +45,383 @@ impl TryFrom<i32> for ReserveEvent {
         }
     }
 }
+
+/// A row of the Uniswap V2 prices stream
+///
+/// Every reserve changing event of a pair produces a row with the reserves
+/// after the event.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Price {
+    pub chain: ChainId,
+    pub block_number: u64,
+    pub timestamp: u64,
+    pub transaction_hash: H256,
+    pub log_index: u64,
+    pub pair_address: Address,
+    pub pair_factory_address: Address,
+    pub event: ReserveEvent,
+    pub reserve0: U128,
+    pub reserve1: U128,
+    pub price: f64,
+    pub sender: Address,
+    pub receiver: Address,
+    pub amount0: f64,
+    pub amount1: f64,
+    pub lp_amount: f64,
+    pub protocol_fee: f64,
+    pub token0_address: Address,
+    pub token0_symbol: String,
+    pub token1_address: Address,
+    pub token1_symbol: String,
+}
+
+/// A row of the Uniswap V2 pairs stream
+///
+/// Every pair created by a factory produces one row, in the block of its
+/// creation.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Pair {
+    pub chain: ChainId,
+    pub block_number: u64,
+    pub timestamp: u64,
+    pub transaction_hash: H256,
+    pub pair_address: Address,
+    pub factory_address: Address,
+    pub token0_address: Address,
+    pub token0_symbol: String,
+    pub token1_address: Address,
+    pub token1_symbol: String,
+}
+
+/// Precision of the price fields
+///
+/// The reserves are the exact `uint112` values of the pair and are kept as
+/// [`U128`], while `price` and the amounts are decimal adjusted by the server
+/// and only available as `f64`. The amounts are signed, so they can not be
+/// modelled as unsigned integers. Whenever exactness matters, derive values
+/// from the reserves with the methods below instead.
+impl Price {
+    /// The exact reserve of `token0`, in the smallest unit of the token
+    pub fn reserve0_raw(&self) -> u128 {
+        self.reserve0.as_u128()
+    }
+
+    /// The exact reserve of `token1`, in the smallest unit of the token
+    pub fn reserve1_raw(&self) -> u128 {
+        self.reserve1.as_u128()
+    }
+
+    /// The reserve of `token0` in whole tokens, approximated as `f64`
+    pub fn reserve0_f64(&self, decimals0: u8) -> f64 {
+        to_units(self.reserve0_raw(), decimals0)
+    }
+
+    /// The reserve of `token1` in whole tokens, approximated as `f64`
+    pub fn reserve1_f64(&self, decimals1: u8) -> f64 {
+        to_units(self.reserve1_raw(), decimals1)
+    }
+
+    /// The price of `token0` in `token1` derived from the reserves, `None` if
+    /// the pair has no `token0` reserve
+    ///
+    /// The reserves are only rounded once each, so this is at least as precise
+    /// as `price` and does not depend on the rounding of the server.
+    pub fn price_from_reserves(&self, decimals0: u8, decimals1: u8) -> Option<f64> {
+        if self.reserve0.is_zero() {
+            return None;
+        }
+        Some(self.reserve1_f64(decimals1) / self.reserve0_f64(decimals0))
+    }
+}
+
+#[cfg(feature = "decimal")]
+impl Price {
+    /// The price of `token0` in `token1` derived from the reserves without
+    /// `f64` rounding
+    ///
+    /// The result is exact up to the 28 significant digits of a
+    /// [`Decimal`](rust_decimal::Decimal). Returns `None` if the pair has no
+    /// `token0` reserve or a reserve exceeds the 96 bit mantissa of a
+    /// `Decimal`.
+    pub fn price_decimal(&self, decimals0: u8, decimals1: u8) -> Option<rust_decimal::Decimal> {
+        let reserve0 = to_decimal(self.reserve0_raw(), decimals0)?;
+        let reserve1 = to_decimal(self.reserve1_raw(), decimals1)?;
+        reserve1.checked_div(reserve0)
+    }
+}
+
+/// Converts an amount in the smallest unit of a token to whole tokens
+#[cfg(feature = "decimal")]
+fn to_decimal(raw: u128, decimals: u8) -> Option<rust_decimal::Decimal> {
+    rust_decimal::Decimal::try_from_i128_with_scale(raw.try_into().ok()?, decimals.into()).ok()
+}
+
+/// Converts an amount in the smallest unit of a token to whole tokens
+fn to_units(raw: u128, decimals: u8) -> f64 {
+    raw as f64 / 10f64.powi(decimals.into())
+}
+
+/// The side of a trade, from the perspective of the trader and `token0`
+#[derive(AsRefStr, Clone, Copy, Debug, Hash, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Side {
+    /// The trader bought `token0` out of the pair
+    Buy,
+    /// The trader sold `token0` into the pair
+    Sell,
+}
+
+/// A swap on a Uniswap V2 pair
+///
+/// Trades are not provided by the server but derived on the client from the
+/// `Swap` rows of the prices stream, see [`TradeDecoder`].
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Trade {
+    pub chain: ChainId,
+    pub block_number: u64,
+    pub timestamp: u64,
+    pub transaction_hash: H256,
+    pub pair_address: Address,
+    /// The price after the swap
+    pub price: f64,
+    /// The price after the previous swap of the same pair, if one was seen
+    pub last_traded_price: Option<f64>,
+    /// The absolute amount of `token0` traded
+    pub volume0: f64,
+    /// The absolute amount of `token1` traded
+    pub volume1: f64,
+    /// The side of the trade
+    pub side: Side,
+}
+
+/// Derives [`Trade`]s from the rows of the prices stream
+///
+/// Only `Swap` rows produce trades, `Sync`, `Mint` and `Burn` rows are
+/// skipped. The amounts of a swap are signed from the perspective of the
+/// pair, positive for tokens flowing into it, so the side is inferred from
+/// their signs:
+/// - a positive `amount0` means `token0` was sold into the pair,
+///   [`Side::Sell`]
+/// - a negative `amount0` means `token0` was bought out of the pair,
+///   [`Side::Buy`]
+/// - without an `amount0`, a positive `amount1` means `token1` was paid for
+///   `token0`, [`Side::Buy`], and a negative one [`Side::Sell`]
+///
+/// Swaps without any amount are skipped. The side does not depend on earlier
+/// rows, so the prices request may filter for `Swap` events only.
+#[derive(Debug, Default)]
+pub struct TradeDecoder {
+    last_prices: HashMap<Address, f64>,
+}
+
+impl TradeDecoder {
+    /// Feeds the next row of the prices stream, returning a trade for swaps
+    pub fn push(&mut self, price: &Price) -> Option<Trade> {
+        if price.event != ReserveEvent::Swap {
+            return None;
+        }
+
+        let side = side_of(price.amount0).or_else(|| side_of(-price.amount1))?;
+        let last_traded_price = self.last_prices.insert(price.pair_address, price.price);
+
+        Some(Trade {
+            chain: price.chain,
+            block_number: price.block_number,
+            timestamp: price.timestamp,
+            transaction_hash: price.transaction_hash,
+            pair_address: price.pair_address,
+            price: price.price,
+            last_traded_price,
+            volume0: price.amount0.abs(),
+            volume1: price.amount1.abs(),
+            side,
+        })
+    }
+}
+
+/// Returns the side of a trade moving `amount0` of `token0` into the pair
+fn side_of(amount0: f64) -> Option<Side> {
+    match amount0.partial_cmp(&0.0)? {
+        Ordering::Greater => Some(Side::Sell),
+        Ordering::Less => Some(Side::Buy),
+        Ordering::Equal => None,
+    }
+}
+
+impl PricePoint for Price {
+    fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    fn block_number(&self) -> u64 {
+        self.block_number
+    }
+
+    fn price(&self) -> f64 {
+        self.price
+    }
+
+    fn volume(&self) -> f64 {
+        self.amount0.abs()
+    }
+}
+
+impl PricePoint for Trade {
+    fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    fn block_number(&self) -> u64 {
+        self.block_number
+    }
+
+    fn price(&self) -> f64 {
+        self.price
+    }
+
+    fn volume(&self) -> f64 {
+        self.volume0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn swap(amount0: f64, amount1: f64) -> Price {
+        Price {
+            event: ReserveEvent::Swap,
+            pair_address: Address::repeat_byte(1),
+            amount0,
+            amount1,
+            price: 2.0,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn price_round_trips_through_json() {
+        let price = Price {
+            chain: ChainId::ETH,
+            block_number: 17_000_000,
+            transaction_hash: H256::repeat_byte(2),
+            reserve0: 100.into(),
+            reserve1: 200.into(),
+            token0_symbol: "WETH".into(),
+            ..swap(-1.5, 3.0)
+        };
+
+        let json = serde_json::to_string(&price).unwrap();
+        assert!(
+            json.contains(&format!("{:?}", price.pair_address)),
+            "{json}"
+        );
+
+        assert_eq!(serde_json::from_str::<Price>(&json).unwrap(), price);
+    }
+
+    #[test]
+    fn decodes_side_from_amount0() {
+        let mut decoder = TradeDecoder::default();
+
+        let sell = decoder.push(&swap(10.0, -20.0)).unwrap();
+        assert_eq!(sell.side, Side::Sell);
+        assert_eq!((sell.volume0, sell.volume1), (10.0, 20.0));
+
+        let buy = decoder.push(&swap(-10.0, 20.0)).unwrap();
+        assert_eq!(buy.side, Side::Buy);
+        assert_eq!(buy.last_traded_price, Some(2.0));
+    }
+
+    #[test]
+    fn decodes_side_from_amount1_without_amount0() {
+        let mut decoder = TradeDecoder::default();
+
+        assert_eq!(decoder.push(&swap(0.0, 5.0)).unwrap().side, Side::Buy);
+        assert_eq!(decoder.push(&swap(0.0, -5.0)).unwrap().side, Side::Sell);
+        assert!(decoder.push(&swap(0.0, 0.0)).is_none());
+    }
+
+    #[test]
+    fn side_ignores_preceding_sync() {
+        let mut decoder = TradeDecoder::default();
+        let sync = Price {
+            event: ReserveEvent::Sync,
+            reserve0: 100.into(),
+            ..swap(0.0, 0.0)
+        };
+
+        // the sync before a swap carries the reserves after the swap
+        assert!(decoder.push(&sync).is_none());
+        let buy = decoder.push(&Price {
+            reserve0: 100.into(),
+            ..swap(-1.0, 2.0)
+        });
+        assert_eq!(buy.unwrap().side, Side::Buy);
+    }
+
+    #[test]
+    fn skips_non_swap_events() {
+        let mut decoder = TradeDecoder::default();
+        for event in [ReserveEvent::Sync, ReserveEvent::Mint, ReserveEvent::Burn] {
+            assert!(decoder
+                .push(&Price {
+                    event,
+                    ..swap(1.0, 1.0)
+                })
+                .is_none());
+        }
+    }
+
+    #[test]
+    fn raw_reserves_are_exact_where_f64_rounds() {
+        // the largest uint112 exceeds the 53 bit mantissa of an f64
+        let max = (1u128 << 112) - 1;
+        let price: Price = serde_json::from_value(serde_json::json!({
+            "reserve0": format!("{max:#x}"),
+            "reserve1": format!("{:#x}", max / 2),
+        }))
+        .unwrap();
+
+        assert_eq!(price.reserve0_raw(), max);
+        assert_eq!(price.reserve0_f64(0), 2f64.powi(112));
+        assert_ne!(price.reserve0_f64(0) as u128, max);
+        let whole_tokens = max as f64 / 1e18;
+        assert!((price.reserve0_f64(18) - whole_tokens).abs() / whole_tokens < 1e-15);
+        assert!((price.price_from_reserves(18, 18).unwrap() - 0.5).abs() < 1e-15);
+        assert_eq!(Price::default().price_from_reserves(18, 18), None);
+    }
+
+    #[cfg(feature = "decimal")]
+    #[test]
+    fn decimal_price_keeps_full_precision() {
+        // 1 WETH (18 decimals) for 3000.000001 USDC (6 decimals)
+        let price = Price {
+            reserve0: 1_000_000_000_000_000_000u128.into(),
+            reserve1: 3_000_000_001u128.into(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            price.price_decimal(18, 6).unwrap(),
+            "3000.000001".parse::<rust_decimal::Decimal>().unwrap()
+        );
+
+        // a difference in the last wei is kept, while f64 rounds it away
+        let price = Price {
+            reserve0: 1_000_000_000_000_000_000u128.into(),
+            reserve1: 3_000_000_000_000_000_001u128.into(),
+            ..Default::default()
+        };
+        assert_eq!(
+            price.price_decimal(18, 18).unwrap(),
+            "3.000000000000000001"
+                .parse::<rust_decimal::Decimal>()
+                .unwrap()
+        );
+        assert_eq!(price.price_from_reserves(18, 18), Some(3.0));
+        assert_eq!(Price::default().price_decimal(18, 18), None);
+    }
+}
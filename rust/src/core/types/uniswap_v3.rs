@@ -0,0 +1,73 @@
+use ethers_core::types::{Address, H256};
+use serde::{Deserialize, Serialize};
+
+use super::ChainId;
+use crate::core::agg::PricePoint;
+
+/// A row of the Uniswap V3 prices stream
+///
+/// Every liquidity changing event of a pool produces a row with the state of
+/// the pool after the event.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Price {
+    pub chain: ChainId,
+    pub block_number: u64,
+    pub timestamp: u64,
+    pub transaction_hash: H256,
+    pub log_index: u64,
+    pub pool_address: Address,
+    pub pool_factory_address: Address,
+    pub virtual0: f64,
+    pub virtual1: f64,
+    pub price: f64,
+    pub sender: Address,
+    pub receiver: Address,
+    pub amount0: f64,
+    pub amount1: f64,
+    pub liquidity: f64,
+    pub tick: i32,
+    pub token0_address: Address,
+    pub token0_symbol: String,
+    pub token1_address: Address,
+    pub token1_symbol: String,
+}
+
+impl PricePoint for Price {
+    fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    fn block_number(&self) -> u64 {
+        self.block_number
+    }
+
+    fn price(&self) -> f64 {
+        self.price
+    }
+
+    fn volume(&self) -> f64 {
+        self.amount0.abs()
+    }
+}
+
+/// A row of the Uniswap V3 pools stream
+///
+/// Every pool created by a factory produces one row, in the block of its
+/// creation.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Pool {
+    pub chain: ChainId,
+    pub block_number: u64,
+    pub timestamp: u64,
+    pub transaction_hash: H256,
+    pub pool_address: Address,
+    pub factory_address: Address,
+    pub fee: i32,
+    pub tick_spacing: i32,
+    pub token0_address: Address,
+    pub token0_symbol: String,
+    pub token1_address: Address,
+    pub token1_symbol: String,
+}
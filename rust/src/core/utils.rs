@@ -1,27 +1,35 @@
-use std::collections::HashSet;
+use std::{collections::HashSet, fmt, marker::PhantomData, time::SystemTime};
 
-use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::{
+    de::{self, DeserializeOwned, SeqAccess, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
 
 use ethers_core::types::Address;
 
+/// Serializes a collection as a comma separated string
+///
+/// The items are sorted, so that sets with the same items always result in the
+/// same string regardless of their iteration order.
 pub fn serialize_comma_separated<S, T, I>(value: T, serializer: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
     T: IntoIterator<Item = I>,
     I: Serialize,
 {
-    let mut buffer = String::new();
-    for (i, item) in value.into_iter().enumerate() {
-        if i > 0 {
-            buffer.push(',');
-        }
-        buffer.push_str(
+    let mut items = value
+        .into_iter()
+        .map(|item| {
             serde_json::to_string(&item)
                 .unwrap()
                 .trim_start_matches('"')
-                .trim_end_matches('"'),
-        );
-    }
+                .trim_end_matches('"')
+                .to_owned()
+        })
+        .collect::<Vec<_>>();
+    items.sort_unstable();
+
+    let buffer = items.join(",");
     if !buffer.is_empty() {
         return serializer.serialize_str(&buffer);
     }
@@ -29,6 +37,74 @@ where
     serializer.serialize_none()
 }
 
+/// The inverse of [`serialize_comma_separated`]
+///
+/// Accepts either a comma separated string, as found in query strings, or a
+/// sequence of items. Each item of a string is decoded like the JSON value
+/// the serializer produced it from, so quoted and unquoted values such as
+/// addresses and numbers are both supported.
+pub fn deserialize_comma_separated<'de, D, C, I>(deserializer: D) -> Result<C, D::Error>
+where
+    D: Deserializer<'de>,
+    C: FromIterator<I>,
+    I: DeserializeOwned,
+{
+    struct CommaSeparatedVisitor<C, I>(PhantomData<(C, I)>);
+
+    impl<'de, C, I> Visitor<'de> for CommaSeparatedVisitor<C, I>
+    where
+        C: FromIterator<I>,
+        I: DeserializeOwned,
+    {
+        type Value = C;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+            formatter.write_str("a comma separated string or a sequence")
+        }
+
+        fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            v.split(',')
+                .filter(|item| !item.is_empty())
+                .map(|item| {
+                    I::deserialize(serde_json::Value::String(item.to_string()))
+                        .or_else(|_| serde_json::from_str::<I>(item))
+                        .map_err(E::custom)
+                })
+                .collect()
+        }
+
+        fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+        where
+            A: SeqAccess<'de>,
+        {
+            let mut items = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+            while let Some(item) = seq.next_element()? {
+                items.push(item);
+            }
+            Ok(items.into_iter().collect())
+        }
+
+        fn visit_none<E>(self) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            Ok(std::iter::empty().collect())
+        }
+
+        fn visit_unit<E>(self) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            Ok(std::iter::empty().collect())
+        }
+    }
+
+    deserializer.deserialize_any(CommaSeparatedVisitor(PhantomData))
+}
+
 pub fn deserialize_addresses<'de, D>(deserializer: D) -> Result<HashSet<Address>, D::Error>
 where
     D: Deserializer<'de>,
@@ -40,3 +116,14 @@ where
     }
     Ok(addresses)
 }
+
+/// Returns the current time of the system clock
+///
+/// `SystemTime::now` panics on wasm32, where the time is read from the
+/// browser's `Date` instead.
+pub(crate) fn system_now() -> SystemTime {
+    #[cfg(not(target_arch = "wasm32"))]
+    return SystemTime::now();
+    #[cfg(target_arch = "wasm32")]
+    return std::time::UNIX_EPOCH + std::time::Duration::from_millis(js_sys::Date::now() as u64);
+}
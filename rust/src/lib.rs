@@ -9,18 +9,39 @@
 pub mod core;
 mod providers;
 
-pub use ::{futures, reqwest, tokio, tokio_tungstenite, tungstenite, url};
+pub use ::{bytes, futures, tokio, url};
+#[cfg(feature = "http")]
+pub use reqwest;
+#[cfg(all(feature = "ws", not(target_arch = "wasm32")))]
+pub use ::{tokio_tungstenite, tungstenite};
+#[cfg(feature = "decimal")]
+pub use rust_decimal;
 
-pub use  ethers_core::types::Address;
+#[cfg(feature = "ethers-types")]
+pub use ethers_core::{self, types::Address};
 
 #[doc(inline)]
 pub use crate::core::{
+    agg,
     builder::ClientBuilder,
     client::Client,
+    decode::DecodeStream,
     error::{Error, Result},
-    provider, requests,
+    progress, provider, requests,
     types::{format::Format, query, ChainId},
     utils,
 };
+#[cfg(not(target_arch = "wasm32"))]
 #[doc(inline)]
-pub use crate::providers::{http::HttpProvider, ws::WsProvider};
+pub use crate::core::builder::RetryPolicy;
+#[doc(inline)]
+pub use crate::providers::{failover::FailoverProvider, load_balance::LoadBalancedProvider};
+#[cfg(feature = "http")]
+#[doc(inline)]
+pub use crate::providers::http::HttpProvider;
+#[cfg(all(feature = "ws", not(target_arch = "wasm32")))]
+#[doc(inline)]
+pub use crate::providers::{
+    subscriptions::{SubscribeCommand, SubscriptionManager},
+    ws::{Operation, StreamEvent, WsProvider},
+};
@@ -0,0 +1,191 @@
+use std::{
+    num::NonZeroUsize,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use bytes::Bytes;
+use futures::{stream, StreamExt};
+use lru::LruCache;
+use tokio::time::Instant;
+
+use crate::core::provider::ResponseStream;
+
+type Chunks = Arc<Vec<Bytes>>;
+
+/// An in-memory LRU cache of fully consumed HTTP responses
+///
+/// Entries are keyed by the full request URL, including the query string.
+#[derive(Clone)]
+pub(crate) struct HttpCache {
+    entries: Arc<Mutex<LruCache<String, (Instant, Chunks)>>>,
+    ttl: Duration,
+}
+
+impl HttpCache {
+    pub(crate) fn new(capacity: NonZeroUsize, ttl: Duration) -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(LruCache::new(capacity))),
+            ttl,
+        }
+    }
+
+    /// Returns whether the response of the URL can be cached
+    ///
+    /// Only requests with exact heights for both block bounds are immutable.
+    /// Relative bounds like `latest` or `none` change with every new block.
+    pub(crate) fn is_cacheable(url: &reqwest::Url) -> bool {
+        let is_exact = |name: &str| {
+            url.query_pairs()
+                .find(|(key, _)| key == name)
+                .is_some_and(|(_, value)| value.parse::<u64>().is_ok())
+        };
+
+        is_exact("from_block") && is_exact("to_block")
+    }
+
+    /// Returns a replay of the cached response, if there is a fresh one
+    pub(crate) fn get(&self, key: &str) -> Option<ResponseStream<Bytes>> {
+        let mut entries = self.entries.lock().unwrap();
+        let (inserted, chunks) = entries.get(key)?;
+        if inserted.elapsed() > self.ttl {
+            entries.pop(key);
+            return None;
+        }
+
+        let chunks = chunks.clone();
+        Some(ResponseStream::new(stream::iter(
+            (0..chunks.len()).map(move |i| Ok(chunks[i].clone())),
+        )))
+    }
+
+    /// Passes the stream through and caches the response once it was fully
+    /// consumed without errors
+    pub(crate) fn record(
+        &self,
+        key: String,
+        stream: ResponseStream<Bytes>,
+    ) -> ResponseStream<Bytes> {
+        let cache = self.clone();
+
+        let stream = stream::unfold(
+            (stream, Some(Vec::new())),
+            move |(mut stream, mut chunks)| {
+                let cache = cache.clone();
+                let key = key.clone();
+                async move {
+                    match stream.next().await {
+                        Some(Ok(bytes)) => {
+                            if let Some(chunks) = chunks.as_mut() {
+                                chunks.push(bytes.clone());
+                            }
+                            Some((Ok(bytes), (stream, chunks)))
+                        }
+                        // A failed response is never cached
+                        Some(Err(e)) => Some((Err(e), (stream, None))),
+                        None => {
+                            if let Some(chunks) = chunks {
+                                cache.insert(key, chunks);
+                            }
+                            None
+                        }
+                    }
+                }
+            },
+        );
+
+        ResponseStream::new(stream)
+    }
+
+    fn insert(&self, key: String, chunks: Vec<Bytes>) {
+        self.entries
+            .lock()
+            .unwrap()
+            .put(key, (Instant::now(), Arc::new(chunks)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use futures::{executor::block_on, TryStreamExt};
+
+    use super::*;
+    use crate::Error;
+
+    /// Serves the request from the cache or from the origin, counting the
+    /// requests that reached the origin
+    fn fetch(cache: &HttpCache, key: &str, origin: &AtomicUsize) -> Vec<Bytes> {
+        let stream = cache.get(key).unwrap_or_else(|| {
+            origin.fetch_add(1, Ordering::SeqCst);
+            let response = stream::iter([Ok(Bytes::from("a\n")), Ok(Bytes::from("b\n"))]);
+            cache.record(key.to_string(), ResponseStream::new(response))
+        });
+        block_on(stream.try_collect()).unwrap()
+    }
+
+    fn cache() -> HttpCache {
+        HttpCache::new(NonZeroUsize::new(8).unwrap(), Duration::from_secs(60))
+    }
+
+    #[test]
+    fn replays_a_cached_response() {
+        let (cache, origin) = (cache(), AtomicUsize::new(0));
+
+        let first = fetch(&cache, "blocks?from_block=1&to_block=2", &origin);
+        let second = fetch(&cache, "blocks?from_block=1&to_block=2", &origin);
+
+        assert_eq!(origin.load(Ordering::SeqCst), 1);
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn other_requests_miss() {
+        let (cache, origin) = (cache(), AtomicUsize::new(0));
+
+        fetch(&cache, "blocks?from_block=1&to_block=2", &origin);
+        fetch(&cache, "blocks?from_block=1&to_block=3", &origin);
+
+        assert_eq!(origin.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn failed_responses_are_not_cached() {
+        let cache = cache();
+        let response = stream::iter([Ok(Bytes::from("a\n")), Err(Error::BackendShutDown)]);
+
+        let stream = cache.record("key".to_string(), ResponseStream::new(response));
+        let _ = block_on(stream.collect::<Vec<_>>());
+
+        assert!(cache.get("key").is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn entries_expire_after_the_ttl() {
+        let (cache, origin) = (cache(), AtomicUsize::new(0));
+
+        fetch(&cache, "key", &origin);
+        tokio::time::advance(Duration::from_secs(59)).await;
+        fetch(&cache, "key", &origin);
+        assert_eq!(origin.load(Ordering::SeqCst), 1);
+
+        tokio::time::advance(Duration::from_secs(2)).await;
+        fetch(&cache, "key", &origin);
+        assert_eq!(origin.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn only_exact_bounds_are_cacheable() {
+        let cacheable = |query: &str| {
+            let url = format!("https://example.com/v1/api/blocks?{query}");
+            HttpCache::is_cacheable(&url.parse().unwrap())
+        };
+
+        assert!(cacheable("from_block=1&to_block=2"));
+        assert!(!cacheable("from_block=1&to_block=latest"));
+        assert!(!cacheable("from_block=-5&to_block=2"));
+        assert!(!cacheable("from_block=1&to_block=none"));
+        assert!(!cacheable("from_block=1"));
+    }
+}
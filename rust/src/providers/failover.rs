@@ -0,0 +1,237 @@
+use std::{
+    future::Future,
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use tracing::warn;
+
+use crate::core::{
+    error::{CloseReason, Error, Result},
+    provider::{Cursored, Provider, ProviderConfig, ReplayProvider, StreamResponse},
+    requests::logs::GetLogsRequest,
+    types::format::Format,
+};
+
+/// A provider that fails over between several endpoints
+///
+/// Only one endpoint is used at a time, this is failover and not load
+/// balancing. The provider starts with the first endpoint that it can
+/// connect to, in the order of [`ClientBuilder::endpoints`]. Whenever
+/// starting a request fails with a connection error or a server error
+/// (status 5xx), or the current provider is no longer
+/// [ready](Provider::ready), e.g. its WebSocket connection is down, the
+/// provider connects to the next endpoint and retries the request there,
+/// trying every endpoint at most once per request.
+///
+/// Requests that already returned a stream are not moved: if the connection
+/// fails while streaming, the stream ends with the error as usual, and the
+/// next request fails over.
+///
+/// [`ClientBuilder::endpoints`]: crate::ClientBuilder::endpoints
+pub struct FailoverProvider<T> {
+    config: Arc<ProviderConfig>,
+    endpoints: Arc<[String]>,
+    active: Arc<Mutex<Active<T>>>,
+    // serializes failovers, so that concurrent failing requests connect to
+    // the next endpoint only once
+    failover: Arc<tokio::sync::Mutex<()>>,
+}
+
+struct Active<T> {
+    index: usize,
+    provider: T,
+}
+
+impl<T> Clone for FailoverProvider<T> {
+    fn clone(&self) -> Self {
+        Self {
+            config: self.config.clone(),
+            endpoints: self.endpoints.clone(),
+            active: self.active.clone(),
+            failover: self.failover.clone(),
+        }
+    }
+}
+
+impl<T> FailoverProvider<T>
+where
+    T: Provider + Clone + Send + Sync,
+{
+    /// Returns the endpoint requests are currently sent to
+    pub fn current_endpoint(&self) -> String {
+        let index = self.active.lock().unwrap().index;
+        self.endpoints[index].clone()
+    }
+
+    /// Returns all endpoints in the order they are failed over to
+    pub fn endpoints(&self) -> &[String] {
+        &self.endpoints
+    }
+
+    fn current(&self) -> (usize, T) {
+        let active = self.active.lock().unwrap();
+        (active.index, active.provider.clone())
+    }
+
+    /// Connects to the first endpoint after `start` that accepts the
+    /// connection, wrapping around to `start` itself last
+    async fn connect_after(
+        config: &ProviderConfig,
+        endpoints: &[String],
+        start: usize,
+    ) -> Result<(usize, T)> {
+        let mut last_error = None;
+        for offset in 1..=endpoints.len() {
+            let index = (start + offset) % endpoints.len();
+            let config = ProviderConfig {
+                endpoint: endpoints[index].clone(),
+                ..config.clone()
+            };
+            match T::try_new_with_config(config).await {
+                Ok(provider) => return Ok((index, provider)),
+                Err(err) => {
+                    warn!("failed to connect to {}: {err}", endpoints[index]);
+                    last_error = Some(err);
+                }
+            }
+        }
+        Err(last_error.unwrap_or(Error::InvalidRequest("no endpoints configured")))
+    }
+
+    /// Replaces the provider of the endpoint at `failed` with the next
+    /// endpoint, unless a concurrent request already failed over
+    async fn fail_over(&self, failed: usize) -> Result<()> {
+        let _guard = self.failover.lock().await;
+        if self.active.lock().unwrap().index != failed {
+            return Ok(());
+        }
+
+        let (index, provider) = Self::connect_after(&self.config, &self.endpoints, failed).await?;
+        warn!(
+            "failing over from {} to {}",
+            self.endpoints[failed], self.endpoints[index]
+        );
+        *self.active.lock().unwrap() = Active { index, provider };
+        Ok(())
+    }
+
+    /// Runs a request on the current provider, failing over to the next
+    /// endpoint for connection and server errors
+    async fn call<F, Fut, R>(&self, request: F) -> Result<R>
+    where
+        F: Fn(T) -> Fut,
+        Fut: Future<Output = Result<R>>,
+    {
+        let mut attempts = 0;
+        loop {
+            let (index, provider) = self.current();
+            attempts += 1;
+
+            let result = if provider.ready() {
+                request(provider).await
+            } else {
+                Err(Error::BackendShutDown)
+            };
+            match result {
+                Err(err) if attempts < self.endpoints.len() && is_failover_error(&err) => {
+                    warn!("request to {} failed: {err}", self.endpoints[index]);
+                    self.fail_over(index).await?;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Returns whether an error indicates that the endpoint is unavailable,
+/// rather than that the request is invalid
+pub(super) fn is_failover_error(err: &Error) -> bool {
+    match err {
+        #[cfg(feature = "http")]
+        Error::Reqwest(err) => {
+            // the WASM backend of reqwest does not tell connection errors apart
+            #[cfg(not(target_arch = "wasm32"))]
+            if err.is_connect() {
+                return true;
+            }
+            err.is_timeout() || err.status().is_some_and(|status| status.is_server_error())
+        }
+        #[cfg(all(feature = "ws", not(target_arch = "wasm32")))]
+        Error::Tungstenite(_) => true,
+        Error::ErrorResponse(err) => err.status >= 500,
+        Error::StreamClosed(reason) => *reason != CloseReason::Unsubscribed,
+        Error::IO(_)
+        | Error::UnexpectedClose
+        | Error::ConnectionClosed
+        | Error::BackendShutDown => true,
+        _ => false,
+    }
+}
+
+#[async_trait]
+impl<T> Provider for FailoverProvider<T>
+where
+    T: Provider + Clone + Send + Sync,
+{
+    async fn try_new(
+        endpoint: String,
+        is_secure: bool,
+        username: Option<String>,
+        password: Option<String>,
+    ) -> Result<Self> {
+        Self::try_new_with_config(ProviderConfig::new(endpoint, is_secure, username, password))
+            .await
+    }
+
+    /// Connects to the first available endpoint of
+    /// [`ProviderConfig::endpoints`], or to [`ProviderConfig::endpoint`] if
+    /// there are none
+    async fn try_new_with_config(config: ProviderConfig) -> Result<Self> {
+        let endpoints: Arc<[String]> = if config.endpoints.is_empty() {
+            vec![config.endpoint.clone()].into()
+        } else {
+            config.endpoints.clone().into()
+        };
+        let (index, provider) =
+            Self::connect_after(&config, &endpoints, endpoints.len() - 1).await?;
+
+        Ok(Self {
+            config: Arc::new(config),
+            endpoints,
+            active: Arc::new(Mutex::new(Active { index, provider })),
+            failover: Arc::new(tokio::sync::Mutex::new(())),
+        })
+    }
+
+    async fn get_status_by_format(&self, format: Format) -> StreamResponse<Bytes> {
+        self.call(|provider| async move { provider.get_status_by_format(format).await })
+            .await
+    }
+
+    fn ready(&self) -> bool {
+        self.active.lock().unwrap().provider.ready()
+    }
+}
+
+#[async_trait]
+impl<T> ReplayProvider for FailoverProvider<T>
+where
+    T: Provider + ReplayProvider + Clone + Send + Sync,
+{
+    async fn replay_logs_records(
+        &self,
+        request: GetLogsRequest,
+        cursor: Option<String>,
+    ) -> StreamResponse<Cursored<Bytes>> {
+        self.call(|provider| {
+            let request = request.clone();
+            let cursor = cursor.clone();
+            async move { provider.replay_logs_records(request, cursor).await }
+        })
+        .await
+    }
+}
+
+impl_dataset_traits!(FailoverProvider);
@@ -1,38 +1,96 @@
-use std::{collections::HashSet, str::FromStr};
+#[cfg(any(feature = "fuel", feature = "btc"))]
+use std::collections::HashSet;
+use std::str::FromStr;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Duration;
 
 use async_trait::async_trait;
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
-use futures::{StreamExt, TryStreamExt};
+use bytes::Bytes;
+use futures::TryStreamExt;
 use reqwest::header;
 
+#[cfg(all(feature = "cache", not(target_arch = "wasm32")))]
+use super::cache::HttpCache;
+#[cfg(feature = "hmac")]
+use crate::core::auth::HmacSigner;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::core::rate_limit::RateLimiter;
+
+#[cfg(feature = "curve")]
+use crate::provider::CurveProvider;
+#[cfg(any(feature = "curve", feature = "uniswap"))]
+use crate::requests;
+#[cfg(any(feature = "fuel", feature = "btc"))]
+use crate::ChainId;
 use crate::{
     core::{
-        error::{Error, Result},
-        provider::{ChainProvider, Provider, StreamResponse, UniswapV2Provider, UniswapV3Provider},
+        decode::split_json_lines,
+        error::{Error, ResponseError, Result},
+        provider::{
+            ChainProvider, Cursored, Provider, ProviderConfig, ResponseStream, StreamResponse,
+        },
         requests::{
-            self, blocks::GetBlocksRequest, logs::GetLogsRequest, txs::GetTxsRequest,
-            uniswap_v2::GetPairsRequest, uniswap_v3::GetPoolsRequest,
+            balances::GetBalancesRequest, blocks::GetBlocksRequest,
+            contracts::GetContractCreationsRequest, erc721::GetErc721TransfersRequest,
+            internal_txs::GetInternalTxsRequest, logs::GetLogsRequest,
+            pending_txs::GetPendingTxsRequest, txs::GetTxsRequest,
+            withdrawals::GetWithdrawalsRequest,
         },
         types::format::Format,
     },
-    provider::{BtcProvider, CurveProvider, Erc20Provider, FuelProvider},
+    provider::{Erc20Provider, Erc721Provider, ReplayProvider},
     requests::{
-        btc::{GetBtcBlocksRequest, GetBtcTxsRequest},
         erc20::{GetErc20ApprovalsRequest, GetErc20Request, GetErc20TransferssRequest},
-        fuel::{
-            GetFuelBlocksRequest, GetFuelLogsRequest, GetFuelReceiptsRequest, GetFuelTxsRequest,
-            GetSparkOrderRequest, GetUtxoRequest,
-        },
+        receipts::GetReceiptsRequest,
         transfers::GetTransfersRequest,
     },
-    ChainId,
+};
+#[cfg(feature = "btc")]
+use crate::{
+    provider::BtcProvider,
+    requests::btc::{GetBtcBlocksRequest, GetBtcTxsRequest},
+};
+#[cfg(feature = "fuel")]
+use crate::{
+    provider::FuelProvider,
+    requests::fuel::{
+        GetFuelBlocksRequest, GetFuelLogsRequest, GetFuelReceiptsRequest, GetFuelTxsRequest,
+        GetSparkOrderRequest, GetUtxoRequest,
+    },
+};
+#[cfg(feature = "uniswap")]
+use crate::{
+    provider::{UniswapV2Provider, UniswapV3Provider},
+    requests::{uniswap_v2::GetPairsRequest, uniswap_v3::GetPoolsRequest},
 };
 
 const API_PATH: &str = "v1/api/";
 
+// the concurrency contract documented on `HttpProvider`
+const _: fn() = || {
+    fn assert_shareable<T: Clone + Send + Sync + 'static>() {}
+    assert_shareable::<HttpProvider>();
+    assert_shareable::<crate::Client<HttpProvider>>();
+};
+
+/// A provider sending every request as a separate HTTP request
+///
+/// The provider is `Clone + Send + Sync`, so a `Client<HttpProvider>` can be
+/// shared between tasks, either behind an `Arc` or by cloning it. Clones share
+/// the connection pool, the rate limit and the response cache, while the
+/// base URL is never modified after creation, so concurrent requests do not
+/// affect each other.
+#[derive(Clone)]
 pub struct HttpProvider {
     inner: reqwest::Client,
     base_url: reqwest::Url,
+    #[cfg(not(target_arch = "wasm32"))]
+    rate_limiter: Option<RateLimiter>,
+    #[cfg(all(feature = "cache", not(target_arch = "wasm32")))]
+    cache: Option<HttpCache>,
+    #[cfg(feature = "hmac")]
+    signer: Option<HmacSigner>,
 }
 
 impl HttpProvider {
@@ -41,24 +99,108 @@ impl HttpProvider {
         url: reqwest::Url,
         request: R,
         format: Format,
-    ) -> StreamResponse<Vec<u8>>
+    ) -> StreamResponse<Bytes>
     where
         R: serde::Serialize,
     {
-        let raw_data_stream = self
+        format.ensure_supported()?;
+        let request = self
             .inner
             .get(url)
             .query(&request)
             .query(&[("format", format)])
-            .send()
-            .await?
-            // .error_for_status()?
-            .bytes_stream()
-            .map_err(Error::from)
-            .map_ok(|bytes| bytes.to_vec())
-            .boxed();
+            .build()?;
+
+        #[cfg(all(feature = "cache", not(target_arch = "wasm32")))]
+        let cache = self
+            .cache
+            .as_ref()
+            .filter(|_| HttpCache::is_cacheable(request.url()))
+            .map(|cache| (cache, request.url().to_string()));
+        #[cfg(all(feature = "cache", not(target_arch = "wasm32")))]
+        if let Some(cached) = cache.as_ref().and_then(|(cache, key)| cache.get(key)) {
+            return Ok(decompress(cached, format));
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(rate_limiter) = &self.rate_limiter {
+            rate_limiter.acquire().await;
+        }
+
+        #[cfg(feature = "hmac")]
+        let request = self.sign(request)?;
+
+        let response = assert_send(self.inner.execute(request)).await?;
+        #[cfg(not(target_arch = "wasm32"))]
+        if let (Some(rate_limiter), Some(retry_after)) =
+            (&self.rate_limiter, retry_after(&response))
+        {
+            rate_limiter.back_off(retry_after);
+        }
+        // server errors are returned before streaming, so that a failover
+        // provider can tell them from a response
+        if response.status().is_server_error() {
+            return Err(server_error(response).await);
+        }
+        // .error_for_status()?
+        let is_success = response.status().is_success();
+
+        // the chunks are passed on as is, without copying them
+        let raw_data_stream =
+            ResponseStream::new(assert_send(response.bytes_stream()).map_err(Error::from));
+
+        #[cfg(all(feature = "cache", not(target_arch = "wasm32")))]
+        let raw_data_stream = match cache.filter(|_| is_success) {
+            Some((cache, key)) => cache.record(key, raw_data_stream),
+            None => raw_data_stream,
+        };
+
+        // error responses are not compressed
+        if is_success {
+            Ok(decompress(raw_data_stream, format))
+        } else {
+            Ok(raw_data_stream)
+        }
+    }
 
-        Ok(raw_data_stream)
+    /// Adds the signature headers to a request, signed right before sending
+    /// it so that the timestamp is fresh
+    #[cfg(feature = "hmac")]
+    fn sign(&self, mut request: reqwest::Request) -> Result<reqwest::Request> {
+        let Some(signer) = &self.signer else {
+            return Ok(request);
+        };
+
+        let url = request.url();
+        let headers = signer.headers(request.method().as_str(), url.path(), url.query(), b"");
+        for (name, value) in headers {
+            let value = header::HeaderValue::from_str(&value)
+                .map_err(|_| Error::Custom(format!("invalid value of header {name}").into()))?;
+            request.headers_mut().insert(name, value);
+        }
+        Ok(request)
+    }
+
+    /// Sends a request to an arbitrary path of the API
+    ///
+    /// This is an escape hatch for endpoints the server already supports, but
+    /// this crate does not model yet. `path` is resolved against
+    /// [`base_url`](Self::base_url) and `params` are encoded as the query
+    /// string. Neither is validated, so this bypasses all type safety of the
+    /// typed requests. Prefer those whenever possible.
+    pub async fn send_raw(
+        &self,
+        path: &str,
+        params: &impl serde::Serialize,
+        format: Format,
+    ) -> StreamResponse<Bytes> {
+        let url = self.url(path)?;
+        self.request(url, params, format).await
+    }
+
+    /// Returns the base URL all API requests are resolved against
+    pub fn base_url(&self) -> &reqwest::Url {
+        &self.base_url
     }
 
     fn url(&self, path: &str) -> Result<reqwest::Url> {
@@ -66,6 +208,40 @@ impl HttpProvider {
     }
 }
 
+/// Reads the body of a `5xx` response into an error
+async fn server_error(response: reqwest::Response) -> Error {
+    let status = response.status().as_u16();
+    let body = match assert_send(response.bytes()).await {
+        Ok(body) => body,
+        Err(err) => return err.into(),
+    };
+    let err = serde_json::from_slice(&body).unwrap_or_else(|_| ResponseError {
+        status,
+        error: String::from_utf8_lossy(&body).into_owned(),
+    });
+    Error::ErrorResponse(err)
+}
+
+/// Returns the `Retry-After` of a `429 Too Many Requests` response
+///
+/// Only the delay in seconds is supported, HTTP dates are ignored.
+#[cfg(not(target_arch = "wasm32"))]
+fn retry_after(response: &reqwest::Response) -> Option<Duration> {
+    if response.status() != reqwest::StatusCode::TOO_MANY_REQUESTS {
+        return None;
+    }
+
+    response
+        .headers()
+        .get(header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+        .map(Duration::from_secs)
+}
+
 const STATUS_PATH: &str = "status";
 
 #[async_trait]
@@ -76,31 +252,65 @@ impl Provider for HttpProvider {
         username: Option<String>,
         password: Option<String>,
     ) -> Result<Self> {
+        Self::try_new_with_config(ProviderConfig::new(endpoint, is_secure, username, password))
+            .await
+    }
+
+    async fn try_new_with_config(config: ProviderConfig) -> Result<Self> {
+        #[cfg(not(target_arch = "wasm32"))]
+        let skip_tls_verification = config.skips_tls_verification();
         let mut headers = reqwest::header::HeaderMap::new();
-        if let (Some(username), Some(password)) = (username, password) {
+        if let (Some(username), Some(password)) = (config.username, config.password) {
             let auth = format!("{username}:{password}");
             let encoded = BASE64.encode(auth);
             headers.insert(
                 header::AUTHORIZATION,
-                header::HeaderValue::from_str(&format!("Basic {encoded}"))
-                    .expect("Only non-ascii chars result in an error"),
+                header::HeaderValue::from_str(&format!("Basic {encoded}")).map_err(|_| {
+                    Error::Custom("invalid credentials: cannot form auth header".into())
+                })?,
             );
         }
 
+        for (name, value) in &config.headers {
+            let name = header::HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| Error::Custom(format!("invalid header name: {name}").into()))?;
+            let value = header::HeaderValue::from_str(value)
+                .map_err(|_| Error::Custom(format!("invalid value of header {name}").into()))?;
+            headers.append(name, value);
+        }
+
         let base_url = reqwest::Url::from_str(&format!(
-            "{}://{endpoint}/{API_PATH}",
-            if is_secure { "https" } else { "http" }
+            "{}://{}/{API_PATH}",
+            if config.is_secure { "https" } else { "http" },
+            config.endpoint,
         ))?;
 
-        let inner = reqwest::ClientBuilder::new()
-            .default_headers(headers)
-            .https_only(is_secure)
-            .build()?;
-
-        Ok(Self { inner, base_url })
+        let inner = reqwest::ClientBuilder::new().default_headers(headers);
+        // the browser controls the user agent and TLS of the fetch API
+        #[cfg(not(target_arch = "wasm32"))]
+        let inner = inner
+            .user_agent(config.user_agent)
+            .https_only(config.is_secure)
+            .danger_accept_invalid_certs(skip_tls_verification);
+        let inner = inner.build()?;
+
+        Ok(Self {
+            inner,
+            base_url,
+            #[cfg(not(target_arch = "wasm32"))]
+            rate_limiter: config
+                .rate_limit
+                .map(|(requests_per_sec, burst)| RateLimiter::new(requests_per_sec, burst)),
+            #[cfg(all(feature = "cache", not(target_arch = "wasm32")))]
+            cache: config
+                .http_cache
+                .map(|(capacity, ttl)| HttpCache::new(capacity, ttl)),
+            #[cfg(feature = "hmac")]
+            signer: config.hmac_signer,
+        })
     }
 
-    async fn get_status_by_format(&self, format: Format) -> StreamResponse<Vec<u8>> {
+    async fn get_status_by_format(&self, format: Format) -> StreamResponse<Bytes> {
         let url = self.url(STATUS_PATH)?;
         self.request(url, (), format).await
     }
@@ -110,6 +320,46 @@ const ETHEREUM_BLOCKS_PATH: &str = "blocks";
 const ETHEREUM_LOGS_PATH: &str = "logs";
 const ETHEREUM_TRANSACTIONS_PATH: &str = "transactions";
 const ETHEREUM_TRANSFERS_PATH: &str = "transfers";
+const ETHEREUM_RECEIPTS_PATH: &str = "receipts";
+const ETHEREUM_INTERNAL_TRANSACTIONS_PATH: &str = "internal-transactions";
+const ETHEREUM_CONTRACTS_PATH: &str = "contracts";
+const ETHEREUM_WITHDRAWALS_PATH: &str = "withdrawals";
+const ETHEREUM_BALANCES_PATH: &str = "balances";
+
+/// Request parameters resuming a response after a cursor
+#[derive(serde::Serialize)]
+struct WithCursor<R> {
+    #[serde(flatten)]
+    request: R,
+    cursor: String,
+}
+
+#[async_trait]
+impl ReplayProvider for HttpProvider {
+    /// The cursor is passed as the `cursor` query parameter. HTTP responses
+    /// report no cursors, so all items are returned without one.
+    async fn replay_logs_records(
+        &self,
+        request: GetLogsRequest,
+        cursor: Option<String>,
+    ) -> StreamResponse<Cursored<Bytes>> {
+        let url = self.url(ETHEREUM_LOGS_PATH)?;
+        let stream = match cursor {
+            Some(cursor) => {
+                let request = WithCursor { request, cursor };
+                self.request(url, request, Format::JsonStream).await?
+            }
+            None => self.request(url, request, Format::JsonStream).await?,
+        };
+
+        let records = split_json_lines(stream).map_ok(|record| Cursored {
+            item: Bytes::from(record),
+            cursor: None,
+            epoch: None,
+        });
+        Ok(ResponseStream::new(records))
+    }
+}
 
 #[async_trait]
 impl ChainProvider for HttpProvider {
@@ -118,7 +368,7 @@ impl ChainProvider for HttpProvider {
         request: GetBlocksRequest,
         format: Format,
         _: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
         let url = self.url(ETHEREUM_BLOCKS_PATH)?;
         self.request(url, request, format).await
     }
@@ -128,7 +378,7 @@ impl ChainProvider for HttpProvider {
         request: GetLogsRequest,
         format: Format,
         _: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
         let url = self.url(ETHEREUM_LOGS_PATH)?;
         self.request(url, request, format).await
     }
@@ -138,7 +388,7 @@ impl ChainProvider for HttpProvider {
         request: GetTxsRequest,
         format: Format,
         _: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
         let url = self.url(ETHEREUM_TRANSACTIONS_PATH)?;
         self.request(url, request, format).await
     }
@@ -148,15 +398,76 @@ impl ChainProvider for HttpProvider {
         request: GetTransfersRequest,
         format: Format,
         _: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
         let url = self.url(ETHEREUM_TRANSFERS_PATH)?;
         self.request(url, request, format).await
     }
+
+    async fn get_receipts_by_format(
+        &self,
+        request: GetReceiptsRequest,
+        format: Format,
+        _: bool,
+    ) -> StreamResponse<Bytes> {
+        let url = self.url(ETHEREUM_RECEIPTS_PATH)?;
+        self.request(url, request, format).await
+    }
+
+    async fn get_internal_txs_by_format(
+        &self,
+        request: GetInternalTxsRequest,
+        format: Format,
+        _: bool,
+    ) -> StreamResponse<Bytes> {
+        let url = self.url(ETHEREUM_INTERNAL_TRANSACTIONS_PATH)?;
+        self.request(url, request, format).await
+    }
+
+    async fn get_contract_creations_by_format(
+        &self,
+        request: GetContractCreationsRequest,
+        format: Format,
+        _: bool,
+    ) -> StreamResponse<Bytes> {
+        let url = self.url(ETHEREUM_CONTRACTS_PATH)?;
+        self.request(url, request, format).await
+    }
+
+    async fn get_withdrawals_by_format(
+        &self,
+        request: GetWithdrawalsRequest,
+        format: Format,
+        _: bool,
+    ) -> StreamResponse<Bytes> {
+        let url = self.url(ETHEREUM_WITHDRAWALS_PATH)?;
+        self.request(url, request, format).await
+    }
+
+    async fn get_balances_by_format(
+        &self,
+        request: GetBalancesRequest,
+        format: Format,
+        _: bool,
+    ) -> StreamResponse<Bytes> {
+        let url = self.url(ETHEREUM_BALANCES_PATH)?;
+        self.request(url, request, format).await
+    }
+
+    async fn get_pending_txs_by_format(
+        &self,
+        _: GetPendingTxsRequest,
+        _: Format,
+    ) -> StreamResponse<Bytes> {
+        Err(Error::InvalidRequest("pending txs require WebSocket"))
+    }
 }
 
+#[cfg(feature = "uniswap")]
 const UNISWAP_V2_PAIRS_PATH: &str = "uniswap/v2/pairs";
+#[cfg(feature = "uniswap")]
 const UNISWAP_V2_PRICES_PATH: &str = "uniswap/v2/prices";
 
+#[cfg(feature = "uniswap")]
 #[async_trait]
 impl UniswapV2Provider for HttpProvider {
     async fn get_pairs_by_format(
@@ -164,7 +475,7 @@ impl UniswapV2Provider for HttpProvider {
         request: GetPairsRequest,
         format: Format,
         _: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
         let url = self.url(UNISWAP_V2_PAIRS_PATH)?;
         self.request(url, request, format).await
     }
@@ -174,15 +485,18 @@ impl UniswapV2Provider for HttpProvider {
         request: requests::uniswap_v2::GetPricesRequest,
         format: Format,
         _: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
         let url = self.url(UNISWAP_V2_PRICES_PATH)?;
         self.request(url, request, format).await
     }
 }
 
+#[cfg(feature = "uniswap")]
 const UNISWAP_V3_POOLS_PATH: &str = "uniswap/v3/pools";
+#[cfg(feature = "uniswap")]
 const UNISWAP_V3_PRICES_PATH: &str = "uniswap/v3/prices";
 
+#[cfg(feature = "uniswap")]
 #[async_trait]
 impl UniswapV3Provider for HttpProvider {
     async fn get_pools_by_format(
@@ -190,7 +504,7 @@ impl UniswapV3Provider for HttpProvider {
         request: GetPoolsRequest,
         format: Format,
         _: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
         let url = self.url(UNISWAP_V3_POOLS_PATH)?;
         self.request(url, request, format).await
     }
@@ -200,16 +514,20 @@ impl UniswapV3Provider for HttpProvider {
         request: requests::uniswap_v3::GetPricesRequest,
         format: Format,
         _: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
         let url = self.url(UNISWAP_V3_PRICES_PATH)?;
         self.request(url, request, format).await
     }
 }
 
+#[cfg(feature = "curve")]
 const CURVE_TOKENS_PATH: &str = "curve/tokens";
+#[cfg(feature = "curve")]
 const CURVE_POOLS_PATH: &str = "curve/pools";
+#[cfg(feature = "curve")]
 const CURVE_PRICES_PATH: &str = "curve/prices";
 
+#[cfg(feature = "curve")]
 #[async_trait]
 impl CurveProvider for HttpProvider {
     async fn get_tokens_by_format(
@@ -217,7 +535,7 @@ impl CurveProvider for HttpProvider {
         request: requests::curve::GetCrvTokenRequest,
         format: Format,
         _: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
         let url = self.url(CURVE_TOKENS_PATH)?;
         self.request(url, request, format).await
     }
@@ -227,7 +545,7 @@ impl CurveProvider for HttpProvider {
         request: requests::curve::GetCrvPoolRequest,
         format: Format,
         _: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
         let url = self.url(CURVE_POOLS_PATH)?;
         self.request(url, request, format).await
     }
@@ -237,7 +555,7 @@ impl CurveProvider for HttpProvider {
         request: requests::curve::GetCrvPriceRequest,
         format: Format,
         _: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
         let url = self.url(CURVE_PRICES_PATH)?;
         self.request(url, request, format).await
     }
@@ -254,7 +572,7 @@ impl Erc20Provider for HttpProvider {
         request: GetErc20Request,
         format: Format,
         _: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
         let url = self.url(ERC20_TOKENS_PATH)?;
         self.request(url, request, format).await
     }
@@ -264,7 +582,7 @@ impl Erc20Provider for HttpProvider {
         request: GetErc20ApprovalsRequest,
         format: Format,
         _: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
         let url = self.url(ERC20_APPROVALS_PATH)?;
         self.request(url, request, format).await
     }
@@ -274,19 +592,41 @@ impl Erc20Provider for HttpProvider {
         request: GetErc20TransferssRequest,
         format: Format,
         _: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
         let url = self.url(ERC20_TRANSFERS_PATH)?;
         self.request(url, request, format).await
     }
 }
 
+const ERC721_TRANSFERS_PATH: &str = "erc721/transfers";
+
+#[async_trait]
+impl Erc721Provider for HttpProvider {
+    async fn get_erc721_transfers_by_format(
+        &self,
+        request: GetErc721TransfersRequest,
+        format: Format,
+        _: bool,
+    ) -> StreamResponse<Bytes> {
+        let url = self.url(ERC721_TRANSFERS_PATH)?;
+        self.request(url, request, format).await
+    }
+}
+
+#[cfg(feature = "fuel")]
 const FUEL_BLOCKS_PATH: &str = "blocks";
+#[cfg(feature = "fuel")]
 const FUEL_LOGS_PATH: &str = "logs";
+#[cfg(feature = "fuel")]
 const FUEL_TRANSACTIONS_PATH: &str = "transactions";
+#[cfg(feature = "fuel")]
 const FUEL_UNSPENT_UTXOS_PATH: &str = "transactions/outputs";
+#[cfg(feature = "fuel")]
 const FUEL_RECEIPTS_PATH: &str = "receipts";
+#[cfg(feature = "fuel")]
 const FUEL_SPARK_ORDER_PATH: &str = "spark/orders";
 
+#[cfg(feature = "fuel")]
 #[async_trait]
 impl FuelProvider for HttpProvider {
     async fn get_fuel_blocks_by_format(
@@ -294,7 +634,7 @@ impl FuelProvider for HttpProvider {
         mut request: GetFuelBlocksRequest,
         format: Format,
         _: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
         request.chains = HashSet::from_iter(vec![ChainId::FUEL]);
         let url = self.url(FUEL_BLOCKS_PATH)?;
         self.request(url, request, format).await
@@ -305,7 +645,7 @@ impl FuelProvider for HttpProvider {
         mut request: GetFuelLogsRequest,
         format: Format,
         _: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
         request.chains = HashSet::from_iter(vec![ChainId::FUEL]);
         let url = self.url(FUEL_LOGS_PATH)?;
         self.request(url, request, format).await
@@ -316,7 +656,7 @@ impl FuelProvider for HttpProvider {
         mut request: GetFuelTxsRequest,
         format: Format,
         _: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
         request.chains = HashSet::from_iter(vec![ChainId::FUEL]);
         let url = self.url(FUEL_TRANSACTIONS_PATH)?;
         self.request(url, request, format).await
@@ -327,7 +667,7 @@ impl FuelProvider for HttpProvider {
         mut request: GetFuelReceiptsRequest,
         format: Format,
         _: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
         request.chains = HashSet::from_iter(vec![ChainId::FUEL]);
         let url = self.url(FUEL_RECEIPTS_PATH)?;
         self.request(url, request, format).await
@@ -338,7 +678,7 @@ impl FuelProvider for HttpProvider {
         mut request: GetUtxoRequest,
         format: Format,
         _: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
         request.chains = HashSet::from_iter(vec![ChainId::FUEL]);
         let url = self.url(FUEL_UNSPENT_UTXOS_PATH)?;
         self.request(url, request, format).await
@@ -349,15 +689,18 @@ impl FuelProvider for HttpProvider {
         mut request: GetSparkOrderRequest,
         format: Format,
         _: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
         request.chains = HashSet::from_iter(vec![ChainId::FUEL]);
         let url = self.url(FUEL_SPARK_ORDER_PATH)?;
         self.request(url, request, format).await
     }
 }
 
+#[cfg(feature = "btc")]
 const BTC_BLOCKS_PATH: &str = "blocks";
+#[cfg(feature = "btc")]
 const BTC_TRANSACTIONS_PATH: &str = "transactions";
+#[cfg(feature = "btc")]
 #[async_trait]
 impl BtcProvider for HttpProvider {
     async fn get_btc_blocks_by_format(
@@ -365,7 +708,7 @@ impl BtcProvider for HttpProvider {
         mut request: GetBtcBlocksRequest,
         format: Format,
         _deltas: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
         request.chains = HashSet::from_iter(vec![ChainId::BTC]);
         let url = self.url(BTC_BLOCKS_PATH)?;
         self.request(url, request, format).await
@@ -376,9 +719,122 @@ impl BtcProvider for HttpProvider {
         mut request: GetBtcTxsRequest,
         format: Format,
         _deltas: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
         request.chains = HashSet::from_iter(vec![ChainId::BTC]);
         let url = self.url(BTC_TRANSACTIONS_PATH)?;
         self.request(url, request, format).await
     }
 }
+
+/// Marks the futures and streams of the WASM backend of reqwest as `Send`
+///
+/// They hold JavaScript values, which are not `Send`. [`SendWrapper`] lets the
+/// provider fulfil the `Send` bounds of the provider traits, and panics
+/// instead of touching the value if it is ever polled or dropped on another
+/// thread, e.g. on wasm32 builds with threads. On any other target the value
+/// is returned unchanged.
+///
+/// [`SendWrapper`]: send_wrapper::SendWrapper
+#[cfg(target_arch = "wasm32")]
+fn assert_send<T>(value: T) -> send_wrapper::SendWrapper<T> {
+    send_wrapper::SendWrapper::new(value)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn assert_send<T>(value: T) -> T {
+    value
+}
+
+/// Decompresses the body of a successful response if it was requested in a
+/// compressed format
+#[cfg_attr(not(feature = "zstd"), allow(unused_variables))]
+fn decompress(stream: ResponseStream<Bytes>, format: Format) -> ResponseStream<Bytes> {
+    #[cfg(feature = "zstd")]
+    if let Format::ZstdJsonStream = format {
+        return crate::core::decode::decompress_zstd_stream(stream);
+    }
+    stream
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn pending_txs_are_rejected() {
+        let config = ProviderConfig::new("localhost:8080".into(), false, None, None);
+        let provider = HttpProvider::try_new_with_config(config).await.unwrap();
+
+        let result = provider
+            .get_pending_txs_by_format(GetPendingTxsRequest::default(), Format::JsonStream)
+            .await;
+
+        assert!(matches!(result, Err(Error::InvalidRequest(_))));
+    }
+
+    #[tokio::test]
+    async fn base_url_is_the_configured_endpoint() {
+        let client = crate::ClientBuilder::default()
+            .endpoint("http://localhost:8080/")
+            .build::<HttpProvider>()
+            .await
+            .unwrap();
+
+        assert_eq!(
+            client.provider().base_url().as_str(),
+            "http://localhost:8080/v1/api/"
+        );
+    }
+
+    #[tokio::test]
+    async fn control_character_in_password_is_encoded() {
+        let config = ProviderConfig::new(
+            "localhost:8080".into(),
+            false,
+            Some("user".into()),
+            Some("pass\nword".into()),
+        );
+
+        // the credentials are Base64 encoded, so any password forms a valid
+        // header
+        assert!(HttpProvider::try_new_with_config(config).await.is_ok());
+    }
+
+    #[test]
+    fn chunks_are_passed_on_without_copying() {
+        use std::sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        };
+
+        /// Counts how often the buffer of a chunk was freed
+        struct Buffer(Vec<u8>, Arc<AtomicUsize>);
+
+        impl AsRef<[u8]> for Buffer {
+            fn as_ref(&self) -> &[u8] {
+                &self.0
+            }
+        }
+
+        impl Drop for Buffer {
+            fn drop(&mut self) {
+                self.1.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        let drops = Arc::new(AtomicUsize::new(0));
+        let chunk = Bytes::from_owner(Buffer(b"{\"number\":1}\n".to_vec(), drops.clone()));
+        let ptr = chunk.as_ptr();
+        let stream = ResponseStream::new(futures::stream::iter([Ok(chunk)]));
+
+        let chunks: Vec<_> =
+            futures::executor::block_on(decompress(stream, Format::JsonStream).try_collect())
+                .unwrap();
+
+        // the received chunk still is the original buffer
+        assert_eq!(chunks[0].as_ptr(), ptr);
+        assert_eq!(drops.load(Ordering::SeqCst), 0);
+        drop(chunks);
+        assert_eq!(drops.load(Ordering::SeqCst), 1);
+    }
+}
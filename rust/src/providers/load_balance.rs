@@ -0,0 +1,212 @@
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, SystemTime},
+};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use tracing::warn;
+
+use super::failover::is_failover_error;
+use crate::core::{
+    error::{Error, Result},
+    provider::{Cursored, Provider, ProviderConfig, ReplayProvider, StreamResponse},
+    requests::logs::GetLogsRequest,
+    types::format::Format,
+    utils::system_now,
+};
+
+/// Time an endpoint is skipped after a request to it failed
+const ENDPOINT_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// A provider that distributes requests across several endpoints
+///
+/// Every request is sent to the next endpoint in round-robin order, which
+/// spreads independent requests, e.g. the chunks of a parallel backfill,
+/// across replicas. Endpoints whose last request failed with a connection
+/// error or a server error (status 5xx) are skipped for 30 seconds, as are
+/// providers that are not [ready](Provider::ready). A failed request is
+/// retried on the next endpoint, trying every endpoint at most once.
+///
+/// Only the start of a request is balanced, the resulting stream stays on
+/// its endpoint: a WebSocket subscription lives on a single connection for
+/// its whole lifetime. The provider connects to all endpoints of
+/// [`ClientBuilder::load_balance`] when it is created, endpoints that can not
+/// be connected to then are left out.
+///
+/// [`ClientBuilder::load_balance`]: crate::ClientBuilder::load_balance
+pub struct LoadBalancedProvider<T> {
+    endpoints: Arc<[Endpoint<T>]>,
+    next: Arc<AtomicUsize>,
+}
+
+struct Endpoint<T> {
+    url: String,
+    provider: T,
+    failed_at: Mutex<Option<SystemTime>>,
+}
+
+impl<T> Endpoint<T>
+where
+    T: Provider,
+{
+    fn is_healthy(&self) -> bool {
+        let cooling_down = self.failed_at.lock().unwrap().is_some_and(|failed_at| {
+            system_now().duration_since(failed_at).unwrap_or_default() < ENDPOINT_COOLDOWN
+        });
+        !cooling_down && self.provider.ready()
+    }
+}
+
+impl<T> Clone for LoadBalancedProvider<T> {
+    fn clone(&self) -> Self {
+        Self {
+            endpoints: self.endpoints.clone(),
+            next: self.next.clone(),
+        }
+    }
+}
+
+impl<T> LoadBalancedProvider<T>
+where
+    T: Provider + Clone + Send + Sync,
+{
+    /// Returns the endpoints that requests are currently distributed across
+    pub fn healthy_endpoints(&self) -> Vec<String> {
+        self.endpoints
+            .iter()
+            .filter(|endpoint| endpoint.is_healthy())
+            .map(|endpoint| endpoint.url.clone())
+            .collect()
+    }
+
+    /// Picks the next healthy endpoint in round-robin order that was not
+    /// tried yet, or the next untried one if none is healthy
+    fn pick(&self, tried: &[bool]) -> usize {
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        let untried = (0..self.endpoints.len())
+            .map(|offset| (start + offset) % self.endpoints.len())
+            .filter(|index| !tried[*index]);
+        untried
+            .clone()
+            .find(|index| self.endpoints[*index].is_healthy())
+            .or_else(|| untried.clone().next())
+            .unwrap_or(start % self.endpoints.len())
+    }
+
+    /// Runs a request on the next endpoint, retrying it on the following
+    /// endpoints for connection and server errors
+    async fn call<F, Fut, R>(&self, request: F) -> Result<R>
+    where
+        F: Fn(T) -> Fut,
+        Fut: Future<Output = Result<R>>,
+    {
+        let mut tried = vec![false; self.endpoints.len()];
+        loop {
+            let index = self.pick(&tried);
+            tried[index] = true;
+
+            let endpoint = &self.endpoints[index];
+            match request(endpoint.provider.clone()).await {
+                Err(err) if is_failover_error(&err) => {
+                    warn!("request to {} failed: {err}", endpoint.url);
+                    *endpoint.failed_at.lock().unwrap() = Some(system_now());
+                    if tried.iter().all(|tried| *tried) {
+                        return Err(err);
+                    }
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<T> Provider for LoadBalancedProvider<T>
+where
+    T: Provider + Clone + Send + Sync,
+{
+    async fn try_new(
+        endpoint: String,
+        is_secure: bool,
+        username: Option<String>,
+        password: Option<String>,
+    ) -> Result<Self> {
+        Self::try_new_with_config(ProviderConfig::new(endpoint, is_secure, username, password))
+            .await
+    }
+
+    /// Connects to all endpoints of [`ProviderConfig::endpoints`], or to
+    /// [`ProviderConfig::endpoint`] if there are none
+    async fn try_new_with_config(config: ProviderConfig) -> Result<Self> {
+        let urls = if config.endpoints.is_empty() {
+            vec![config.endpoint.clone()]
+        } else {
+            config.endpoints.clone()
+        };
+
+        let mut endpoints = Vec::with_capacity(urls.len());
+        let mut last_error = None;
+        for url in urls {
+            let config = ProviderConfig {
+                endpoint: url.clone(),
+                ..config.clone()
+            };
+            match T::try_new_with_config(config).await {
+                Ok(provider) => endpoints.push(Endpoint {
+                    url,
+                    provider,
+                    failed_at: Mutex::new(None),
+                }),
+                Err(err) => {
+                    warn!("failed to connect to {url}: {err}");
+                    last_error = Some(err);
+                }
+            }
+        }
+        if endpoints.is_empty() {
+            return Err(last_error.unwrap_or(Error::InvalidRequest("no endpoints configured")));
+        }
+
+        Ok(Self {
+            endpoints: endpoints.into(),
+            next: Arc::new(AtomicUsize::new(0)),
+        })
+    }
+
+    async fn get_status_by_format(&self, format: Format) -> StreamResponse<Bytes> {
+        self.call(|provider| async move { provider.get_status_by_format(format).await })
+            .await
+    }
+
+    fn ready(&self) -> bool {
+        self.endpoints
+            .iter()
+            .any(|endpoint| endpoint.provider.ready())
+    }
+}
+
+#[async_trait]
+impl<T> ReplayProvider for LoadBalancedProvider<T>
+where
+    T: Provider + ReplayProvider + Clone + Send + Sync,
+{
+    async fn replay_logs_records(
+        &self,
+        request: GetLogsRequest,
+        cursor: Option<String>,
+    ) -> StreamResponse<Cursored<Bytes>> {
+        self.call(|provider| {
+            let request = request.clone();
+            let cursor = cursor.clone();
+            async move { provider.replay_logs_records(request, cursor).await }
+        })
+        .await
+    }
+}
+
+impl_dataset_traits!(LoadBalancedProvider);
@@ -1,2 +1,102 @@
+/// Implements all dataset traits for a provider that wraps other providers,
+/// by running every method on a wrapped provider through its `call` method
+macro_rules! impl_dataset_traits {
+    ($wrapper:ident) => {
+        impl_dataset_trait!($wrapper, ChainProvider {
+            get_blocks_by_format(blocks::GetBlocksRequest, deltas);
+            get_logs_by_format(logs::GetLogsRequest, deltas);
+            get_txs_by_format(txs::GetTxsRequest, deltas);
+            get_transfers_by_format(transfers::GetTransfersRequest, deltas);
+            get_receipts_by_format(receipts::GetReceiptsRequest, deltas);
+            get_internal_txs_by_format(internal_txs::GetInternalTxsRequest, deltas);
+            get_contract_creations_by_format(contracts::GetContractCreationsRequest, deltas);
+            get_withdrawals_by_format(withdrawals::GetWithdrawalsRequest, deltas);
+            get_balances_by_format(balances::GetBalancesRequest, deltas);
+            get_pending_txs_by_format(pending_txs::GetPendingTxsRequest);
+        });
+
+        impl_dataset_trait!($wrapper, Erc20Provider {
+            get_erc20_by_format(erc20::GetErc20Request, deltas);
+            get_erc20_approval_by_format(erc20::GetErc20ApprovalsRequest, deltas);
+            get_erc20_transfers_by_format(erc20::GetErc20TransferssRequest, deltas);
+        });
+
+        impl_dataset_trait!($wrapper, Erc721Provider {
+            get_erc721_transfers_by_format(erc721::GetErc721TransfersRequest, deltas);
+        });
+
+        #[cfg(feature = "uniswap")]
+        impl_dataset_trait!($wrapper, UniswapV2Provider {
+            get_pairs_by_format(uniswap_v2::GetPairsRequest, deltas);
+            get_prices_by_format(uniswap_v2::GetPricesRequest, deltas);
+        });
+
+        #[cfg(feature = "uniswap")]
+        impl_dataset_trait!($wrapper, UniswapV3Provider {
+            get_pools_by_format(uniswap_v3::GetPoolsRequest, deltas);
+            get_prices_by_format(uniswap_v3::GetPricesRequest, deltas);
+        });
+
+        #[cfg(feature = "curve")]
+        impl_dataset_trait!($wrapper, CurveProvider {
+            get_tokens_by_format(curve::GetCrvTokenRequest, deltas);
+            get_pools_by_format(curve::GetCrvPoolRequest, deltas);
+            get_prices_by_format(curve::GetCrvPriceRequest, deltas);
+        });
+
+        #[cfg(feature = "fuel")]
+        impl_dataset_trait!($wrapper, FuelProvider {
+            get_fuel_blocks_by_format(fuel::GetFuelBlocksRequest, deltas);
+            get_fuel_logs_by_format(fuel::GetFuelLogsRequest, deltas);
+            get_fuel_txs_by_format(fuel::GetFuelTxsRequest, deltas);
+            get_fuel_receipts_by_format(fuel::GetFuelReceiptsRequest, deltas);
+            get_fuel_unspent_utxos_by_format(fuel::GetUtxoRequest, deltas);
+            get_fuel_spark_orders_by_format(fuel::GetSparkOrderRequest, deltas);
+        });
+
+        #[cfg(feature = "btc")]
+        impl_dataset_trait!($wrapper, BtcProvider {
+            get_btc_blocks_by_format(btc::GetBtcBlocksRequest, deltas);
+            get_btc_txs_by_format(btc::GetBtcTxsRequest, deltas);
+        });
+    };
+}
+
+macro_rules! impl_dataset_trait {
+    (
+        $wrapper:ident,
+        $trait:ident { $($method:ident($($request:ident)::+ $(, $deltas:ident)?);)+ }
+    ) => {
+        #[async_trait::async_trait]
+        impl<T> $crate::provider::$trait for $wrapper<T>
+        where
+            T: $crate::provider::Provider + $crate::provider::$trait + Clone + Send + Sync,
+        {
+            $(
+                async fn $method(
+                    &self,
+                    request: $crate::requests::$($request)::+,
+                    format: $crate::Format,
+                    $($deltas: bool,)?
+                ) -> $crate::provider::StreamResponse<bytes::Bytes> {
+                    self.call(|provider| {
+                        let request = request.clone();
+                        async move { provider.$method(request, format, $($deltas)?).await }
+                    })
+                    .await
+                }
+            )+
+        }
+    };
+}
+
+#[cfg(all(feature = "cache", not(target_arch = "wasm32")))]
+mod cache;
+pub mod failover;
+#[cfg(feature = "http")]
 pub mod http;
+pub mod load_balance;
+#[cfg(all(feature = "ws", not(target_arch = "wasm32")))]
+pub mod subscriptions;
+#[cfg(all(feature = "ws", not(target_arch = "wasm32")))]
 pub mod ws;
@@ -0,0 +1,164 @@
+use std::{
+    collections::HashMap,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+
+use bytes::Bytes;
+use futures::{
+    stream::{AbortHandle, Abortable, BoxStream, SelectAll},
+    Sink, Stream, StreamExt,
+};
+use uuid::Uuid;
+
+use super::ws::{Operation, WsProvider};
+use crate::core::{
+    error::{CloseReason, Error, Result},
+    types::format::Format,
+};
+
+/// A command changing the subscriptions of a [`SubscriptionManager`]
+#[derive(Clone, Debug)]
+pub enum SubscribeCommand {
+    /// Starts a subscription, its events are tagged with `id`
+    ///
+    /// Reusing the id of a running subscription replaces it.
+    Subscribe {
+        id: Uuid,
+        operation: Box<Operation>,
+        format: Format,
+        deltas: bool,
+    },
+    /// Stops the subscription with the given id, unknown ids are ignored
+    Unsubscribe(Uuid),
+}
+
+/// Multiplexes a changing set of subscriptions over the connection of a
+/// [`WsProvider`]
+///
+/// Subscriptions are added and removed by sending [`SubscribeCommand`]s into
+/// the manager as a [`Sink`], while the events of all subscriptions are read
+/// from it as a [`Stream`] of `(id, data)` pairs. Use [`StreamExt::split`] to
+/// drive both halves from different tasks. The stream stays pending while
+/// there are no subscriptions instead of ending.
+///
+/// The commands map onto the state of the WebSocket worker as follows:
+/// - `Subscribe` registers the operation with the worker under the given id,
+///   which keys the response channel, the request stored to re-subscribe on
+///   reconnect, the resume cursor and the sequence counters.
+/// - `Unsubscribe` drops the response channel of the subscription. The worker
+///   then removes all state of the id and asks the server to stop serving
+///   it with an `unsubscribe` operation. The last event of the id is
+///   an [`Error::StreamClosed`] with [`CloseReason::Unsubscribed`], no
+///   further events are yielded for it.
+///
+/// Subscriptions ended by the server yield no terminal error when they
+/// completed, and an [`Error::StreamClosed`] with the reason otherwise.
+///
+/// Unlike the typed request methods, subscribing through the manager does
+/// not wait for the rate limit of the provider.
+pub struct SubscriptionManager {
+    provider: WsProvider,
+    subscriptions: HashMap<Uuid, AbortHandle>,
+    events: SelectAll<BoxStream<'static, (Uuid, Result<Bytes>)>>,
+    waker: Option<Waker>,
+}
+
+impl SubscriptionManager {
+    /// Creates a manager without any subscriptions
+    pub fn new(provider: WsProvider) -> Self {
+        Self {
+            provider,
+            subscriptions: HashMap::new(),
+            events: SelectAll::new(),
+            waker: None,
+        }
+    }
+
+    fn subscribe(
+        &mut self,
+        id: Uuid,
+        operation: Operation,
+        format: Format,
+        deltas: bool,
+    ) -> Result<()> {
+        // a replaced subscription ends silently, its id lives on
+        if let Some(handle) = self.subscriptions.remove(&id) {
+            handle.abort();
+        }
+
+        let stream = self.provider.subscribe(id, operation, format, deltas)?;
+        let (handle, registration) = AbortHandle::new_pair();
+        let stream = Abortable::new(stream, registration).map(move |data| (id, data));
+
+        self.subscriptions.insert(id, handle);
+        self.events.push(stream.boxed());
+        // new subscriptions are not polled until the reading task is woken
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+
+        Ok(())
+    }
+
+    fn unsubscribe(&mut self, id: Uuid) {
+        if let Some(handle) = self.subscriptions.remove(&id) {
+            handle.abort();
+            let closed = (id, Err(Error::StreamClosed(CloseReason::Unsubscribed)));
+            self.events
+                .push(futures::stream::once(async move { closed }).boxed());
+            if let Some(waker) = self.waker.take() {
+                waker.wake();
+            }
+        }
+    }
+}
+
+impl Sink<SubscribeCommand> for SubscriptionManager {
+    type Error = Error;
+
+    fn poll_ready(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<()>> {
+        if self.provider.ready() {
+            Poll::Ready(Ok(()))
+        } else {
+            Poll::Ready(Err(Error::BackendShutDown))
+        }
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, command: SubscribeCommand) -> Result<()> {
+        match command {
+            SubscribeCommand::Subscribe {
+                id,
+                operation,
+                format,
+                deltas,
+            } => self.subscribe(id, *operation, format, deltas),
+            SubscribeCommand::Unsubscribe(id) => {
+                self.unsubscribe(id);
+                Ok(())
+            }
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl Stream for SubscriptionManager {
+    type Item = (Uuid, Result<Bytes>);
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        match self.events.poll_next_unpin(cx) {
+            Poll::Ready(Some(event)) => Poll::Ready(Some(event)),
+            Poll::Ready(None) | Poll::Pending => {
+                self.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
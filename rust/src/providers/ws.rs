@@ -1,94 +1,250 @@
 use std::{
     collections::{HashMap, HashSet},
     fmt::Debug,
+    pin::Pin,
+    task::{Context, Poll},
     time::Duration,
 };
 
 use async_trait::async_trait;
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use bytes::Bytes;
 use futures::{
-    channel::mpsc, select_biased, stream::Fuse, FutureExt, SinkExt, StreamExt, TryStreamExt,
+    channel::mpsc, select_biased, stream::Fuse, FutureExt, SinkExt, Stream, StreamExt, TryStreamExt,
 };
 use http::header;
 use serde::{Deserialize, Serialize};
-use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+use tokio::{net::TcpStream, sync::broadcast};
+use tokio_tungstenite::{
+    client_async_tls_with_config, connect_async_tls_with_config, Connector, MaybeTlsStream,
+    WebSocketStream,
+};
 use tracing::{debug, error, warn};
-use tungstenite::{client::IntoClientRequest, Message};
+use tungstenite::{
+    client::IntoClientRequest,
+    protocol::{frame::coding::CloseCode, CloseFrame, WebSocketConfig},
+    Message,
+};
 use uuid::Uuid;
 
+#[cfg(feature = "hmac")]
+use crate::core::auth::HmacSigner;
+#[cfg(feature = "zstd")]
+use crate::core::decode::ZstdStreamDecoder;
+#[cfg(feature = "curve")]
+use crate::provider::CurveProvider;
 use crate::{
     core::{
-        error::{Error, ResponseError, Result},
-        provider::{ChainProvider, Provider, StreamResponse, UniswapV2Provider, UniswapV3Provider},
-        types::format::Format,
+        error::{CloseReason, Error, ResponseError, Result},
+        provider::{
+            ChainProvider, Cursored, IdStrategy, Provider, ProviderConfig, ResponseStream,
+            StreamResponse,
+        },
+        rate_limit::RateLimiter,
+        types::{format::Format, status::Status},
     },
-    provider::{BtcProvider, CurveProvider, Erc20Provider, FuelProvider},
+    provider::{Erc20Provider, Erc721Provider, ReplayProvider},
+    query::Bound,
     requests::{
         self,
+        balances::GetBalancesRequest,
         blocks::GetBlocksRequest,
-        btc::{GetBtcBlocksRequest, GetBtcTxsRequest},
+        contracts::GetContractCreationsRequest,
         erc20::{GetErc20ApprovalsRequest, GetErc20Request, GetErc20TransferssRequest},
-        fuel::{
-            GetFuelBlocksRequest, GetFuelLogsRequest, GetFuelReceiptsRequest, GetFuelTxsRequest,
-            GetSparkOrderRequest, GetUtxoRequest,
-        },
+        erc721::GetErc721TransfersRequest,
+        internal_txs::GetInternalTxsRequest,
         logs::GetLogsRequest,
+        pending_txs::GetPendingTxsRequest,
+        receipts::GetReceiptsRequest,
         transfers::GetTransfersRequest,
         txs::GetTxsRequest,
+        withdrawals::GetWithdrawalsRequest,
+    },
+    ChainId,
+};
+#[cfg(feature = "btc")]
+use crate::{
+    provider::BtcProvider,
+    requests::btc::{GetBtcBlocksRequest, GetBtcTxsRequest},
+};
+#[cfg(feature = "fuel")]
+use crate::{
+    provider::FuelProvider,
+    requests::fuel::{
+        GetFuelBlocksRequest, GetFuelLogsRequest, GetFuelReceiptsRequest, GetFuelTxsRequest,
+        GetSparkOrderRequest, GetUtxoRequest,
+    },
+};
+#[cfg(feature = "uniswap")]
+use crate::{
+    provider::{UniswapV2Provider, UniswapV3Provider},
+    requests::{
         uniswap_v2::{GetPairsRequest, GetPricesRequest as GetUniswapV2PricesRequest},
         uniswap_v3::{GetPoolsRequest, GetPricesRequest as GetUniswapV3PricesRequest},
     },
-    ChainId,
 };
 
 const WS_PATH: &str = "v1/websocket";
+/// Time after which a status request without an end is abandoned
+const STATUS_REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+/// Events kept for receivers of [`WsProvider::events`] that fall behind
+const EVENTS_CAPACITY: usize = 64;
 
-type WsResult = Result<Vec<u8>>;
+type WsResult = Result<Cursored<Bytes>>;
 type OperationMsg = (
     Uuid,
     Operation,
     Format,
     bool,
+    Option<String>,
     mpsc::UnboundedSender<WsResult>,
 );
 
 #[derive(Clone, Debug)]
 pub struct WsProvider {
     operations: mpsc::UnboundedSender<OperationMsg>,
+    unsubscribes: mpsc::UnboundedSender<Uuid>,
+    events: broadcast::Sender<StreamEvent>,
+    endpoint: String,
+    rate_limiter: Option<RateLimiter>,
+    id_strategy: IdStrategy,
 }
 
 impl WsProvider {
+    /// Returns a receiver of the events of all subscriptions of this
+    /// provider, sent from the moment of the call
+    ///
+    /// Events are delivered alongside the response streams, which continue
+    /// unchanged. A receiver that falls more than a few events behind skips
+    /// the oldest ones.
+    pub fn events(&self) -> broadcast::Receiver<StreamEvent> {
+        self.events.subscribe()
+    }
+
     async fn request(
         &self,
         operation: Operation,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
+        let id = match self.id_strategy {
+            IdStrategy::Random => Uuid::new_v4(),
+            IdStrategy::Deterministic => deterministic_id(&operation, format, deltas)?,
+        };
+
+        self.request_with_id(id, operation, format, deltas).await
+    }
+
+    /// Sends an operation under the given id
+    ///
+    /// The id identifies the subscription towards the server, a stable id,
+    /// e.g. derived from the query, allows the server to recognize a
+    /// re-established subscription. Ids have to be unique per provider: the
+    /// stream of an operation sent with the id of a running subscription
+    /// yields an [`Error::InvalidRequest`] and the running subscription is
+    /// left untouched. The id can be reused once the stream of the previous
+    /// subscription ended or was dropped.
+    pub async fn request_with_id(
+        &self,
+        id: Uuid,
+        operation: Operation,
+        format: Format,
+        deltas: bool,
+    ) -> StreamResponse<Bytes> {
+        if let Some(rate_limiter) = &self.rate_limiter {
+            rate_limiter.acquire().await;
+        }
+
+        self.subscribe(id, operation, format, deltas)
+    }
+
+    /// Hands the operation to the worker and returns its response stream
+    pub(super) fn subscribe(
+        &self,
+        id: Uuid,
+        operation: Operation,
+        format: Format,
+        deltas: bool,
+    ) -> StreamResponse<Bytes> {
+        let stream = self
+            .subscribe_from_cursor(id, operation, format, deltas, None)?
+            .map_ok(|data| data.item);
+        Ok(ResponseStream::new(stream))
+    }
+
+    /// Hands the operation to the worker, resuming right after `cursor` if
+    /// given, and returns its response stream along with the cursors
+    fn subscribe_from_cursor(
+        &self,
+        id: Uuid,
+        operation: Operation,
+        format: Format,
+        deltas: bool,
+        cursor: Option<String>,
+    ) -> StreamResponse<Cursored<Bytes>> {
+        format.ensure_supported()?;
         let (sink, stream) = mpsc::unbounded();
-        let id = Uuid::new_v4();
 
         self.operations
-            .unbounded_send((id, operation, format, deltas, sink))
+            .unbounded_send((id, operation, format, deltas, cursor, sink))
             .map_err(|_| Error::BackendShutDown)?;
 
-        let stream = stream
-            .map_err(Error::from)
-            .filter_map(|data| async {
-                match data {
-                    Ok(data) if !data.is_empty() => Some(Ok(data)),
-                    Ok(_) => None,
-                    Err(e) => Some(Err(e)),
-                }
-            })
-            .boxed();
+        let stream = SubscriptionStream {
+            id,
+            receiver: stream,
+            unsubscribes: self.unsubscribes.clone(),
+        };
+        let stream = stream.filter_map(|data| async {
+            match data {
+                Ok(data) if !data.item.is_empty() => Some(Ok(data)),
+                Ok(_) => None,
+                Err(e) => Some(Err(e)),
+            }
+        });
+
+        Ok(ResponseStream::new(stream))
+    }
+
+    /// Sends an arbitrary operation to the server
+    ///
+    /// This is an escape hatch for operations the server already supports, but
+    /// this crate does not model yet. `operation` is used as the operation tag
+    /// and the fields of `params`, which has to be a JSON object or null, are
+    /// sent alongside it. Neither is validated, so this bypasses all type
+    /// safety of the typed requests. Prefer those whenever possible.
+    pub async fn send_raw(
+        &self,
+        operation: &str,
+        params: serde_json::Value,
+        format: Format,
+        deltas: bool,
+    ) -> StreamResponse<Bytes> {
+        let params = match params {
+            serde_json::Value::Object(params) => params,
+            serde_json::Value::Null => serde_json::Map::new(),
+            _ => {
+                return Err(Error::Custom(
+                    "the params of a raw operation have to be a JSON object".into(),
+                ))
+            }
+        };
+        let operation = Operation::Raw {
+            operation: operation.to_owned(),
+            params,
+        };
 
-        Ok(stream)
+        self.request(operation, format, deltas).await
     }
 
     /// Returns true if the WS connection is active, false otherwise
     pub fn ready(&self) -> bool {
         !self.operations.is_closed()
     }
+
+    /// Returns the endpoint the WS connection was configured with
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
 }
 
 #[async_trait]
@@ -99,33 +255,129 @@ impl Provider for WsProvider {
         username: Option<String>,
         password: Option<String>,
     ) -> Result<Self> {
+        Self::try_new_with_config(ProviderConfig::new(endpoint, is_secure, username, password))
+            .await
+    }
+
+    async fn try_new_with_config(config: ProviderConfig) -> Result<Self> {
+        let skip_tls_verification = config.skips_tls_verification();
+        let endpoint = config.endpoint;
         let mut req = format!(
             "{}://{endpoint}/{WS_PATH}",
-            if is_secure { "wss" } else { "ws" },
+            if config.is_secure { "wss" } else { "ws" },
         )
         .into_client_request()?;
 
-        if let (Some(username), Some(password)) = (username, password) {
+        if let (Some(username), Some(password)) = (config.username, config.password) {
             let auth = format!("{username}:{password}");
             let encoded = BASE64.encode(auth);
 
             req.headers_mut().append(
                 header::AUTHORIZATION,
-                header::HeaderValue::from_str(&format!("Basic {encoded}"))
-                    .expect("Only non-ascii chars result in an error"),
+                header::HeaderValue::from_str(&format!("Basic {encoded}")).map_err(|_| {
+                    Error::Custom("invalid credentials: cannot form auth header".into())
+                })?,
             );
         }
+        for (name, value) in &config.headers {
+            let name = header::HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| Error::Custom(format!("invalid header name: {name}").into()))?;
+            let value = header::HeaderValue::from_str(value)
+                .map_err(|_| Error::Custom(format!("invalid value of header {name}").into()))?;
+            req.headers_mut().append(name, value);
+        }
+        req.headers_mut().insert(
+            header::USER_AGENT,
+            header::HeaderValue::from_str(&config.user_agent)
+                .map_err(|_| Error::Custom("invalid user agent".into()))?,
+        );
+
+        let mut ws_config = WebSocketConfig::default();
+        if let Some(max_message_size) = config.ws_max_message_size {
+            ws_config.max_message_size = Some(max_message_size);
+        }
+        if let Some(max_frame_size) = config.ws_max_frame_size {
+            ws_config.max_frame_size = Some(max_frame_size);
+        }
+
+        let connector = if skip_tls_verification {
+            let connector = native_tls::TlsConnector::builder()
+                .danger_accept_invalid_certs(true)
+                .build()
+                .map_err(|e| Error::Custom(format!("failed to build TLS connector: {e}").into()))?;
+            Some(Connector::NativeTls(connector))
+        } else {
+            None
+        };
+
+        #[cfg(feature = "hmac")]
+        if let Some(signer) = &config.hmac_signer {
+            sign_handshake(&mut req, signer)?;
+        }
 
         let (sink, stream) = mpsc::unbounded();
-        let bw = BackgroundWorker::new(req, stream).await?;
-        tokio::spawn(bw.main_loop());
+        let (unsubscribes, dropped) = mpsc::unbounded();
+        let (events, _) = broadcast::channel(EVENTS_CAPACITY);
+        let mut bw = BackgroundWorker::new(
+            req,
+            ws_config,
+            connector,
+            config.ws_connect_timeout,
+            stream,
+            dropped,
+        )
+        .await?;
+        bw.idle_timeout = config.ws_idle_timeout;
+        bw.max_missed_pongs = config.ws_max_missed_pongs;
+        bw.batch_window = config.ws_batch_window;
+        bw.events = events.clone();
+        #[cfg(feature = "hmac")]
+        {
+            bw.signer = config.hmac_signer;
+        }
+        config.spawner.spawn(bw.main_loop());
 
-        Ok(Self { operations: sink })
+        Ok(Self {
+            operations: sink,
+            unsubscribes,
+            events,
+            endpoint,
+            rate_limiter: config
+                .rate_limit
+                .map(|(requests_per_sec, burst)| RateLimiter::new(requests_per_sec, burst)),
+            id_strategy: config.id_strategy,
+        })
     }
 
-    async fn get_status_by_format(&self, format: Format) -> StreamResponse<Vec<u8>> {
+    async fn get_status_by_format(&self, format: Format) -> StreamResponse<Bytes> {
         self.request(Operation::GetStatus, format, false).await
     }
+
+    fn ready(&self) -> bool {
+        WsProvider::ready(self)
+    }
+}
+
+#[async_trait]
+impl ReplayProvider for WsProvider {
+    /// The stored cursor is seeded before the subscription is sent, so the
+    /// server resumes right after it, and every frame reports a new cursor
+    async fn replay_logs_records(
+        &self,
+        request: GetLogsRequest,
+        cursor: Option<String>,
+    ) -> StreamResponse<Cursored<Bytes>> {
+        if let Some(rate_limiter) = &self.rate_limiter {
+            rate_limiter.acquire().await;
+        }
+
+        let operation = Operation::GetLogs { params: request };
+        let id = match self.id_strategy {
+            IdStrategy::Random => Uuid::new_v4(),
+            IdStrategy::Deterministic => deterministic_id(&operation, Format::JsonStream, false)?,
+        };
+        self.subscribe_from_cursor(id, operation, Format::JsonStream, false, cursor)
+    }
 }
 
 #[async_trait]
@@ -135,7 +387,7 @@ impl ChainProvider for WsProvider {
         request: GetBlocksRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
         self.request(Operation::GetBlocks { params: request }, format, deltas)
             .await
     }
@@ -145,7 +397,7 @@ impl ChainProvider for WsProvider {
         request: GetLogsRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
         self.request(Operation::GetLogs { params: request }, format, deltas)
             .await
     }
@@ -155,9 +407,15 @@ impl ChainProvider for WsProvider {
         request: GetTxsRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>> {
-        self.request(Operation::GetTxs { params: request }, format, deltas)
-            .await
+    ) -> StreamResponse<Bytes> {
+        self.request(
+            Operation::GetTxs {
+                params: Box::new(request),
+            },
+            format,
+            deltas,
+        )
+        .await
     }
 
     async fn get_transfers_by_format(
@@ -165,12 +423,84 @@ impl ChainProvider for WsProvider {
         request: GetTransfersRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
         self.request(Operation::GetTransfers { params: request }, format, deltas)
             .await
     }
+
+    async fn get_receipts_by_format(
+        &self,
+        request: GetReceiptsRequest,
+        format: Format,
+        deltas: bool,
+    ) -> StreamResponse<Bytes> {
+        self.request(Operation::GetReceipts { params: request }, format, deltas)
+            .await
+    }
+
+    async fn get_internal_txs_by_format(
+        &self,
+        request: GetInternalTxsRequest,
+        format: Format,
+        deltas: bool,
+    ) -> StreamResponse<Bytes> {
+        self.request(
+            Operation::GetInternalTxs { params: request },
+            format,
+            deltas,
+        )
+        .await
+    }
+
+    async fn get_contract_creations_by_format(
+        &self,
+        request: GetContractCreationsRequest,
+        format: Format,
+        deltas: bool,
+    ) -> StreamResponse<Bytes> {
+        self.request(
+            Operation::GetContractCreations { params: request },
+            format,
+            deltas,
+        )
+        .await
+    }
+
+    async fn get_withdrawals_by_format(
+        &self,
+        request: GetWithdrawalsRequest,
+        format: Format,
+        deltas: bool,
+    ) -> StreamResponse<Bytes> {
+        self.request(
+            Operation::GetWithdrawals { params: request },
+            format,
+            deltas,
+        )
+        .await
+    }
+
+    async fn get_balances_by_format(
+        &self,
+        request: GetBalancesRequest,
+        format: Format,
+        deltas: bool,
+    ) -> StreamResponse<Bytes> {
+        self.request(Operation::GetBalances { params: request }, format, deltas)
+            .await
+    }
+
+    async fn get_pending_txs_by_format(
+        &self,
+        request: GetPendingTxsRequest,
+        format: Format,
+    ) -> StreamResponse<Bytes> {
+        self.request(Operation::GetPendingTxs { params: request }, format, false)
+            .await
+    }
 }
 
+#[cfg(feature = "uniswap")]
 #[async_trait]
 impl UniswapV2Provider for WsProvider {
     async fn get_pairs_by_format(
@@ -178,7 +508,7 @@ impl UniswapV2Provider for WsProvider {
         request: GetPairsRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
         self.request(
             Operation::GetUniswapV2Pairs { params: request },
             format,
@@ -192,9 +522,11 @@ impl UniswapV2Provider for WsProvider {
         request: requests::uniswap_v2::GetPricesRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
         self.request(
-            Operation::GetUniswapV2Prices { params: request },
+            Operation::GetUniswapV2Prices {
+                params: Box::new(request),
+            },
             format,
             deltas,
         )
@@ -202,6 +534,7 @@ impl UniswapV2Provider for WsProvider {
     }
 }
 
+#[cfg(feature = "uniswap")]
 #[async_trait]
 impl UniswapV3Provider for WsProvider {
     async fn get_pools_by_format(
@@ -209,7 +542,7 @@ impl UniswapV3Provider for WsProvider {
         request: GetPoolsRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
         self.request(
             Operation::GetUniswapV3Pools { params: request },
             format,
@@ -223,9 +556,11 @@ impl UniswapV3Provider for WsProvider {
         request: requests::uniswap_v3::GetPricesRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
         self.request(
-            Operation::GetUniswapV3Prices { params: request },
+            Operation::GetUniswapV3Prices {
+                params: Box::new(request),
+            },
             format,
             deltas,
         )
@@ -233,6 +568,7 @@ impl UniswapV3Provider for WsProvider {
     }
 }
 
+#[cfg(feature = "curve")]
 #[async_trait]
 impl CurveProvider for WsProvider {
     async fn get_tokens_by_format(
@@ -240,7 +576,7 @@ impl CurveProvider for WsProvider {
         request: requests::curve::GetCrvTokenRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
         self.request(
             Operation::GetCurveTokens { params: request },
             format,
@@ -254,9 +590,15 @@ impl CurveProvider for WsProvider {
         request: requests::curve::GetCrvPoolRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>> {
-        self.request(Operation::GetCurvePools { params: request }, format, deltas)
-            .await
+    ) -> StreamResponse<Bytes> {
+        self.request(
+            Operation::GetCurvePools {
+                params: Box::new(request),
+            },
+            format,
+            deltas,
+        )
+        .await
     }
 
     async fn get_prices_by_format(
@@ -264,9 +606,11 @@ impl CurveProvider for WsProvider {
         request: requests::curve::GetCrvPriceRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
         self.request(
-            Operation::GetCurvePrices { params: request },
+            Operation::GetCurvePrices {
+                params: Box::new(request),
+            },
             format,
             deltas,
         )
@@ -281,7 +625,7 @@ impl Erc20Provider for WsProvider {
         request: GetErc20Request,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
         self.request(Operation::GetErc20 { params: request }, format, deltas)
             .await
     }
@@ -291,7 +635,7 @@ impl Erc20Provider for WsProvider {
         request: GetErc20ApprovalsRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
         self.request(
             Operation::GetErc20Approvals { params: request },
             format,
@@ -305,7 +649,7 @@ impl Erc20Provider for WsProvider {
         request: GetErc20TransferssRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
         self.request(
             Operation::GetErc20Transfers { params: request },
             format,
@@ -315,6 +659,24 @@ impl Erc20Provider for WsProvider {
     }
 }
 
+#[async_trait]
+impl Erc721Provider for WsProvider {
+    async fn get_erc721_transfers_by_format(
+        &self,
+        request: GetErc721TransfersRequest,
+        format: Format,
+        deltas: bool,
+    ) -> StreamResponse<Bytes> {
+        self.request(
+            Operation::GetErc721Transfers { params: request },
+            format,
+            deltas,
+        )
+        .await
+    }
+}
+
+#[cfg(feature = "fuel")]
 #[async_trait]
 impl FuelProvider for WsProvider {
     async fn get_fuel_blocks_by_format(
@@ -322,7 +684,7 @@ impl FuelProvider for WsProvider {
         mut request: GetFuelBlocksRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
         request.chains = HashSet::from_iter(vec![ChainId::FUEL]);
         self.request(Operation::GetFuelBlocks { params: request }, format, deltas)
             .await
@@ -333,7 +695,7 @@ impl FuelProvider for WsProvider {
         mut request: GetFuelLogsRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
         request.chains = HashSet::from_iter(vec![ChainId::FUEL]);
         self.request(Operation::GetFuelLogs { params: request }, format, deltas)
             .await
@@ -344,7 +706,7 @@ impl FuelProvider for WsProvider {
         mut request: GetFuelTxsRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
         request.chains = HashSet::from_iter(vec![ChainId::FUEL]);
         self.request(Operation::GetFuelTxs { params: request }, format, deltas)
             .await
@@ -355,7 +717,7 @@ impl FuelProvider for WsProvider {
         mut request: GetFuelReceiptsRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
         request.chains = HashSet::from_iter(vec![ChainId::FUEL]);
         self.request(
             Operation::GetFuelReceipts { params: request },
@@ -370,7 +732,7 @@ impl FuelProvider for WsProvider {
         mut request: GetUtxoRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
         request.chains = HashSet::from_iter(vec![ChainId::FUEL]);
         self.request(
             Operation::GetFuelUnspentUtxos { params: request },
@@ -385,13 +747,14 @@ impl FuelProvider for WsProvider {
         mut request: GetSparkOrderRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
         request.chains = HashSet::from_iter(vec![ChainId::FUEL]);
         self.request(Operation::GetSparkOrder { params: request }, format, deltas)
             .await
     }
 }
 
+#[cfg(feature = "btc")]
 #[async_trait]
 impl BtcProvider for WsProvider {
     async fn get_btc_blocks_by_format(
@@ -399,7 +762,7 @@ impl BtcProvider for WsProvider {
         mut request: GetBtcBlocksRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
         request.chains = HashSet::from_iter(vec![ChainId::BTC]);
         self.request(Operation::GetBtcBlocks { params: request }, format, deltas)
             .await
@@ -410,36 +773,178 @@ impl BtcProvider for WsProvider {
         mut request: GetBtcTxsRequest,
         format: Format,
         deltas: bool,
-    ) -> StreamResponse<Vec<u8>> {
+    ) -> StreamResponse<Bytes> {
         request.chains = HashSet::from_iter(vec![ChainId::BTC]);
         self.request(Operation::GetBtcTxs { params: request }, format, deltas)
             .await
     }
 }
 
+type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Opens a WebSocket connection for the handshake request
+///
+/// Without a connect timeout, the system resolver and the connect behavior of
+/// tungstenite are used. With a timeout, the addresses of the host are tried
+/// one after another, alternating between IPv6 and IPv4. Every attempt,
+/// including the TLS and WebSocket handshakes, is given up after
+/// `connect_timeout`, and any failure moves on to the next address, so
+/// neither an unreachable address family nor a stalled server hangs the
+/// connection.
+async fn connect(
+    request: http::Request<()>,
+    ws_config: WebSocketConfig,
+    connector: Option<Connector>,
+    connect_timeout: Option<Duration>,
+) -> Result<WsStream> {
+    let Some(connect_timeout) = connect_timeout else {
+        let (ws, _) =
+            connect_async_tls_with_config(request, Some(ws_config), false, connector).await?;
+        return Ok(ws);
+    };
+
+    let uri = request.uri();
+    let host = uri
+        .host()
+        .ok_or(Error::InvalidRequest("the endpoint has no host"))?
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .to_owned();
+    let port = uri
+        .port_u16()
+        .unwrap_or(if uri.scheme_str() == Some("wss") {
+            443
+        } else {
+            80
+        });
+
+    let (v6, v4): (Vec<_>, Vec<_>) = tokio::net::lookup_host((host.as_str(), port))
+        .await?
+        .partition(|addr| addr.is_ipv6());
+    let mut addrs = Vec::with_capacity(v6.len() + v4.len());
+    let (mut v6, mut v4) = (v6.into_iter(), v4.into_iter());
+    loop {
+        match (v6.next(), v4.next()) {
+            (None, None) => break,
+            (first, second) => addrs.extend(first.into_iter().chain(second)),
+        }
+    }
+
+    let mut last_error = None;
+    for addr in addrs {
+        let attempt = async {
+            let stream = TcpStream::connect(addr).await?;
+            let (ws, _) = client_async_tls_with_config(
+                request.clone(),
+                stream,
+                Some(ws_config),
+                connector.clone(),
+            )
+            .await?;
+            Ok::<_, Error>(ws)
+        };
+        match tokio::time::timeout(connect_timeout, attempt).await {
+            Ok(Ok(ws)) => return Ok(ws),
+            Ok(Err(err)) => {
+                warn!("Failed to connect to {addr}: {err}");
+                last_error = Some(err);
+            }
+            Err(_) => {
+                warn!("Connecting to {addr} timed out after {connect_timeout:?}");
+                last_error = Some(Error::IO(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    format!("connecting to {addr} timed out"),
+                )));
+            }
+        }
+    }
+
+    Err(last_error.unwrap_or_else(|| {
+        Error::IO(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("{host} did not resolve to any address"),
+        ))
+    }))
+}
+
 struct BackgroundWorker {
-    ws: WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>,
+    ws: WsStream,
     operations: Fuse<mpsc::UnboundedReceiver<OperationMsg>>,
-    subscriptions: HashMap<Uuid, mpsc::UnboundedSender<Result<Vec<u8>>>>,
+    // the ids of subscriptions whose stream was dropped
+    dropped: Fuse<mpsc::UnboundedReceiver<Uuid>>,
+    subscriptions: HashMap<Uuid, mpsc::UnboundedSender<WsResult>>,
     subscription_requests: HashMap<Uuid, Request>,
     subscription_cursor: HashMap<Uuid, String>,
+    subscription_sequence: HashMap<Uuid, (Option<u64>, u32)>,
+    #[cfg(feature = "zstd")]
+    subscription_decoders: HashMap<Uuid, ZstdDecoder>,
+    // the id of the pending status request and when it was sent
+    status_request: Option<(Uuid, tokio::time::Instant)>,
+    latest_heights: HashMap<ChainId, u64>,
+    // subscriptions starting relative to the latest block, which are pinned
+    // to the latest known height when re-subscribed without a cursor
+    relative_starts: HashSet<Uuid>,
     ws_server: http::Request<()>,
+    ws_config: WebSocketConfig,
+    connector: Option<Connector>,
+    connect_timeout: Option<Duration>,
+    idle_timeout: Option<Duration>,
+    last_activity: tokio::time::Instant,
+    max_missed_pongs: u32,
+    missed_pongs: u32,
+    batch_window: Option<Duration>,
+    // operations held back until the batch window closes at the deadline
+    pending_operations: Vec<OperationMsg>,
+    batch_deadline: Option<tokio::time::Instant>,
+    events: broadcast::Sender<StreamEvent>,
+    #[cfg(feature = "hmac")]
+    signer: Option<HmacSigner>,
 }
 
 impl BackgroundWorker {
     pub async fn new(
         ws_server: http::Request<()>,
+        ws_config: WebSocketConfig,
+        connector: Option<Connector>,
+        connect_timeout: Option<Duration>,
         operations: mpsc::UnboundedReceiver<OperationMsg>,
+        dropped: mpsc::UnboundedReceiver<Uuid>,
     ) -> Result<Self> {
-        let (ws, _) = connect_async(ws_server.clone()).await?;
+        let ws = connect(
+            ws_server.clone(),
+            ws_config,
+            connector.clone(),
+            connect_timeout,
+        )
+        .await?;
 
         Ok(Self {
             ws,
             operations: operations.fuse(),
+            dropped: dropped.fuse(),
             subscriptions: HashMap::default(),
             ws_server,
+            ws_config,
+            connector,
+            connect_timeout,
+            idle_timeout: None,
+            last_activity: tokio::time::Instant::now(),
+            max_missed_pongs: 2,
+            missed_pongs: 0,
+            batch_window: None,
+            pending_operations: Vec::new(),
+            batch_deadline: None,
+            events: broadcast::channel(EVENTS_CAPACITY).0,
+            #[cfg(feature = "hmac")]
+            signer: None,
             subscription_requests: HashMap::default(),
             subscription_cursor: HashMap::default(),
+            subscription_sequence: HashMap::default(),
+            #[cfg(feature = "zstd")]
+            subscription_decoders: HashMap::default(),
+            status_request: None,
+            latest_heights: HashMap::default(),
+            relative_starts: HashSet::default(),
         })
     }
 
@@ -449,20 +954,59 @@ impl BackgroundWorker {
         loop {
             select_biased! {
                 _ = ping_interval.tick().fuse() => {
+                    if self.missed_pongs > self.max_missed_pongs {
+                        warn!("{} pings were not answered, reconnecting", self.missed_pongs);
+                        if !self.attempt_reconnect().await {
+                            break;
+                        }
+                    }
+
                     if let Err(e) = self.ws.send(Message::Ping(vec![])).await {
                         error!("Ping failed: {:?}", e);
                     } else {
+                        self.missed_pongs += 1;
                         debug!("Sent WebSocket ping");
                     }
+                    self.refresh_status_if_needed().await;
                 }
-                operation = self.operations.select_next_some() => {
-                    if let Err(e) = self.operate(operation).await {
+                _ = until(self.idle_deadline()).fuse() => {
+                    warn!("No WebSocket activity within {:?}, reconnecting", self.idle_timeout);
+                    if !self.attempt_reconnect().await {
+                        break;
+                    }
+                }
+                id = self.dropped.select_next_some() => {
+                    self.unsubscribe(id).await;
+                }
+                _ = until(self.batch_deadline).fuse() => {
+                    self.batch_deadline = None;
+                    let operations = std::mem::take(&mut self.pending_operations);
+                    if let Err(e) = self.operate(operations).await {
                         error!("Operation error: {:?}", e);
                     }
                 }
+                operation = self.operations.select_next_some() => {
+                    let Some(batch_window) = self.batch_window else {
+                        if let Err(e) = self.operate(vec![operation]).await {
+                            error!("Operation error: {:?}", e);
+                        }
+                        continue;
+                    };
+                    // the window opens with the first operation held back
+                    self.batch_deadline
+                        .get_or_insert_with(|| tokio::time::Instant::now() + batch_window);
+                    self.pending_operations.push(operation);
+                }
                 resp = self.ws.try_next() => {
                     match resp {
+                        Ok(Some(Message::Close(frame))) => {
+                            self.last_activity = tokio::time::Instant::now();
+                            if !self.handle_close(frame).await {
+                                break;
+                            }
+                        }
                         Ok(Some(message)) => {
+                            self.last_activity = tokio::time::Instant::now();
                             if let Err(e) = self.handle(message).await {
                                 error!("Failed to handle message: {:?}", e);
                             }
@@ -483,13 +1027,49 @@ impl BackgroundWorker {
                 }
             }
         }
+
+        // subscriptions that were not closed with a more specific reason
+        self.close_subscriptions(CloseReason::ReconnectFailed);
+    }
+
+    /// Returns the handshake request of a new connection attempt
+    fn handshake(&self) -> http::Request<()> {
+        #[allow(unused_mut)]
+        let mut request = self.ws_server.clone();
+        // the headers were already validated by the first handshake
+        #[cfg(feature = "hmac")]
+        if let Some(signer) = &self.signer {
+            let _ = sign_handshake(&mut request, signer);
+        }
+        request
+    }
+
+    fn idle_deadline(&self) -> Option<tokio::time::Instant> {
+        self.idle_timeout
+            .map(|idle_timeout| self.last_activity + idle_timeout)
     }
 
     async fn attempt_reconnect(&mut self) -> bool {
         for _ in 0..100 {
-            match connect_async(self.ws_server.clone()).await {
-                Ok((new_ws, _)) => {
+            match connect(
+                self.handshake(),
+                self.ws_config,
+                self.connector.clone(),
+                self.connect_timeout,
+            )
+            .await
+            {
+                Ok(new_ws) => {
                     self.ws = new_ws;
+                    self.last_activity = tokio::time::Instant::now();
+                    self.missed_pongs = 0;
+
+                    // the server restarts its counters for every new subscription
+                    self.subscription_sequence.clear();
+                    #[cfg(feature = "zstd")]
+                    self.subscription_decoders.clear();
+                    self.status_request = None;
+                    self.prune_subscriptions();
 
                     // re-subscribe to all subscriptions
                     for (id, request) in self.subscription_requests.iter() {
@@ -500,6 +1080,10 @@ impl BackgroundWorker {
                             .cloned()
                             .unwrap_or(request.cursor.clone());
 
+                        if req.cursor.is_empty() {
+                            self.pin_relative_start(&mut req);
+                        }
+
                         let Ok(payload) = serde_json::to_vec(&req) else {
                             error!(
                                 "Failed to re-subscribe to id {:?}: failed to serialize request",
@@ -526,36 +1110,243 @@ impl BackgroundWorker {
         false
     }
 
-    async fn operate(&mut self, operation: OperationMsg) -> Result<()> {
-        let (id, operation, format, deltas, sink) = operation;
+    /// Handles a close frame sent by the server
+    ///
+    /// Closing is routine, e.g. for maintenance or idle connections, so the
+    /// connection is re-established with all subscriptions. Only a close for
+    /// a policy violation, which is used for failed authentication, is fatal
+    /// and forwarded to all subscriptions.
+    /// Returns false if the worker should shut down.
+    async fn handle_close(&mut self, frame: Option<CloseFrame<'static>>) -> bool {
+        match frame {
+            Some(frame) if frame.code == CloseCode::Policy => {
+                error!("Server rejected the connection: {}", frame.reason);
+                for (_, sink) in self.subscriptions.drain() {
+                    let _ = sink
+                        .unbounded_send(Err(Error::ConnectionRejected(frame.reason.to_string())));
+                }
+                false
+            }
+            frame => {
+                warn!("Server closed the connection ({:?}), reconnecting", frame);
+                if self.attempt_reconnect().await {
+                    return true;
+                }
+                if let Some(frame) = frame {
+                    self.close_subscriptions(CloseReason::ServerClosed {
+                        code: frame.code.into(),
+                        reason: frame.reason.to_string(),
+                    });
+                }
+                false
+            }
+        }
+    }
+
+    /// Ends all subscriptions with [`Error::StreamClosed`], so their
+    /// consumers can tell them from completed streams
+    fn close_subscriptions(&mut self, reason: CloseReason) {
+        let pending = self
+            .pending_operations
+            .drain(..)
+            .map(|operation| operation.5);
+        for sink in pending.chain(self.subscriptions.drain().map(|(_, sink)| sink)) {
+            let _ = sink.unbounded_send(Err(Error::StreamClosed(reason.clone())));
+            sink.close_channel();
+        }
+    }
+
+    /// Replaces a start bound relative to the latest block with the last
+    /// known block height
+    ///
+    /// Without a cursor, re-subscribing a request starting at `latest` would
+    /// skip all blocks produced while the connection was down. The heights
+    /// are refreshed when such a subscription is sent and with every ping
+    /// while one has not received a cursor yet, so this is best-effort: blocks produced
+    /// since the last refresh may be delivered twice, but none are skipped as
+    /// long as a refresh succeeded before the connection dropped.
+    fn pin_relative_start(&self, request: &mut Request) {
+        let Some((chains, from_block)) = request.operation.start_mut() else {
+            return;
+        };
+
+        let offset = match from_block {
+            Bound::Latest => 0,
+            Bound::FromLatest(n) => *n,
+            Bound::Exact(_) | Bound::Subscribe => return,
+        };
+
+        let height = chains
+            .iter()
+            .filter_map(|chain| self.latest_heights.get(chain))
+            .min();
+
+        if let Some(height) = height {
+            *from_block = Bound::Exact(height.saturating_sub(offset) as i64);
+        }
+    }
+
+    /// Returns whether a subscription would be pinned to the latest known
+    /// height if it was re-subscribed now, i.e. it starts relative to the
+    /// latest block and has not received a cursor yet
+    fn needs_status(&self) -> bool {
+        self.relative_starts
+            .iter()
+            .any(|id| !self.subscription_cursor.contains_key(id))
+    }
+
+    /// Refreshes the latest block heights while a subscription needs them,
+    /// see [`needs_status`](Self::needs_status)
+    async fn refresh_status_if_needed(&mut self) {
+        if !self.needs_status() {
+            return;
+        }
+        if let Err(e) = self.refresh_status().await {
+            warn!("Failed to refresh latest block heights: {:?}", e);
+        }
+    }
+
+    /// Requests the status of all services to keep track of the latest block
+    /// height per chain
+    ///
+    /// Only one request is in flight at a time. A request that did not end
+    /// within [`STATUS_REQUEST_TIMEOUT`] is abandoned and replaced, its late
+    /// frames are ignored.
+    async fn refresh_status(&mut self) -> Result<()> {
+        if let Some((id, sent)) = self.status_request {
+            if sent.elapsed() < STATUS_REQUEST_TIMEOUT {
+                return Ok(());
+            }
+            warn!("Status request {:?} did not end in time, retrying", id);
+        }
 
+        let id = Uuid::new_v4();
         let request = Request {
             id,
-            operation,
-            format,
-            deltas,
-            cursor: self
-                .subscription_cursor
-                .get(&id)
-                .cloned()
-                .unwrap_or_default(),
+            operation: Operation::GetStatus,
+            format: Format::JsonStream,
+            deltas: false,
+            cursor: String::new(),
         };
         let payload = serde_json::to_vec(&request)?;
 
-        self.subscription_requests.insert(id, request);
+        self.status_request = Some((id, tokio::time::Instant::now()));
+        self.ws.send(Message::Binary(payload)).await?;
+
+        Ok(())
+    }
+
+    fn handle_status(&mut self, kind: Kind, data: Bytes) {
+        match kind {
+            Kind::Continue => {
+                for record in split_records(data) {
+                    let Ok(status) = serde_json::from_slice::<Status>(&record) else {
+                        continue;
+                    };
+                    // keep the lowest height over all services of a chain
+                    self.latest_heights
+                        .entry(status.chain)
+                        .and_modify(|height| *height = (*height).min(status.latest_block_height))
+                        .or_insert(status.latest_block_height);
+                }
+            }
+            Kind::End | Kind::Error | Kind::ContinueWithError => self.status_request = None,
+            Kind::Start | Kind::Subscription => {}
+        }
+    }
+
+    /// Registers the operations and sends them
+    ///
+    /// A single operation is sent as a plain request, several operations are
+    /// sent together in one frame, see [`BatchRequest`].
+    async fn operate(&mut self, operations: Vec<OperationMsg>) -> Result<()> {
+        let mut requests = Vec::with_capacity(operations.len());
+        for (id, operation, format, deltas, cursor, sink) in operations {
+            // the stream was dropped while the operation was held back
+            if sink.is_closed() {
+                continue;
+            }
+            if self
+                .subscriptions
+                .get(&id)
+                .is_some_and(|running| !running.is_closed())
+            {
+                warn!(
+                    "Rejecting operation with the id of running subscription {:?}",
+                    id
+                );
+                let _ = sink.unbounded_send(Err(Error::InvalidRequest(
+                    "the id is used by a running subscription",
+                )));
+                continue;
+            }
+            // the state of a dropped subscription under the same id is stale
+            self.remove_subscription(id);
+            if let Some(cursor) = cursor {
+                self.subscription_cursor.insert(id, cursor);
+            }
+
+            let mut request = Request {
+                id,
+                operation,
+                format,
+                deltas,
+                cursor: self
+                    .subscription_cursor
+                    .get(&id)
+                    .cloned()
+                    .unwrap_or_default(),
+            };
+            let payload = match serde_json::to_vec(&request) {
+                Ok(payload) => payload,
+                Err(e) => {
+                    let _ = sink.unbounded_send(Err(Error::from(e)));
+                    continue;
+                }
+            };
+
+            if let Some((_, Bound::Latest | Bound::FromLatest(_))) = request.operation.start_mut() {
+                self.relative_starts.insert(id);
+            } else {
+                self.relative_starts.remove(&id);
+            }
+            requests.push((id, payload, request.clone()));
+            self.subscription_requests.insert(id, request);
 
-        if self.subscriptions.insert(id, sink).is_some() {
-            warn!("Replacing already-registered subscription with id {:?}", id);
+            self.subscriptions.insert(id, sink);
         }
 
+        let payload = match requests.as_slice() {
+            [] => return Ok(()),
+            [(_, payload, _)] => payload.clone(),
+            requests => {
+                let batch = BatchRequest {
+                    requests: requests.iter().map(|(_, _, request)| request).collect(),
+                };
+                // the requests were serialized one by one before
+                serde_json::to_vec(&batch)?
+            }
+        };
+
+        let ids: Vec<_> = requests.into_iter().map(|(id, _, _)| id).collect();
         if let Err(e) = self.ws.send(Message::Binary(payload)).await {
             error!("WS connection error: {:?}", e);
-            let sink = self.subscriptions.remove(&id);
-            if let Some(sink) = sink {
-                sink.close_channel();
+            // the error itself can not be cloned, so only the first
+            // subscription of a batch receives it
+            let message = format!("WS connection error: {e}");
+            let mut error = Some(Error::from(e));
+            for id in ids {
+                let error = error
+                    .take()
+                    .unwrap_or_else(|| Error::Custom(message.clone().into()));
+                self.fail_subscription(id, error);
             }
+            return Ok(());
         }
 
+        // heights are needed as soon as a relative subscription may have to
+        // be re-subscribed, not only after the next ping
+        self.refresh_status_if_needed().await;
         Ok(())
     }
 
@@ -564,9 +1355,13 @@ impl BackgroundWorker {
             Message::Text(_) => Err(Error::UnexpectedMessage),
             Message::Frame(_) => Ok(()), // Server is allowed to send Raw frames
             Message::Ping(inner) => self.handle_ping(inner).await,
-            Message::Pong(_) => Ok(()), // Server is allowed to send unsolicited pongs.
-            Message::Close(_) => Err(Error::UnexpectedClose),
-            Message::Binary(buf) => self.handle_binary(buf).await,
+            Message::Pong(_) => {
+                // Server is allowed to send unsolicited pongs.
+                self.missed_pongs = 0;
+                Ok(())
+            }
+            Message::Close(_) => Ok(()), // Handled by the main loop
+            Message::Binary(buf) => self.handle_binary(Bytes::from(buf)).await,
         }
     }
 
@@ -575,52 +1370,242 @@ impl BackgroundWorker {
         Ok(())
     }
 
-    async fn handle_binary(&mut self, data: Vec<u8>) -> Result<()> {
+    /// Handles a data frame
+    ///
+    /// The header, the payload and the records of the payload are all views
+    /// into the buffer of the frame, which is freed once the last of them was
+    /// dropped by the subscriptions.
+    async fn handle_binary(&mut self, data: Bytes) -> Result<()> {
         let (header, data) = Header::try_from_data(data)?;
         let id = header.id;
 
+        if self
+            .status_request
+            .is_some_and(|(status_id, _)| status_id == id.0)
+        {
+            if let Kind::Start = header.kind {
+                // heights of the previous refresh are replaced by this one
+                self.latest_heights.clear();
+            }
+            self.handle_status(header.kind, data);
+            return Ok(());
+        }
+
+        if let Some(gap) = self.check_sequence(&header) {
+            self.send_to_subscription(id, Err(gap))?;
+        }
+
         let msg = match header.kind {
             Kind::Start => {
                 return Ok(());
             }
             Kind::Continue => {
-                if let Some(cursor) = header.cursor {
-                    self.subscription_cursor.insert(id.0, cursor);
+                if let Some(cursor) = &header.cursor {
+                    self.subscription_cursor.insert(id.0, cursor.clone());
                 }
-                Ok(data)
-            }
-            Kind::ContinueWithError => match data.first() {
-                Some(b'{') => match serde_json::from_slice::<ResponseError>(&data) {
-                    Ok(err) => Err(Error::ErrorResponse(err)),
-                    Err(_) => Err(Error::UnexpectedMessageFormat),
-                },
-                _ => match String::from_utf8(data) {
-                    Ok(s) => Err(Error::ErrorMsg(s)),
-                    Err(_) => Err(Error::UnexpectedMessageFormat),
-                },
-            },
+
+                let format = self
+                    .subscription_requests
+                    .get(&id.0)
+                    .map(|request| request.format)
+                    .unwrap_or_default();
+
+                // a single frame may carry several newline separated records
+                if let Format::JsonStream = format {
+                    return self.send_records(id, split_records(data), header.cursor, header.epoch);
+                }
+
+                #[cfg(feature = "zstd")]
+                if let Format::ZstdJsonStream = format {
+                    let records = match self.subscription_decoders.entry(id.0) {
+                        std::collections::hash_map::Entry::Occupied(entry) => {
+                            entry.into_mut().decode(&data)
+                        }
+                        std::collections::hash_map::Entry::Vacant(entry) => ZstdDecoder::new()
+                            .and_then(|decoder| entry.insert(decoder).decode(&data)),
+                    };
+                    // the decoder state is lost, so the subscription can not continue
+                    let records = match records {
+                        Ok(records) => records,
+                        Err(e) => {
+                            self.fail_subscription(id.0, e);
+                            return Ok(());
+                        }
+                    };
+                    // the cursor is only valid if no partial record is left over
+                    let cursor = header.cursor.filter(|_| {
+                        self.subscription_decoders
+                            .get(&id.0)
+                            .is_some_and(|decoder| decoder.pending.is_empty())
+                    });
+                    return self.send_records(id, records, cursor, header.epoch);
+                }
+
+                Ok(Cursored {
+                    item: data,
+                    cursor: header.cursor,
+                    epoch: header.epoch,
+                })
+            }
+            Kind::ContinueWithError => Err(decode_error(data)),
             Kind::End => {
-                let sink = self.subscriptions.remove(&header.id.0);
-                if let Some(sink) = sink {
+                if let Some(sink) = self.remove_subscription(id.0) {
                     sink.close_channel();
                 }
                 return Ok(());
             }
-            Kind::Error => match String::from_utf8(data) {
-                Ok(s) => Err(Error::ErrorMsg(s)),
-                Err(_) => Err(Error::UnexpectedMessageFormat),
-            },
+            Kind::Error => {
+                // the operation failed and no further messages will follow
+                self.fail_subscription(id.0, decode_error(data));
+                return Ok(());
+            }
             _ => Err(Error::UnexpectedMessageFormat),
         };
 
-        if let std::collections::hash_map::Entry::Occupied(occupied) =
-            self.subscriptions.entry(id.0)
-        {
-            if let Err(err) = occupied.get().unbounded_send(msg) {
-                if err.is_disconnected() {
-                    // subscription channel was closed on the receiver end
-                    occupied.remove();
-                }
+        self.send_to_subscription(id, msg)
+    }
+
+    /// Records the counter of the received message and returns an error if
+    /// messages were skipped since the previous one of the same epoch
+    ///
+    /// A change of the epoch is no error, the server reset the stream and
+    /// continues it. Cursors are only valid within the epoch they were issued
+    /// in though, so the stored cursor of the subscription is dropped.
+    fn check_sequence(&mut self, header: &Header) -> Option<Error> {
+        let previous = self
+            .subscription_sequence
+            .insert(header.id.0, (header.epoch, header.counter));
+
+        match previous {
+            Some((epoch, _)) if epoch != header.epoch => {
+                warn!(
+                    "Epoch changed for id {:?}: {:?} -> {:?}",
+                    header.id.0, epoch, header.epoch
+                );
+                // cursors are scoped to an epoch, consumers see the change in
+                // the epoch of the following items and the stream continues
+                self.subscription_cursor.remove(&header.id.0);
+                // there may be no receiver of the events
+                let _ = self.events.send(StreamEvent::EpochChanged {
+                    id: header.id.0,
+                    from: epoch,
+                    to: header.epoch,
+                });
+                None
+            }
+            Some((epoch, counter))
+                if epoch == header.epoch && header.counter != counter.wrapping_add(1) =>
+            {
+                warn!(
+                    "Message gap detected for id {:?}: expected counter {}, received {}",
+                    header.id.0,
+                    counter.wrapping_add(1),
+                    header.counter
+                );
+                Some(Error::MessageGap {
+                    expected: counter.wrapping_add(1),
+                    received: header.counter,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Removes all state of a subscription, so it is neither served nor
+    /// re-subscribed on reconnect anymore
+    fn remove_subscription(&mut self, id: Uuid) -> Option<mpsc::UnboundedSender<WsResult>> {
+        self.subscription_requests.remove(&id);
+        self.relative_starts.remove(&id);
+        self.subscription_cursor.remove(&id);
+        self.subscription_sequence.remove(&id);
+        #[cfg(feature = "zstd")]
+        self.subscription_decoders.remove(&id);
+        self.subscriptions.remove(&id)
+    }
+
+    /// Stops a subscription whose stream was dropped, e.g. after it was
+    /// cancelled, and tells the server to stop serving it
+    ///
+    /// The id may have been taken over by a new subscription in the
+    /// meantime, which is left running.
+    async fn unsubscribe(&mut self, id: Uuid) {
+        let dropped = self
+            .subscriptions
+            .get(&id)
+            .is_some_and(|sink| sink.is_closed());
+        if !dropped {
+            return;
+        }
+
+        debug!("Unsubscribing {:?}", id);
+        self.remove_subscription(id);
+        let payload = match serde_json::to_vec(&UnsubscribeRequest { id }) {
+            Ok(payload) => payload,
+            Err(e) => {
+                error!("Failed to encode unsubscribe: {:?}", e);
+                return;
+            }
+        };
+        if let Err(e) = self.ws.send(Message::Binary(payload)).await {
+            error!("Failed to unsubscribe {:?}: {:?}", id, e);
+        }
+    }
+
+    /// Removes all subscriptions whose stream was dropped, e.g. after it was
+    /// cancelled
+    fn prune_subscriptions(&mut self) {
+        let dropped: Vec<_> = self
+            .subscriptions
+            .iter()
+            .filter(|(_, sink)| sink.is_closed())
+            .map(|(id, _)| *id)
+            .collect();
+        for id in dropped {
+            debug!("Subscription {:?} was dropped", id);
+            self.remove_subscription(id);
+        }
+    }
+
+    /// Forwards a fatal error to a single subscription and removes it,
+    /// leaving all other subscriptions untouched
+    fn fail_subscription(&mut self, id: Uuid, error: Error) {
+        warn!("Subscription {:?} failed: {:?}", id, error);
+        if let Some(sink) = self.remove_subscription(id) {
+            let _ = sink.unbounded_send(Err(error));
+            sink.close_channel();
+        }
+    }
+
+    /// Forwards the records of a frame, the cursor of the frame is attached to
+    /// its last record as resuming after it skips the whole frame
+    fn send_records(
+        &mut self,
+        id: MsgId,
+        records: Vec<Bytes>,
+        cursor: Option<String>,
+        epoch: Option<u64>,
+    ) -> Result<()> {
+        let last = records.len().saturating_sub(1);
+        let mut cursor = cursor;
+        for (i, record) in records.into_iter().enumerate() {
+            let cursor = if i == last { cursor.take() } else { None };
+            self.send_to_subscription(
+                id,
+                Ok(Cursored {
+                    item: record,
+                    cursor,
+                    epoch,
+                }),
+            )?;
+        }
+        Ok(())
+    }
+
+    fn send_to_subscription(&mut self, id: MsgId, msg: WsResult) -> Result<()> {
+        if let Some(sink) = self.subscriptions.get(&id.0) {
+            if let Err(err) = sink.unbounded_send(msg) {
+                // a closed channel means the stream was dropped, which also
+                // queued its id to be unsubscribed, so it is kept until then
                 return Err(Error::Custom(
                     format!("failed to send message: {err:?}").into(),
                 ));
@@ -631,6 +1616,156 @@ impl BackgroundWorker {
     }
 }
 
+/// Decodes the body of an error message
+///
+/// Structured errors are returned as [`Error::ErrorResponse`], the same as
+/// the HTTP provider's error bodies after [`ResponseError::map_stream`], so
+/// that errors look the same regardless of the provider.
+fn decode_error(data: Bytes) -> Error {
+    match data.first() {
+        Some(b'{') => match serde_json::from_slice::<ResponseError>(&data) {
+            Ok(err) => Error::ErrorResponse(err),
+            Err(_) => Error::UnexpectedMessageFormat,
+        },
+        _ => match String::from_utf8(data.into()) {
+            Ok(s) => Error::ErrorMsg(s),
+            Err(_) => Error::UnexpectedMessageFormat,
+        },
+    }
+}
+
+/// Derives a stable id from everything that is sent with a request except
+/// its id and cursor
+fn deterministic_id(operation: &Operation, format: Format, deltas: bool) -> Result<Uuid> {
+    let mut query = serde_json::to_vec(operation)?;
+    query.extend(serde_json::to_vec(&(format, deltas))?);
+    Ok(Uuid::new_v5(&Uuid::NAMESPACE_OID, &query))
+}
+
+/// Adds the signature headers to a handshake request
+#[cfg(feature = "hmac")]
+fn sign_handshake(request: &mut http::Request<()>, signer: &HmacSigner) -> Result<()> {
+    let uri = request.uri().clone();
+    let headers = signer.headers(request.method().as_str(), uri.path(), uri.query(), b"");
+    for (name, value) in headers {
+        let value = header::HeaderValue::from_str(&value)
+            .map_err(|_| Error::Custom(format!("invalid value of header {name}").into()))?;
+        request.headers_mut().insert(name, value);
+    }
+    Ok(())
+}
+
+/// Completes once the deadline passed, never if there is none
+async fn until(deadline: Option<tokio::time::Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline).await,
+        None => futures::future::pending().await,
+    }
+}
+
+/// Splits a JSON lines payload into its individual records, skipping empty lines
+///
+/// Every record keeps its terminating newline, so the records of consecutive
+/// items are still JSON lines when decoded together, a final record without
+/// one gets it appended. The records share the buffer of the payload instead
+/// of being copied.
+fn split_records(data: Bytes) -> Vec<Bytes> {
+    let mut records = Vec::new();
+    let mut start = 0;
+    for (end, byte) in data.iter().enumerate() {
+        if *byte == b'\n' {
+            if end > start {
+                records.push(data.slice(start..=end));
+            }
+            start = end + 1;
+        }
+    }
+    if start < data.len() {
+        let mut record = data[start..].to_vec();
+        record.push(b'\n');
+        records.push(Bytes::from(record));
+    }
+    records
+}
+
+/// A streaming zstd decoder for a single subscription
+///
+/// Frames may split a compressed block, so the decoder state and any trailing
+/// partial record are kept between frames.
+#[cfg(feature = "zstd")]
+struct ZstdDecoder {
+    inner: ZstdStreamDecoder,
+    pending: Vec<u8>,
+}
+
+#[cfg(feature = "zstd")]
+impl ZstdDecoder {
+    fn new() -> Result<Self> {
+        Ok(Self {
+            inner: ZstdStreamDecoder::new()?,
+            pending: Vec::new(),
+        })
+    }
+
+    /// Decompresses the frame and returns all records completed by it
+    fn decode(&mut self, data: &[u8]) -> Result<Vec<Bytes>> {
+        let decompressed = self.inner.decompress(data)?;
+        self.pending.extend_from_slice(&decompressed);
+
+        let Some(last_newline) = self.pending.iter().rposition(|b| *b == b'\n') else {
+            return Ok(Vec::new());
+        };
+        let rest = self.pending.split_off(last_newline + 1);
+        let complete = std::mem::replace(&mut self.pending, rest);
+
+        Ok(split_records(Bytes::from(complete)))
+    }
+}
+
+/// Several requests sent in a single frame
+///
+/// Assumes the server accepts the batch envelope
+/// `{"operation": "batch", "requests": [...]}`, where every element of
+/// `requests` is a request as it would be sent on its own. The server answers
+/// every request of the batch under its own id, as if they were sent
+/// separately.
+#[derive(serde::Serialize)]
+#[serde(tag = "operation", rename = "batch")]
+struct BatchRequest<'a> {
+    requests: Vec<&'a Request>,
+}
+
+/// Asks the server to stop serving a subscription
+#[derive(serde::Serialize)]
+#[serde(tag = "operation", rename = "unsubscribe")]
+struct UnsubscribeRequest {
+    id: Uuid,
+}
+
+/// The response channel of a subscription, which reports its drop to the
+/// worker so that the subscription is unsubscribed
+struct SubscriptionStream {
+    id: Uuid,
+    receiver: mpsc::UnboundedReceiver<WsResult>,
+    unsubscribes: mpsc::UnboundedSender<Uuid>,
+}
+
+impl Stream for SubscriptionStream {
+    type Item = WsResult;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_next_unpin(cx)
+    }
+}
+
+impl Drop for SubscriptionStream {
+    fn drop(&mut self) {
+        // closed first, so the worker sees the subscription as dropped
+        self.receiver.close();
+        let _ = self.unsubscribes.unbounded_send(self.id);
+    }
+}
+
 #[derive(Clone, serde::Serialize)]
 struct Request {
     id: Uuid,
@@ -644,9 +1779,10 @@ struct Request {
     cursor: String,
 }
 
-#[derive(Clone, serde::Serialize)]
+/// An operation of the WebSocket API together with its parameters
+#[derive(Clone, Debug, serde::Serialize)]
 #[serde(tag = "operation", rename_all = "camelCase")]
-enum Operation {
+pub enum Operation {
     GetStatus,
     GetBlocks {
         #[serde(flatten)]
@@ -658,70 +1794,88 @@ enum Operation {
     },
     GetTxs {
         #[serde(flatten)]
-        params: GetTxsRequest,
+        params: Box<GetTxsRequest>,
+    },
+    GetReceipts {
+        #[serde(flatten)]
+        params: GetReceiptsRequest,
     },
+    #[cfg(feature = "btc")]
     #[serde(rename = "getBlocks")]
     GetBtcBlocks {
         #[serde(flatten)]
         params: GetBtcBlocksRequest,
     },
+    #[cfg(feature = "btc")]
     #[serde(rename = "getTxs")]
     GetBtcTxs {
         #[serde(flatten)]
         params: GetBtcTxsRequest,
     },
+    #[cfg(feature = "fuel")]
     #[serde(rename = "getBlocks")]
     GetFuelBlocks {
         #[serde(flatten)]
         params: GetFuelBlocksRequest,
     },
+    #[cfg(feature = "fuel")]
     #[serde(rename = "getLogs")]
     GetFuelLogs {
         #[serde(flatten)]
         params: GetFuelLogsRequest,
     },
+    #[cfg(feature = "fuel")]
     #[serde(rename = "getTxs")]
     GetFuelTxs {
         #[serde(flatten)]
         params: GetFuelTxsRequest,
     },
+    #[cfg(feature = "fuel")]
     #[serde(rename = "getReceipts")]
     GetFuelReceipts {
         #[serde(flatten)]
         params: GetFuelReceiptsRequest,
     },
+    #[cfg(feature = "fuel")]
     #[serde(rename = "getUnspentUtxos")]
     GetFuelUnspentUtxos {
         #[serde(flatten)]
         params: GetUtxoRequest,
     },
+    #[cfg(feature = "uniswap")]
     GetUniswapV2Pairs {
         #[serde(flatten)]
         params: GetPairsRequest,
     },
+    #[cfg(feature = "uniswap")]
     GetUniswapV2Prices {
         #[serde(flatten)]
-        params: GetUniswapV2PricesRequest,
+        params: Box<GetUniswapV2PricesRequest>,
     },
+    #[cfg(feature = "uniswap")]
     GetUniswapV3Pools {
         #[serde(flatten)]
         params: GetPoolsRequest,
     },
+    #[cfg(feature = "uniswap")]
     GetUniswapV3Prices {
         #[serde(flatten)]
-        params: GetUniswapV3PricesRequest,
+        params: Box<GetUniswapV3PricesRequest>,
     },
+    #[cfg(feature = "curve")]
     GetCurveTokens {
         #[serde(flatten)]
         params: requests::curve::GetCrvTokenRequest,
     },
+    #[cfg(feature = "curve")]
     GetCurvePools {
         #[serde(flatten)]
-        params: requests::curve::GetCrvPoolRequest,
+        params: Box<requests::curve::GetCrvPoolRequest>,
     },
+    #[cfg(feature = "curve")]
     GetCurvePrices {
         #[serde(flatten)]
-        params: requests::curve::GetCrvPriceRequest,
+        params: Box<requests::curve::GetCrvPriceRequest>,
     },
     GetTransfers {
         #[serde(flatten)]
@@ -739,31 +1893,119 @@ enum Operation {
         #[serde(flatten)]
         params: requests::erc20::GetErc20TransferssRequest,
     },
+    #[cfg(feature = "fuel")]
     GetSparkOrder {
         #[serde(flatten)]
         params: requests::fuel::GetSparkOrderRequest,
     },
+    GetInternalTxs {
+        #[serde(flatten)]
+        params: GetInternalTxsRequest,
+    },
+    GetContractCreations {
+        #[serde(flatten)]
+        params: GetContractCreationsRequest,
+    },
+    GetWithdrawals {
+        #[serde(flatten)]
+        params: GetWithdrawalsRequest,
+    },
+    GetPendingTxs {
+        #[serde(flatten)]
+        params: GetPendingTxsRequest,
+    },
+    GetBalances {
+        #[serde(flatten)]
+        params: GetBalancesRequest,
+    },
+    GetErc721Transfers {
+        #[serde(flatten)]
+        params: GetErc721TransfersRequest,
+    },
+    /// An operation that is not modelled by this crate, see [`WsProvider::send_raw`]
+    #[serde(untagged)]
+    Raw {
+        operation: String,
+        #[serde(flatten)]
+        params: serde_json::Map<String, serde_json::Value>,
+    },
+}
+
+impl Operation {
+    /// Returns the chains and the start bound of the request, if it has any
+    fn start_mut(&mut self) -> Option<(&HashSet<ChainId>, &mut Bound)> {
+        let range = match self {
+            Self::GetStatus
+            | Self::GetPendingTxs { .. }
+            | Self::GetBalances { .. }
+            | Self::Raw { .. } => return None,
+            Self::GetBlocks { params } => (&params.chains, &mut params.from_block),
+            Self::GetLogs { params } => (&params.chains, &mut params.from_block),
+            Self::GetTxs { params } => (&params.chains, &mut params.from_block),
+            Self::GetReceipts { params } => (&params.chains, &mut params.from_block),
+            #[cfg(feature = "btc")]
+            Self::GetBtcBlocks { params } => (&params.chains, &mut params.from_block),
+            #[cfg(feature = "btc")]
+            Self::GetBtcTxs { params } => (&params.chains, &mut params.from_block),
+            #[cfg(feature = "fuel")]
+            Self::GetFuelBlocks { params } => (&params.chains, &mut params.from_block),
+            #[cfg(feature = "fuel")]
+            Self::GetFuelLogs { params } => (&params.chains, &mut params.from_block),
+            #[cfg(feature = "fuel")]
+            Self::GetFuelTxs { params } => (&params.chains, &mut params.from_block),
+            #[cfg(feature = "fuel")]
+            Self::GetFuelReceipts { params } => (&params.chains, &mut params.from_block),
+            #[cfg(feature = "fuel")]
+            Self::GetFuelUnspentUtxos { params } => (&params.chains, &mut params.from_block),
+            #[cfg(feature = "uniswap")]
+            Self::GetUniswapV2Pairs { params } => (&params.chains, &mut params.from_block),
+            #[cfg(feature = "uniswap")]
+            Self::GetUniswapV2Prices { params } => (&params.chains, &mut params.from_block),
+            #[cfg(feature = "uniswap")]
+            Self::GetUniswapV3Pools { params } => (&params.chains, &mut params.from_block),
+            #[cfg(feature = "uniswap")]
+            Self::GetUniswapV3Prices { params } => (&params.chains, &mut params.from_block),
+            #[cfg(feature = "curve")]
+            Self::GetCurveTokens { params } => (&params.chains, &mut params.from_block),
+            #[cfg(feature = "curve")]
+            Self::GetCurvePools { params } => (&params.chains, &mut params.from_block),
+            #[cfg(feature = "curve")]
+            Self::GetCurvePrices { params } => (&params.chains, &mut params.from_block),
+            Self::GetTransfers { params } => (&params.chains, &mut params.from_block),
+            Self::GetErc20 { params } => (&params.chains, &mut params.from_block),
+            Self::GetErc20Approvals { params } => (&params.chains, &mut params.from_block),
+            Self::GetErc20Transfers { params } => (&params.chains, &mut params.from_block),
+            #[cfg(feature = "fuel")]
+            Self::GetSparkOrder { params } => (&params.chains, &mut params.from_block),
+            Self::GetInternalTxs { params } => (&params.chains, &mut params.from_block),
+            Self::GetContractCreations { params } => (&params.chains, &mut params.from_block),
+            Self::GetWithdrawals { params } => (&params.chains, &mut params.from_block),
+            Self::GetErc721Transfers { params } => (&params.chains, &mut params.from_block),
+        };
+        Some(range)
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
 struct Header {
     pub kind: Kind,
     pub id: MsgId,
-    #[serde(rename = "counter")]
-    pub _counter: u32,
-    #[serde(rename = "epoch")]
-    pub _epoch: Option<u64>,
+    pub counter: u32,
+    pub epoch: Option<u64>,
     pub cursor: Option<String>,
 }
 
 impl Header {
-    fn try_from_data(mut data: Vec<u8>) -> Result<(Self, Vec<u8>)> {
+    /// Splits a frame into its header and payload, the payload shares the
+    /// buffer of the frame
+    fn try_from_data(data: Bytes) -> Result<(Self, Bytes)> {
         // seperate by new line
-        let mut split = data.splitn_mut(2, |b| *b == b'\n');
-        let header = split.next().ok_or_else(|| Error::UnexpectedMessageFormat)?;
-        let data = split.next().ok_or_else(|| Error::UnexpectedMessageFormat)?;
-        let header = serde_json::from_slice::<Header>(header)?;
-        Ok((header, data.to_vec()))
+        let newline = data
+            .iter()
+            .position(|b| *b == b'\n')
+            .ok_or(Error::UnexpectedMessageFormat)?;
+        let header = serde_json::from_slice::<Header>(&data[..newline])?;
+        Ok((header, data.slice(newline + 1..)))
     }
 }
 
@@ -780,3 +2022,1154 @@ pub enum Kind {
 /// An id describing a subscription or a response
 #[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct MsgId(pub Uuid);
+
+/// An event of a subscription, see [`WsProvider::events`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StreamEvent {
+    /// The server restarted the stream of the subscription `id` in a new
+    /// epoch, e.g. after a restart or reorganization
+    ///
+    /// Cursors are only valid within their epoch, so the cursor of the
+    /// subscription was dropped and a re-subscription starts without one.
+    EpochChanged {
+        id: Uuid,
+        from: Option<u64>,
+        to: Option<u64>,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::{net::TcpListener, sync::mpsc::UnboundedReceiver};
+
+    use super::*;
+
+    type ServerStream = WebSocketStream<TcpStream>;
+
+    /// Accepts WebSocket connections on a local port, returning the endpoint
+    /// and the accepted connections
+    async fn mock_server() -> (String, UnboundedReceiver<ServerStream>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = listener.local_addr().unwrap().to_string();
+        let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let Ok(ws) = tokio_tungstenite::accept_async(stream).await else {
+                    continue;
+                };
+                if sender.send(ws).is_err() {
+                    break;
+                }
+            }
+        });
+        (endpoint, receiver)
+    }
+
+    /// Accepts a single WebSocket connection, returning the endpoint and the
+    /// headers of its handshake
+    async fn handshake_headers() -> (String, tokio::sync::oneshot::Receiver<http::HeaderMap>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = listener.local_addr().unwrap().to_string();
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            // the error response type is defined by tungstenite
+            #[allow(clippy::result_large_err)]
+            let callback = |request: &http::Request<()>, response| {
+                let _ = sender.send(request.headers().clone());
+                Ok(response)
+            };
+            let _ws = tokio_tungstenite::accept_hdr_async(stream, callback)
+                .await
+                .unwrap();
+            std::future::pending::<()>().await;
+        });
+        (endpoint, receiver)
+    }
+
+    /// Accepts a single WebSocket connection and refuses all later ones,
+    /// so that reconnecting fails
+    async fn single_connection() -> (String, tokio::sync::oneshot::Receiver<ServerStream>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = listener.local_addr().unwrap().to_string();
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            drop(listener);
+            let ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            let _ = sender.send(ws);
+        });
+        (endpoint, receiver)
+    }
+
+    async fn connect(endpoint: String) -> WsProvider {
+        WsProvider::try_new_with_config(ProviderConfig::new(endpoint, false, None, None))
+            .await
+            .unwrap()
+    }
+
+    /// Returns the next request a client sent
+    async fn next_request(ws: &mut ServerStream) -> serde_json::Value {
+        loop {
+            match ws.next().await.unwrap().unwrap() {
+                Message::Binary(data) => return serde_json::from_slice(&data).unwrap(),
+                Message::Text(data) => return serde_json::from_str(&data).unwrap(),
+                _ => continue,
+            }
+        }
+    }
+
+    /// Encodes a frame of the server
+    fn frame(kind: &str, id: &serde_json::Value, counter: u32, payload: &str) -> Message {
+        epoch_frame(kind, id, counter, None, None, payload)
+    }
+
+    /// Encodes a frame of the server within an epoch, optionally with a
+    /// cursor
+    fn epoch_frame(
+        kind: &str,
+        id: &serde_json::Value,
+        counter: u32,
+        epoch: Option<u64>,
+        cursor: Option<&str>,
+        payload: &str,
+    ) -> Message {
+        let header = serde_json::json!({
+            "kind": kind,
+            "id": id,
+            "counter": counter,
+            "epoch": epoch,
+            "cursor": cursor,
+        });
+        Message::Binary(format!("{header}\n{payload}").into_bytes())
+    }
+
+    /// Answers a status request with the given height of `ETH`
+    async fn send_status(ws: &mut ServerStream, request: &serde_json::Value, height: u64) {
+        let status = Status {
+            chain: ChainId::ETH,
+            latest_block_height: height,
+            ..Default::default()
+        };
+        let status = serde_json::to_string(&status).unwrap();
+        let id = &request["id"];
+        ws.send(frame("Start", id, 0, "")).await.unwrap();
+        ws.send(frame("Continue", id, 1, &status)).await.unwrap();
+        ws.send(frame("End", id, 2, "")).await.unwrap();
+    }
+
+    async fn with_timeout<F: std::future::Future>(future: F) -> F::Output {
+        tokio::time::timeout(Duration::from_secs(10), future)
+            .await
+            .expect("test timed out")
+    }
+
+    #[tokio::test]
+    async fn reconnect_without_cursor_resumes_at_latest_known_height() {
+        with_timeout(async {
+            let (endpoint, mut connections) = mock_server().await;
+            let provider = connect(endpoint).await;
+            let mut ws = connections.recv().await.unwrap();
+
+            let request = GetBlocksRequest {
+                from_block: Bound::Latest,
+                to_block: Bound::Subscribe,
+                ..Default::default()
+            };
+            let _stream = provider
+                .get_blocks_by_format(request, Format::JsonStream, false)
+                .await
+                .unwrap();
+
+            // the subscription is followed by a status request for its start
+            let subscription = next_request(&mut ws).await;
+            assert_eq!(subscription["operation"], "getBlocks");
+            assert_eq!(subscription["from_block"], "latest");
+            let status = next_request(&mut ws).await;
+            assert_eq!(status["operation"], "getStatus");
+            send_status(&mut ws, &status, 100).await;
+
+            // disconnect before the subscription received any cursor
+            drop(ws);
+
+            let mut ws = connections.recv().await.unwrap();
+            let resubscription = next_request(&mut ws).await;
+            assert_eq!(resubscription["id"], subscription["id"]);
+            assert_eq!(resubscription["from_block"], 100);
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn status_is_not_polled_without_relative_subscriptions() {
+        with_timeout(async {
+            let (endpoint, mut connections) = mock_server().await;
+            let provider = connect(endpoint).await;
+            let mut ws = connections.recv().await.unwrap();
+
+            let request = GetBlocksRequest {
+                from_block: Bound::Exact(10),
+                to_block: Bound::Exact(20),
+                ..Default::default()
+            };
+            let _stream = provider
+                .get_blocks_by_format(request, Format::JsonStream, false)
+                .await
+                .unwrap();
+
+            assert_eq!(next_request(&mut ws).await["operation"], "getBlocks");
+            let next = tokio::time::timeout(Duration::from_millis(200), next_request(&mut ws));
+            assert!(next.await.is_err(), "unexpected request");
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn connect_timeout_covers_stalled_handshake() {
+        // accepts connections but never answers the handshake
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = listener.local_addr().unwrap().to_string();
+        tokio::spawn(async move {
+            let mut held = Vec::new();
+            while let Ok((stream, _)) = listener.accept().await {
+                held.push(stream);
+            }
+        });
+
+        let config = ProviderConfig {
+            ws_connect_timeout: Some(Duration::from_millis(200)),
+            ..ProviderConfig::new(endpoint, false, None, None)
+        };
+        let start = tokio::time::Instant::now();
+        let result = with_timeout(WsProvider::try_new_with_config(config)).await;
+
+        assert!(
+            matches!(&result, Err(Error::IO(err)) if err.kind() == std::io::ErrorKind::TimedOut),
+            "{result:?}"
+        );
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+
+    #[cfg(feature = "cancellation")]
+    #[tokio::test]
+    async fn cancelled_stream_is_unsubscribed() {
+        with_timeout(async {
+            let (endpoint, mut connections) = mock_server().await;
+            let provider = connect(endpoint).await;
+            let mut ws = connections.recv().await.unwrap();
+
+            let request = GetBlocksRequest {
+                from_block: Bound::Exact(10),
+                to_block: Bound::Subscribe,
+                ..Default::default()
+            };
+            let stream = provider
+                .get_blocks_by_format(request, Format::JsonStream, false)
+                .await
+                .unwrap();
+            let token = tokio_util::sync::CancellationToken::new();
+            let mut stream =
+                crate::Client::new(provider.clone()).with_cancellation(stream, token.clone());
+
+            let request = next_request(&mut ws).await;
+            let id = &request["id"];
+            ws.send(frame("Start", id, 0, "")).await.unwrap();
+            ws.send(frame("Continue", id, 1, "{\"number\":10}\n"))
+                .await
+                .unwrap();
+            assert_eq!(stream.next().await.unwrap().unwrap(), "{\"number\":10}\n");
+
+            token.cancel();
+            assert!(stream.next().await.is_none());
+
+            let unsubscribe = next_request(&mut ws).await;
+            assert_eq!(unsubscribe["operation"], "unsubscribe");
+            assert_eq!(&unsubscribe["id"], id);
+
+            // frames still in flight are dropped without reaching anyone
+            ws.send(frame("Continue", id, 2, "{\"number\":11}\n"))
+                .await
+                .unwrap();
+            assert!(provider.ready());
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn request_with_id_sends_the_id_and_rejects_running_ids() {
+        with_timeout(async {
+            let (endpoint, mut connections) = mock_server().await;
+            let provider = connect(endpoint).await;
+            let mut ws = connections.recv().await.unwrap();
+            let id = Uuid::from_u128(0x0123_4567_89ab_cdef);
+            let operation = || Operation::GetBlocks {
+                params: GetBlocksRequest {
+                    from_block: Bound::Exact(10),
+                    to_block: Bound::Subscribe,
+                    ..Default::default()
+                },
+            };
+
+            let _running = provider
+                .request_with_id(id, operation(), Format::JsonStream, false)
+                .await
+                .unwrap();
+            let request = next_request(&mut ws).await;
+            assert_eq!(request["id"], id.to_string());
+
+            let duplicate = provider
+                .request_with_id(id, operation(), Format::JsonStream, false)
+                .await
+                .unwrap();
+            let items: Vec<_> = duplicate.collect().await;
+            assert!(matches!(items.as_slice(), [Err(Error::InvalidRequest(_))]));
+            let next = tokio::time::timeout(Duration::from_millis(200), next_request(&mut ws));
+            assert!(next.await.is_err(), "unexpected request");
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn batched_operations_are_sent_in_one_frame_after_the_window() {
+        with_timeout(async {
+            let (endpoint, mut connections) = mock_server().await;
+            let config = ProviderConfig {
+                ws_batch_window: Some(Duration::from_millis(300)),
+                ..ProviderConfig::new(endpoint, false, None, None)
+            };
+            let provider = WsProvider::try_new_with_config(config).await.unwrap();
+            let mut ws = connections.recv().await.unwrap();
+
+            let mut streams = Vec::new();
+            for from_block in [10, 20, 30] {
+                let request = GetBlocksRequest {
+                    from_block: Bound::Exact(from_block),
+                    to_block: Bound::Subscribe,
+                    ..Default::default()
+                };
+                let stream = provider
+                    .get_blocks_by_format(request, Format::JsonStream, false)
+                    .await
+                    .unwrap();
+                streams.push(stream);
+            }
+
+            let early = tokio::time::timeout(Duration::from_millis(100), next_request(&mut ws));
+            assert!(early.await.is_err(), "sent before the batch window closed");
+            // all three operations arrive in one frame
+            let batch = next_request(&mut ws).await;
+            assert_eq!(batch["operation"], "batch");
+            let requests = batch["requests"].as_array().unwrap();
+            assert_eq!(requests.len(), 3);
+            for (request, from_block) in requests.iter().zip([10, 20, 30]) {
+                assert_eq!(request["operation"], "getBlocks");
+                assert_eq!(request["from_block"], from_block);
+            }
+            let later = tokio::time::timeout(Duration::from_millis(100), next_request(&mut ws));
+            assert!(later.await.is_err(), "sent more than one frame");
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn worker_runs_on_the_custom_spawner() {
+        use std::sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        };
+
+        use crate::core::provider::Spawner;
+
+        with_timeout(async {
+            let (endpoint, mut connections) = mock_server().await;
+            let spawned = Arc::new(AtomicUsize::new(0));
+            let config = ProviderConfig {
+                spawner: Spawner::new({
+                    let spawned = spawned.clone();
+                    move |future| {
+                        spawned.fetch_add(1, Ordering::SeqCst);
+                        tokio::spawn(future);
+                    }
+                }),
+                ..ProviderConfig::new(endpoint, false, None, None)
+            };
+            let provider = WsProvider::try_new_with_config(config).await.unwrap();
+            let mut ws = connections.recv().await.unwrap();
+            assert_eq!(spawned.load(Ordering::SeqCst), 1);
+
+            // the spawned worker serves the requests
+            let _stream = provider
+                .get_blocks_by_format(Default::default(), Format::JsonStream, false)
+                .await
+                .unwrap();
+            assert_eq!(next_request(&mut ws).await["operation"], "getBlocks");
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn epoch_change_continues_the_stream_and_drops_the_cursor() {
+        with_timeout(async {
+            let (endpoint, mut connections) = mock_server().await;
+            let provider = connect(endpoint).await;
+            let mut ws = connections.recv().await.unwrap();
+
+            let request = GetLogsRequest {
+                from_block: Bound::Exact(10),
+                to_block: Bound::Subscribe,
+                ..Default::default()
+            };
+            let mut events = provider.events();
+            let mut stream = provider.replay_logs_records(request, None).await.unwrap();
+            let subscription = next_request(&mut ws).await;
+            let id = &subscription["id"];
+
+            let log = "{\"block_number\":10}\n";
+            for message in [
+                epoch_frame("Start", id, 0, Some(1), None, ""),
+                epoch_frame("Continue", id, 1, Some(1), Some("c1"), log),
+                // the server restarted the stream in a new epoch
+                epoch_frame("Continue", id, 0, Some(2), None, log),
+            ] {
+                ws.send(message).await.unwrap();
+            }
+
+            let first = stream.next().await.unwrap().unwrap();
+            assert_eq!(
+                (first.cursor.as_deref(), first.epoch),
+                (Some("c1"), Some(1))
+            );
+            let second = stream.next().await.unwrap().unwrap();
+            assert_eq!((second.cursor, second.epoch), (None, Some(2)));
+            assert_eq!(
+                events.recv().await.unwrap(),
+                StreamEvent::EpochChanged {
+                    id: id.as_str().unwrap().parse().unwrap(),
+                    from: Some(1),
+                    to: Some(2),
+                }
+            );
+
+            // the cursor of the previous epoch is not used to resume
+            drop(ws);
+            let mut ws = connections.recv().await.unwrap();
+            let resubscription = next_request(&mut ws).await;
+            assert_eq!(&resubscription["id"], id);
+            assert_eq!(resubscription["cursor"], "");
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn counter_gap_is_reported_before_the_records() {
+        with_timeout(async {
+            let (endpoint, mut connections) = mock_server().await;
+            let provider = connect(endpoint).await;
+            let mut ws = connections.recv().await.unwrap();
+
+            let request = GetBlocksRequest {
+                from_block: Bound::Exact(10),
+                to_block: Bound::Subscribe,
+                ..Default::default()
+            };
+            let stream = provider
+                .get_blocks_by_format(request, Format::JsonStream, false)
+                .await
+                .unwrap();
+            let subscription = next_request(&mut ws).await;
+            let id = &subscription["id"];
+
+            for message in [
+                frame("Start", id, 0, ""),
+                frame("Continue", id, 1, "{\"number\":10}\n"),
+                // the message with counter 2 was lost
+                frame("Continue", id, 3, "{\"number\":12}\n"),
+                frame("End", id, 4, ""),
+            ] {
+                ws.send(message).await.unwrap();
+            }
+
+            let items: Vec<_> = stream.collect().await;
+            assert!(matches!(
+                items.as_slice(),
+                [
+                    Ok(_),
+                    Err(Error::MessageGap {
+                        expected: 2,
+                        received: 3
+                    }),
+                    Ok(_)
+                ]
+            ));
+            assert_eq!(items[2].as_ref().unwrap(), "{\"number\":12}\n");
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn payload_with_several_records_yields_every_record() {
+        use crate::DecodeStream;
+
+        with_timeout(async {
+            let (endpoint, mut connections) = mock_server().await;
+            let provider = connect(endpoint).await;
+            let mut ws = connections.recv().await.unwrap();
+
+            let request = GetBlocksRequest {
+                from_block: Bound::Exact(10),
+                to_block: Bound::Exact(13),
+                ..Default::default()
+            };
+            let stream = provider
+                .get_blocks_by_format(request, Format::JsonStream, false)
+                .await
+                .unwrap();
+            let subscription = next_request(&mut ws).await;
+            let id = &subscription["id"];
+
+            let records = "{\"number\":10}\n{\"number\":11}\n{\"number\":12}\n";
+            for message in [
+                frame("Start", id, 0, ""),
+                frame("Continue", id, 1, records),
+                frame("End", id, 2, ""),
+            ] {
+                ws.send(message).await.unwrap();
+            }
+
+            let blocks: Vec<serde_json::Value> =
+                stream.decode_json().unwrap().try_collect().await.unwrap();
+            assert_eq!(
+                blocks,
+                [10, 11, 12].map(|number| serde_json::json!({ "number": number }))
+            );
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn cloned_clients_share_the_connection() {
+        with_timeout(async {
+            let (endpoint, mut connections) = mock_server().await;
+            let client = crate::Client::new(connect(endpoint).await);
+            let clone = client.clone();
+            let mut ws = connections.recv().await.unwrap();
+
+            let request = GetBlocksRequest {
+                from_block: Bound::Exact(10),
+                to_block: Bound::Exact(20),
+                ..Default::default()
+            };
+            let _first = client
+                .get_blocks_by_format(request.clone(), Format::JsonStream, false)
+                .await
+                .unwrap();
+            let _second = clone
+                .get_blocks_by_format(request, Format::JsonStream, false)
+                .await
+                .unwrap();
+
+            // both requests go over the one connection with their own ids
+            let first = next_request(&mut ws).await;
+            let second = next_request(&mut ws).await;
+            assert_eq!(first["operation"], "getBlocks");
+            assert_eq!(second["operation"], "getBlocks");
+            assert_ne!(first["id"], second["id"]);
+            assert!(connections.try_recv().is_err());
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn endpoint_is_the_configured_endpoint() {
+        with_timeout(async {
+            let (endpoint, _connections) = mock_server().await;
+            let client = crate::ClientBuilder::default()
+                .endpoint(format!("ws://{endpoint}"))
+                .build::<WsProvider>()
+                .await
+                .unwrap();
+
+            assert_eq!(client.provider().endpoint(), endpoint);
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn oversized_message_drops_the_connection() {
+        with_timeout(async {
+            let (endpoint, mut connections) = mock_server().await;
+            let config = ProviderConfig {
+                ws_max_message_size: Some(256),
+                ..ProviderConfig::new(endpoint, false, None, None)
+            };
+            let provider = WsProvider::try_new_with_config(config).await.unwrap();
+            let mut ws = connections.recv().await.unwrap();
+
+            let request = GetBlocksRequest {
+                from_block: Bound::Exact(10),
+                to_block: Bound::Subscribe,
+                ..Default::default()
+            };
+            let _stream = provider
+                .get_blocks_by_format(request, Format::JsonStream, false)
+                .await
+                .unwrap();
+            let subscription = next_request(&mut ws).await;
+            let id = &subscription["id"];
+
+            let records = "{\"number\":10}\n".repeat(32);
+            ws.send(frame("Start", id, 0, "")).await.unwrap();
+            ws.send(frame("Continue", id, 1, &records)).await.unwrap();
+
+            // the read fails with a capacity error, so the client reconnects
+            // and resubscribes
+            let mut ws = connections.recv().await.unwrap();
+            let resubscription = next_request(&mut ws).await;
+            assert_eq!(&resubscription["id"], id);
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn silent_connection_is_reconnected_after_the_idle_timeout() {
+        with_timeout(async {
+            let (endpoint, mut connections) = mock_server().await;
+            let config = ProviderConfig {
+                ws_idle_timeout: Some(Duration::from_millis(300)),
+                ..ProviderConfig::new(endpoint, false, None, None)
+            };
+            let provider = WsProvider::try_new_with_config(config).await.unwrap();
+            // the server neither reads nor writes, so not even pings are
+            // answered
+            let _silent = connections.recv().await.unwrap();
+
+            let request = GetBlocksRequest {
+                from_block: Bound::Exact(10),
+                to_block: Bound::Subscribe,
+                ..Default::default()
+            };
+            let _stream = provider
+                .get_blocks_by_format(request, Format::JsonStream, false)
+                .await
+                .unwrap();
+
+            let start = tokio::time::Instant::now();
+            let mut ws = connections.recv().await.unwrap();
+            assert!(start.elapsed() < Duration::from_secs(2));
+            assert_eq!(next_request(&mut ws).await["operation"], "getBlocks");
+        })
+        .await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn unanswered_pings_force_a_reconnect() {
+        let (endpoint, mut connections) = mock_server().await;
+        let config = ProviderConfig {
+            ws_max_missed_pongs: 0,
+            ..ProviderConfig::new(endpoint, false, None, None)
+        };
+        let provider = WsProvider::try_new_with_config(config).await.unwrap();
+        // the server never reads, so the pings are never answered
+        let _silent = connections.recv().await.unwrap();
+
+        let request = GetBlocksRequest {
+            from_block: Bound::Exact(10),
+            to_block: Bound::Subscribe,
+            ..Default::default()
+        };
+        let _stream = provider
+            .get_blocks_by_format(request, Format::JsonStream, false)
+            .await
+            .unwrap();
+
+        // the paused clock skips ahead to the next ping, which finds the
+        // first one unanswered
+        let start = tokio::time::Instant::now();
+        let mut ws = connections.recv().await.unwrap();
+        assert!(start.elapsed() >= Duration::from_secs(30));
+        assert_eq!(next_request(&mut ws).await["operation"], "getBlocks");
+    }
+
+    #[tokio::test]
+    async fn erc721_transfers_are_routed_to_their_operation() {
+        with_timeout(async {
+            let (endpoint, mut connections) = mock_server().await;
+            let provider = connect(endpoint).await;
+            let mut ws = connections.recv().await.unwrap();
+
+            let _stream = provider
+                .get_erc721_transfers_by_format(Default::default(), Format::JsonStream, false)
+                .await
+                .unwrap();
+
+            let request = next_request(&mut ws).await;
+            assert_eq!(request["operation"], "getErc721Transfers");
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn withdrawals_are_routed_to_their_operation() {
+        with_timeout(async {
+            let (endpoint, mut connections) = mock_server().await;
+            let provider = connect(endpoint).await;
+            let mut ws = connections.recv().await.unwrap();
+
+            let request = GetWithdrawalsRequest {
+                validator_index__in: [7].into(),
+                ..Default::default()
+            };
+            let _stream = provider
+                .get_withdrawals_by_format(request, Format::JsonStream, false)
+                .await
+                .unwrap();
+
+            let request = next_request(&mut ws).await;
+            assert_eq!(request["operation"], "getWithdrawals");
+            assert_eq!(request["validator_index__in"], "7");
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn balances_are_routed_to_their_operation() {
+        with_timeout(async {
+            let (endpoint, mut connections) = mock_server().await;
+            let provider = connect(endpoint).await;
+            let mut ws = connections.recv().await.unwrap();
+
+            let request = GetBalancesRequest {
+                at_block: Bound::Exact(10),
+                ..Default::default()
+            };
+            let _stream = provider
+                .get_balances_by_format(request, Format::JsonStream, false)
+                .await
+                .unwrap();
+
+            let request = next_request(&mut ws).await;
+            assert_eq!(request["operation"], "getBalances");
+            assert_eq!(request["at_block"], 10);
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn raw_operation_is_sent_with_its_tag() {
+        with_timeout(async {
+            let (endpoint, mut connections) = mock_server().await;
+            let provider = connect(endpoint).await;
+            let mut ws = connections.recv().await.unwrap();
+
+            let params = serde_json::json!({ "chains": "ETH", "pool__in": "0x01" });
+            let _stream = provider
+                .send_raw("getUnreleased", params, Format::JsonStream, false)
+                .await
+                .unwrap();
+
+            let request = next_request(&mut ws).await;
+            assert_eq!(request["operation"], "getUnreleased");
+            assert_eq!(request["chains"], "ETH");
+            assert_eq!(request["pool__in"], "0x01");
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn user_agent_is_sent_with_the_handshake() {
+        with_timeout(async {
+            let (endpoint, headers) = handshake_headers().await;
+            let config = ProviderConfig {
+                user_agent: "indexer/1.0".into(),
+                ..ProviderConfig::new(endpoint, false, None, None)
+            };
+            let _provider = WsProvider::try_new_with_config(config).await.unwrap();
+
+            let headers = headers.await.unwrap();
+            assert_eq!(headers[header::USER_AGENT], "indexer/1.0");
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn custom_header_is_sent_with_the_handshake() {
+        with_timeout(async {
+            let (endpoint, headers) = handshake_headers().await;
+            let config = ProviderConfig {
+                headers: vec![("X-Api-Key".into(), "secret".into())],
+                ..ProviderConfig::new(endpoint, false, None, None)
+            };
+            let _provider = WsProvider::try_new_with_config(config).await.unwrap();
+
+            let headers = headers.await.unwrap();
+            assert_eq!(headers["x-api-key"], "secret");
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn invalid_header_name_fails_the_connection() {
+        let config = ProviderConfig {
+            headers: vec![("X Api Key".into(), "secret".into())],
+            ..ProviderConfig::new("localhost:8080".into(), false, None, None)
+        };
+
+        let result = WsProvider::try_new_with_config(config).await;
+
+        assert!(matches!(result, Err(Error::Custom(_))));
+    }
+
+    #[tokio::test]
+    async fn control_character_in_password_is_encoded() {
+        with_timeout(async {
+            let (endpoint, headers) = handshake_headers().await;
+            let config = ProviderConfig::new(
+                endpoint,
+                false,
+                Some("user".into()),
+                Some("pass\nword".into()),
+            );
+            let _provider = WsProvider::try_new_with_config(config).await.unwrap();
+
+            let headers = headers.await.unwrap();
+            let expected = format!("Basic {}", BASE64.encode("user:pass\nword"));
+            assert_eq!(headers[header::AUTHORIZATION], expected.as_str());
+        })
+        .await;
+    }
+
+    /// Subscribes to blocks, returning the stream and the id of the
+    /// subscription
+    async fn subscribe(
+        provider: &WsProvider,
+        ws: &mut ServerStream,
+    ) -> (ResponseStream<Bytes>, serde_json::Value) {
+        let request = GetBlocksRequest {
+            from_block: Bound::Exact(10),
+            to_block: Bound::Subscribe,
+            ..Default::default()
+        };
+        let stream = provider
+            .get_blocks_by_format(request, Format::JsonStream, false)
+            .await
+            .unwrap();
+        let subscription = next_request(ws).await;
+        (stream, subscription["id"].clone())
+    }
+
+    #[tokio::test]
+    async fn normal_close_reconnects_and_resubscribes() {
+        with_timeout(async {
+            let (endpoint, mut connections) = mock_server().await;
+            let provider = connect(endpoint).await;
+            let mut ws = connections.recv().await.unwrap();
+            let (_stream, id) = subscribe(&provider, &mut ws).await;
+
+            ws.close(Some(CloseFrame {
+                code: CloseCode::Normal,
+                reason: "maintenance".into(),
+            }))
+            .await
+            .unwrap();
+
+            let mut ws = connections.recv().await.unwrap();
+            assert_eq!(next_request(&mut ws).await["id"], id);
+            assert!(provider.ready());
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn policy_close_is_fatal() {
+        with_timeout(async {
+            let (endpoint, mut connections) = mock_server().await;
+            let provider = connect(endpoint).await;
+            let mut ws = connections.recv().await.unwrap();
+            let (stream, _) = subscribe(&provider, &mut ws).await;
+
+            ws.close(Some(CloseFrame {
+                code: CloseCode::Policy,
+                reason: "bad credentials".into(),
+            }))
+            .await
+            .unwrap();
+
+            let items: Vec<_> = stream.collect().await;
+            assert!(
+                matches!(items.as_slice(), [Err(Error::ConnectionRejected(reason))] if reason == "bad credentials"),
+                "{items:?}"
+            );
+            let reconnect = tokio::time::timeout(Duration::from_millis(200), connections.recv());
+            assert!(reconnect.await.is_err(), "reconnected");
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn failing_subscription_leaves_its_siblings_running() {
+        with_timeout(async {
+            let (endpoint, mut connections) = mock_server().await;
+            let provider = connect(endpoint).await;
+            let mut ws = connections.recv().await.unwrap();
+            let (mut good, good_id) = subscribe(&provider, &mut ws).await;
+            let (failing, failing_id) = subscribe(&provider, &mut ws).await;
+
+            let error = "{\"status\":400,\"error\":\"unknown pool\"}";
+            for message in [
+                frame("Start", &good_id, 0, ""),
+                frame("Start", &failing_id, 0, ""),
+                frame("Continue", &good_id, 1, "{\"number\":10}\n"),
+                frame("Error", &failing_id, 1, error),
+                // a frame the worker can not parse
+                Message::Text("garbage".into()),
+                frame("Continue", &good_id, 2, "{\"number\":11}\n"),
+            ] {
+                ws.send(message).await.unwrap();
+            }
+
+            let failed: Vec<_> = failing.collect().await;
+            assert!(
+                matches!(
+                    failed.as_slice(),
+                    [Err(Error::ErrorResponse(ResponseError { status: 400, .. }))]
+                ),
+                "{failed:?}"
+            );
+            assert_eq!(good.next().await.unwrap().unwrap(), "{\"number\":10}\n");
+            assert_eq!(good.next().await.unwrap().unwrap(), "{\"number\":11}\n");
+            assert!(provider.ready());
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn status_error_is_passed_through_unchanged() {
+        with_timeout(async {
+            let (endpoint, mut connections) = mock_server().await;
+            let client = crate::Client::new(connect(endpoint).await);
+            let mut ws = connections.recv().await.unwrap();
+
+            let status = client.get_status().await.unwrap();
+            let request = next_request(&mut ws).await;
+            assert_eq!(request["operation"], "getStatus");
+            let id = &request["id"];
+            let error = "{\"status\":503,\"error\":\"indexer lagging\"}";
+            for message in [
+                frame("Start", id, 0, ""),
+                frame("ContinueWithError", id, 1, error),
+                frame("End", id, 2, ""),
+            ] {
+                ws.send(message).await.unwrap();
+            }
+
+            let items: Vec<_> = status.collect().await;
+            let [Err(Error::ErrorResponse(error))] = items.as_slice() else {
+                panic!("{items:?}");
+            };
+            assert_eq!(
+                (error.status, error.error.as_str()),
+                (503, "indexer lagging")
+            );
+        })
+        .await;
+    }
+
+    #[test]
+    fn records_share_the_buffer_of_the_frame() {
+        let id = serde_json::json!(Uuid::nil());
+        let Message::Binary(data) = frame("Continue", &id, 1, "{\"number\":10}\n{\"number\":11}\n")
+        else {
+            unreachable!()
+        };
+        let data = Bytes::from(data);
+        let buffer = data.as_ptr_range();
+
+        let (_, payload) = Header::try_from_data(data.clone()).unwrap();
+        let records = split_records(payload);
+
+        assert_eq!(records, ["{\"number\":10}\n", "{\"number\":11}\n"]);
+        for record in &records {
+            assert!(buffer.contains(&record.as_ptr()), "record was copied");
+        }
+    }
+
+    #[tokio::test]
+    async fn manager_adds_and_removes_subscriptions_at_runtime() {
+        use crate::providers::subscriptions::{SubscribeCommand, SubscriptionManager};
+
+        with_timeout(async {
+            let (endpoint, mut connections) = mock_server().await;
+            let provider = connect(endpoint).await;
+            let mut ws = connections.recv().await.unwrap();
+            let mut manager = SubscriptionManager::new(provider);
+
+            let id = Uuid::new_v4();
+            let operation = Operation::GetBlocks {
+                params: GetBlocksRequest {
+                    from_block: Bound::Exact(10),
+                    to_block: Bound::Subscribe,
+                    ..Default::default()
+                },
+            };
+            manager
+                .send(SubscribeCommand::Subscribe {
+                    id,
+                    operation: Box::new(operation),
+                    format: Format::JsonStream,
+                    deltas: false,
+                })
+                .await
+                .unwrap();
+
+            let request = next_request(&mut ws).await;
+            assert_eq!(request["id"], serde_json::json!(id));
+            let wire_id = &request["id"];
+            ws.send(frame("Start", wire_id, 0, "")).await.unwrap();
+            ws.send(frame("Continue", wire_id, 1, "{\"number\":10}\n"))
+                .await
+                .unwrap();
+            let (event_id, data) = manager.next().await.unwrap();
+            assert_eq!(event_id, id);
+            assert_eq!(data.unwrap(), "{\"number\":10}\n");
+
+            manager
+                .send(SubscribeCommand::Unsubscribe(id))
+                .await
+                .unwrap();
+            let (event_id, closed) = manager.next().await.unwrap();
+            assert_eq!(event_id, id);
+            assert!(matches!(
+                closed,
+                Err(Error::StreamClosed(CloseReason::Unsubscribed))
+            ));
+
+            let unsubscribe = next_request(&mut ws).await;
+            assert_eq!(unsubscribe["operation"], "unsubscribe");
+            assert_eq!(unsubscribe["id"], serde_json::json!(id));
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn persisted_cursor_resumes_a_new_client() {
+        with_timeout(async {
+            let (endpoint, mut connections) = mock_server().await;
+            let request = GetLogsRequest {
+                from_block: Bound::Exact(10),
+                to_block: Bound::Subscribe,
+                ..Default::default()
+            };
+
+            let persisted = {
+                let client = crate::Client::new(connect(endpoint).await);
+                let mut ws = connections.recv().await.unwrap();
+                let mut logs = client
+                    .replay_logs::<serde_json::Value>(request.clone(), None)
+                    .await
+                    .unwrap();
+                let subscription = next_request(&mut ws).await;
+                assert_eq!(subscription["cursor"], "");
+                let id = &subscription["id"];
+                ws.send(epoch_frame("Start", id, 0, Some(1), None, ""))
+                    .await
+                    .unwrap();
+                ws.send(epoch_frame(
+                    "Continue",
+                    id,
+                    1,
+                    Some(1),
+                    Some("c1"),
+                    "{\"block_number\":10}\n",
+                ))
+                .await
+                .unwrap();
+                let log = logs.next().await.unwrap().unwrap();
+                assert_eq!(log.item["block_number"], 10);
+                log.cursor.unwrap()
+            };
+
+            // a restarted process resumes right after the persisted cursor
+            let (endpoint, mut connections) = mock_server().await;
+            let client = crate::Client::new(connect(endpoint).await);
+            let mut ws = connections.recv().await.unwrap();
+            let _logs = client
+                .replay_logs::<serde_json::Value>(request, Some(persisted))
+                .await
+                .unwrap();
+            let subscription = next_request(&mut ws).await;
+            assert_eq!(subscription["operation"], "getLogs");
+            assert_eq!(subscription["cursor"], "c1");
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn completed_subscription_ends_without_error() {
+        with_timeout(async {
+            let (endpoint, mut connections) = mock_server().await;
+            let provider = connect(endpoint).await;
+            let mut ws = connections.recv().await.unwrap();
+            let (stream, id) = subscribe(&provider, &mut ws).await;
+
+            for message in [
+                frame("Start", &id, 0, ""),
+                frame("Continue", &id, 1, "{\"number\":10}\n"),
+                frame("End", &id, 2, ""),
+            ] {
+                ws.send(message).await.unwrap();
+            }
+
+            let items: Vec<_> = stream.collect().await;
+            assert!(
+                matches!(items.as_slice(), [Ok(record)] if record == "{\"number\":10}\n"),
+                "{items:?}"
+            );
+        })
+        .await;
+    }
+
+    // the paused clock skips the delays between the failing reconnects
+    #[tokio::test(start_paused = true)]
+    async fn server_close_without_reconnect_reports_its_code() {
+        let (endpoint, connection) = single_connection().await;
+        let provider = connect(endpoint).await;
+        let mut ws = connection.await.unwrap();
+        let (stream, _) = subscribe(&provider, &mut ws).await;
+
+        ws.close(Some(CloseFrame {
+            code: CloseCode::Away,
+            reason: "maintenance".into(),
+        }))
+        .await
+        .unwrap();
+
+        let items: Vec<_> = stream.collect().await;
+        assert!(
+            matches!(
+                items.as_slice(),
+                [Err(Error::StreamClosed(CloseReason::ServerClosed { code: 1001, reason }))]
+                    if reason == "maintenance"
+            ),
+            "{items:?}"
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn lost_connection_without_reconnect_reports_it() {
+        let (endpoint, connection) = single_connection().await;
+        let provider = connect(endpoint).await;
+        let mut ws = connection.await.unwrap();
+        let (stream, _) = subscribe(&provider, &mut ws).await;
+
+        drop(ws);
+
+        let items: Vec<_> = stream.collect().await;
+        assert!(
+            matches!(
+                items.as_slice(),
+                [Err(Error::StreamClosed(CloseReason::ReconnectFailed))]
+            ),
+            "{items:?}"
+        );
+    }
+}
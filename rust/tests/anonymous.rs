@@ -0,0 +1,47 @@
+//! An anonymous client sends no `Authorization` header, even with
+//! credentials in the environment
+#![cfg(all(feature = "http", not(target_arch = "wasm32")))]
+
+mod common;
+
+use futures::TryStreamExt;
+use superchain_client::{provider::ChainProvider, ClientBuilder, Format, HttpProvider};
+
+fn authorization(request: &str) -> String {
+    let authorized = common::header(request, "authorization").is_some();
+    format!("{{\"authorized\":{authorized}}}\n")
+}
+
+async fn authorized(builder: ClientBuilder) -> bool {
+    let client = builder.build::<HttpProvider>().await.unwrap();
+    let body: Vec<_> = client
+        .get_blocks_by_format(Default::default(), Format::JsonStream, false)
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&body.concat()).unwrap();
+    body["authorized"].as_bool().unwrap()
+}
+
+#[tokio::test]
+async fn anonymous_client_sends_no_authorization_header() {
+    // the defaults are read once, before the builder is first used
+    std::env::set_var("SUPER_USERNAME", "user");
+    std::env::set_var("SUPER_PASSWORD", "pass");
+    let endpoint = common::mock_server(authorization).await;
+
+    assert!(authorized(ClientBuilder::default().endpoint(&endpoint)).await);
+    assert!(!authorized(ClientBuilder::default().endpoint(&endpoint).anonymous()).await);
+    assert!(
+        !authorized(
+            ClientBuilder::default()
+                .endpoint(&endpoint)
+                .credential("other", "secret")
+                .header("Authorization", "Bearer token")
+                .anonymous()
+        )
+        .await
+    );
+}
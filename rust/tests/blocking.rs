@@ -0,0 +1,59 @@
+//! Pulls a bounded range through the blocking facade
+#![cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+
+mod common;
+
+use superchain_client::{query::Bound, requests::blocks::GetBlocksRequest, ClientBuilder};
+
+/// Serves the blocks of the requested range, echoing the target in each row
+fn blocks(request: &str) -> String {
+    let from: u64 = common::query_param(request, "from_block")
+        .unwrap()
+        .parse()
+        .unwrap();
+    let to: u64 = common::query_param(request, "to_block")
+        .unwrap()
+        .parse()
+        .unwrap();
+    (from..to)
+        .map(|number| {
+            format!(
+                "{{\"number\":{number},\"target\":\"{}\"}}\n",
+                common::target(request)
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn blocking_client_pulls_a_bounded_range() {
+    // the mock server runs on a runtime of its own, the client brings its own
+    let server = tokio::runtime::Runtime::new().unwrap();
+    let endpoint = server.block_on(common::mock_server(blocks));
+    let client = ClientBuilder::default()
+        .endpoint(endpoint)
+        .build_blocking()
+        .unwrap();
+    let request = GetBlocksRequest {
+        from_block: Bound::Exact(10),
+        to_block: Bound::Exact(13),
+        ..Default::default()
+    };
+
+    let rows: Vec<serde_json::Value> = client.get_blocks(request.clone()).unwrap();
+    let numbers: Vec<_> = rows
+        .iter()
+        .map(|row| row["number"].as_u64().unwrap())
+        .collect();
+    assert_eq!(numbers, [10, 11, 12]);
+    assert!(rows[0]["target"]
+        .as_str()
+        .unwrap()
+        .starts_with("/v1/api/blocks?"));
+
+    let mut iter = client.iter_blocks::<serde_json::Value>(request).unwrap();
+    assert_eq!(iter.next().unwrap().unwrap()["number"], 10);
+    drop(client);
+    // the iterator keeps the runtime of the dropped client alive
+    assert_eq!(iter.count(), 2);
+}
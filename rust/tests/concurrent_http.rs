@@ -0,0 +1,57 @@
+//! A `Client<HttpProvider>` shared between tasks serves concurrent requests
+#![cfg(all(feature = "http", not(target_arch = "wasm32")))]
+
+mod common;
+
+use std::sync::Arc;
+
+use futures::TryStreamExt;
+use superchain_client::{
+    provider::ChainProvider, query::Bound, requests::logs::GetLogsRequest, ClientBuilder,
+    DecodeStream, Format, HttpProvider,
+};
+
+/// Answers every request with a single log at its `from_block`
+fn echo_from_block(request: &str) -> String {
+    let from_block = common::query_param(request, "from_block").unwrap();
+    format!("{{\"block_number\":{from_block}}}\n")
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn shared_client_serves_concurrent_get_logs() {
+    let endpoint = common::mock_server(echo_from_block).await;
+    let client = Arc::new(
+        ClientBuilder::default()
+            .endpoint(endpoint)
+            .build::<HttpProvider>()
+            .await
+            .unwrap(),
+    );
+
+    let tasks: Vec<_> = (0..16)
+        .map(|block| {
+            let client = Arc::clone(&client);
+            tokio::spawn(async move {
+                let request = GetLogsRequest {
+                    from_block: Bound::Exact(block),
+                    to_block: Bound::Exact(block + 1),
+                    ..Default::default()
+                };
+                let logs: Vec<serde_json::Value> = client
+                    .get_logs_by_format(request, Format::JsonStream, false)
+                    .await
+                    .decode_json()
+                    .unwrap()
+                    .try_collect()
+                    .await
+                    .unwrap();
+                (block, logs)
+            })
+        })
+        .collect();
+
+    for task in tasks {
+        let (block, logs) = task.await.unwrap();
+        assert_eq!(logs, [serde_json::json!({ "block_number": block })]);
+    }
+}
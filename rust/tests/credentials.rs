@@ -0,0 +1,39 @@
+//! A client without credentials in the environment sends no `Authorization`
+//! header
+#![cfg(all(feature = "http", not(target_arch = "wasm32")))]
+
+mod common;
+
+use futures::TryStreamExt;
+use superchain_client::{provider::ChainProvider, ClientBuilder, Format, HttpProvider};
+
+fn authorization(request: &str) -> String {
+    let authorized = request
+        .lines()
+        .any(|line| line.to_ascii_lowercase().starts_with("authorization:"));
+    format!("{{\"authorized\":{authorized}}}\n")
+}
+
+#[tokio::test]
+async fn unset_credentials_send_no_authorization_header() {
+    // the defaults are read once, before the builder is first used
+    std::env::remove_var("SUPER_USERNAME");
+    std::env::remove_var("SUPER_PASSWORD");
+
+    let endpoint = common::mock_server(authorization).await;
+    let client = ClientBuilder::default()
+        .endpoint(endpoint)
+        .build::<HttpProvider>()
+        .await
+        .unwrap();
+
+    let body: Vec<_> = client
+        .get_blocks_by_format(Default::default(), Format::JsonStream, false)
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+
+    assert_eq!(body.concat(), b"{\"authorized\":false}\n");
+}
@@ -0,0 +1,49 @@
+//! Requests fail over to the next endpoint when one is unavailable
+#![cfg(all(feature = "http", not(target_arch = "wasm32")))]
+
+mod common;
+
+use futures::TryStreamExt;
+use superchain_client::{
+    provider::ChainProvider, ClientBuilder, FailoverProvider, Format, HttpProvider,
+};
+
+fn ok(_: &str) -> String {
+    "{\"number\":10}\n".to_owned()
+}
+
+/// Returns an endpoint that refuses all connections
+fn refusing_endpoint() -> String {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    format!("http://{}", listener.local_addr().unwrap())
+}
+
+#[tokio::test]
+async fn failing_endpoint_fails_over_to_the_next() {
+    let failing = refusing_endpoint();
+    let working = common::mock_server(ok).await;
+    let client = ClientBuilder::default()
+        .endpoints([&failing, &working])
+        .build::<FailoverProvider<HttpProvider>>()
+        .await
+        .unwrap();
+    let provider = client.provider();
+    assert_eq!(
+        provider.current_endpoint(),
+        failing.trim_start_matches("http://")
+    );
+
+    let body: Vec<_> = client
+        .get_blocks_by_format(Default::default(), Format::JsonStream, false)
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+
+    assert_eq!(body.concat(), b"{\"number\":10}\n");
+    assert_eq!(
+        provider.current_endpoint(),
+        working.trim_start_matches("http://")
+    );
+}
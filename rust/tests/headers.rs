@@ -0,0 +1,76 @@
+//! The configured headers are sent with every HTTP request
+#![cfg(all(feature = "http", not(target_arch = "wasm32")))]
+
+mod common;
+
+use futures::TryStreamExt;
+use superchain_client::{ClientBuilder, DecodeStream, Error, Format, HttpProvider};
+
+/// Answers every request with its `User-Agent`
+fn echo_user_agent(request: &str) -> String {
+    let user_agent = common::header(request, "user-agent").unwrap_or_default();
+    format!("{{\"user_agent\":\"{user_agent}\"}}\n")
+}
+
+/// Answers every request with its `X-Api-Key`
+fn echo_api_key(request: &str) -> String {
+    let api_key = common::header(request, "x-api-key").unwrap_or_default();
+    format!("{{\"api_key\":\"{api_key}\"}}\n")
+}
+
+#[tokio::test]
+async fn user_agent_is_sent() {
+    let endpoint = common::mock_server(echo_user_agent).await;
+    let client = ClientBuilder::default()
+        .endpoint(endpoint)
+        .user_agent("indexer/1.0")
+        .build::<HttpProvider>()
+        .await
+        .unwrap();
+
+    let rows: Vec<serde_json::Value> = client
+        .provider()
+        .send_raw("status", &(), Format::JsonStream)
+        .await
+        .decode_json()
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+
+    assert_eq!(rows, [serde_json::json!({ "user_agent": "indexer/1.0" })]);
+}
+
+#[tokio::test]
+async fn custom_header_is_sent() {
+    let endpoint = common::mock_server(echo_api_key).await;
+    let client = ClientBuilder::default()
+        .endpoint(endpoint)
+        .header("X-Api-Key", "secret")
+        .build::<HttpProvider>()
+        .await
+        .unwrap();
+
+    let rows: Vec<serde_json::Value> = client
+        .provider()
+        .send_raw("status", &(), Format::JsonStream)
+        .await
+        .decode_json()
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+
+    assert_eq!(rows, [serde_json::json!({ "api_key": "secret" })]);
+}
+
+#[tokio::test]
+async fn invalid_header_fails_the_build() {
+    let result = ClientBuilder::default()
+        .endpoint("http://localhost:8080")
+        .header("X-Tenant", "line\nbreak")
+        .build::<HttpProvider>()
+        .await;
+
+    assert!(matches!(result, Err(Error::Custom(_))));
+}
@@ -0,0 +1,72 @@
+//! Requests are distributed across the endpoints of a load balanced client
+#![cfg(all(feature = "http", not(target_arch = "wasm32")))]
+
+mod common;
+
+use futures::TryStreamExt;
+use superchain_client::{
+    provider::ChainProvider, Client, ClientBuilder, Format, HttpProvider, LoadBalancedProvider,
+};
+
+fn first(_: &str) -> String {
+    "first\n".to_owned()
+}
+
+fn second(_: &str) -> String {
+    "second\n".to_owned()
+}
+
+/// Returns the response body of a blocks request
+async fn respond(client: &Client<LoadBalancedProvider<HttpProvider>>) -> String {
+    let body: Vec<_> = client
+        .get_blocks_by_format(Default::default(), Format::JsonStream, false)
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+    String::from_utf8(body.concat()).unwrap()
+}
+
+#[tokio::test]
+async fn requests_alternate_between_endpoints() {
+    let endpoints = [
+        common::mock_server(first).await,
+        common::mock_server(second).await,
+    ];
+    let client = ClientBuilder::default()
+        .load_balance(endpoints)
+        .build::<LoadBalancedProvider<HttpProvider>>()
+        .await
+        .unwrap();
+
+    let mut responses = Vec::new();
+    for _ in 0..4 {
+        responses.push(respond(&client).await);
+    }
+
+    responses.sort();
+    assert_eq!(responses, ["first\n", "first\n", "second\n", "second\n"]);
+}
+
+#[tokio::test]
+async fn failing_endpoint_is_skipped() {
+    // an endpoint that refuses all connections
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let refusing = format!("http://{}", listener.local_addr().unwrap());
+    drop(listener);
+    let working = common::mock_server(first).await;
+    let client = ClientBuilder::default()
+        .load_balance([&refusing, &working])
+        .build::<LoadBalancedProvider<HttpProvider>>()
+        .await
+        .unwrap();
+
+    for _ in 0..4 {
+        assert_eq!(respond(&client).await, "first\n");
+    }
+    assert_eq!(
+        client.provider().healthy_endpoints(),
+        [working.trim_start_matches("http://")]
+    );
+}
@@ -0,0 +1,30 @@
+//! Builds the HTTP provider and the core types without the optional
+//! features, see `cargo test --no-default-features --features http`
+#![cfg(all(feature = "http", not(target_arch = "wasm32")))]
+
+use superchain_client::{
+    provider::ChainProvider,
+    query::Bound,
+    requests::{blocks::GetBlocksRequest, logs::GetLogsRequest},
+    ChainId, ClientBuilder, Format, HttpProvider,
+};
+
+#[tokio::test]
+async fn builds_an_http_client_with_the_core_requests() {
+    let client = ClientBuilder::default()
+        .endpoint("http://localhost:8080")
+        .build::<HttpProvider>()
+        .await
+        .unwrap();
+
+    let blocks = GetBlocksRequest::default().last_n_blocks(10);
+    let logs = GetLogsRequest {
+        chains: [ChainId::ETH].into(),
+        from_block: Bound::Exact(17_000_000),
+        ..Default::default()
+    };
+
+    // the requests are only built, never sent
+    drop(client.get_blocks_by_format(blocks, Format::JsonStream, false));
+    drop(client.get_logs_by_format(logs, Format::JsonStream, false));
+}
@@ -0,0 +1,92 @@
+//! Every dataset is requested from its path of the HTTP API
+#![cfg(all(feature = "http", not(target_arch = "wasm32")))]
+
+mod common;
+
+use bytes::Bytes;
+use ethers_core::types::Address;
+use futures::TryStreamExt;
+use superchain_client::{
+    provider::{ChainProvider, Erc721Provider, StreamResponse},
+    query::Bound,
+    requests::logs::GetLogsRequest,
+    ChainId, Client, ClientBuilder, DecodeStream, Format, HttpProvider,
+};
+
+/// Answers every request with its target
+fn echo_target(request: &str) -> String {
+    format!("{{\"target\":\"{}\"}}\n", common::target(request))
+}
+
+async fn client() -> Client<HttpProvider> {
+    ClientBuilder::default()
+        .endpoint(common::mock_server(echo_target).await)
+        .build::<HttpProvider>()
+        .await
+        .unwrap()
+}
+
+/// Returns the path a response was requested from
+async fn path(response: StreamResponse<Bytes>) -> String {
+    let mut rows = response.decode_json::<serde_json::Value>().unwrap();
+    let row = rows.try_next().await.unwrap().unwrap();
+    let target = row["target"].as_str().unwrap();
+    target.split('?').next().unwrap().to_owned()
+}
+
+#[tokio::test]
+async fn erc721_transfers_are_requested_from_their_path() {
+    let client = client().await;
+
+    let response = client
+        .get_erc721_transfers_by_format(Default::default(), Format::JsonStream, false)
+        .await;
+
+    assert_eq!(path(response).await, "/v1/api/erc721/transfers");
+}
+
+#[tokio::test]
+async fn rendered_query_is_the_sent_query() {
+    let client = client().await;
+    let request = GetLogsRequest {
+        chains: [ChainId::ETH, ChainId::ARB].into(),
+        from_block: Bound::Exact(10),
+        to_block: Bound::Latest,
+        address__in: [Address::repeat_byte(1), Address::repeat_byte(2)].into(),
+        ..Default::default()
+    };
+    let rendered = request.to_string();
+
+    let response = client
+        .get_logs_by_format(request, Format::JsonStream, false)
+        .await
+        .unwrap();
+    let mut rows = response.decode_json::<serde_json::Value>().unwrap();
+    let row = rows.try_next().await.unwrap().unwrap();
+    let target = row["target"].as_str().unwrap();
+
+    let expected = format!("/v1/api/logs?{rendered}&format=json_stream");
+    assert_eq!(target, expected);
+}
+
+#[tokio::test]
+async fn withdrawals_are_requested_from_their_path() {
+    let client = client().await;
+
+    let response = client
+        .get_withdrawals_by_format(Default::default(), Format::JsonStream, false)
+        .await;
+
+    assert_eq!(path(response).await, "/v1/api/withdrawals");
+}
+
+#[tokio::test]
+async fn balances_are_requested_from_their_path() {
+    let client = client().await;
+
+    let response = client
+        .get_balances_by_format(Default::default(), Format::JsonStream, false)
+        .await;
+
+    assert_eq!(path(response).await, "/v1/api/balances");
+}
@@ -0,0 +1,64 @@
+//! The streams returned by the providers are `Send + 'static`, so they can
+//! be moved to and polled on another task
+#![cfg(all(feature = "http", not(target_arch = "wasm32")))]
+
+mod common;
+
+use futures::{Stream, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use superchain_client::{
+    core::decode::erase_item_type, provider::ChainProvider, requests::blocks::GetBlocksRequest,
+    ClientBuilder, DecodeStream, Format, HttpProvider, Result,
+};
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+struct Row {
+    number: u64,
+}
+
+fn respond(request: &str) -> String {
+    if common::target(request).starts_with("/v1/api/csv") {
+        "number\n1\n2\n".to_string()
+    } else {
+        "{\"number\":1}\n{\"number\":2}\n".to_string()
+    }
+}
+
+/// Collects the stream on a spawned task
+async fn collect_on_task<S, T>(stream: S) -> Vec<T>
+where
+    S: Stream<Item = Result<T>> + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::spawn(stream.try_collect()).await.unwrap().unwrap()
+}
+
+#[tokio::test]
+async fn returned_streams_can_be_moved_to_another_task() {
+    let endpoint = common::mock_server(respond).await;
+    let client = ClientBuilder::default()
+        .endpoint(endpoint)
+        .build::<HttpProvider>()
+        .await
+        .unwrap();
+    let blocks =
+        || client.get_blocks_by_format(GetBlocksRequest::default(), Format::JsonStream, false);
+
+    let bytes = collect_on_task(blocks().await.unwrap()).await;
+    assert!(!bytes.is_empty());
+
+    let rows: Vec<Row> = collect_on_task(blocks().await.decode_json().unwrap()).await;
+    assert_eq!(rows, [Row { number: 1 }, Row { number: 2 }]);
+
+    let csv = client
+        .provider()
+        .send_raw("csv", &(), Format::JsonStream)
+        .await
+        .decode_csv::<Row>()
+        .unwrap();
+    assert_eq!(collect_on_task(csv).await.len(), 2);
+
+    let typed = blocks().await.decode_json::<Row>().unwrap();
+    let erased = erase_item_type(typed).decode_json::<Row>().unwrap();
+    assert_eq!(collect_on_task(erased).await.len(), 2);
+}
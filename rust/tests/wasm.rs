@@ -0,0 +1,34 @@
+//! Builds the HTTP provider and decodes the core types on `wasm32`, which
+//! must not pull in a native-only dependency such as a native RNG, see
+//! `cargo test --target wasm32-unknown-unknown --no-default-features
+//! --features http --test wasm --no-run`
+#![cfg(all(feature = "http", target_arch = "wasm32"))]
+
+use superchain_client::{
+    core::types::ethereum::Block, requests::logs::GetLogsRequest, ChainId, ClientBuilder,
+    HttpProvider,
+};
+use wasm_bindgen_test::wasm_bindgen_test;
+
+#[wasm_bindgen_test]
+async fn builds_the_http_provider() {
+    let client = ClientBuilder::default()
+        .endpoint("app.superchain.network")
+        .credential("user", "pass")
+        .build::<HttpProvider>()
+        .await;
+
+    assert!(client.is_ok());
+}
+
+#[wasm_bindgen_test]
+fn decodes_a_block_and_renders_a_query() {
+    let block: Block = serde_json::from_str(r#"{"chain":1,"number":10}"#).unwrap();
+    assert_eq!((block.chain, block.number), (ChainId::ETH, 10));
+
+    let request = GetLogsRequest {
+        chains: [ChainId::ETH].into(),
+        ..Default::default()
+    };
+    assert!(request.to_query().unwrap().starts_with("chains=ETH"));
+}